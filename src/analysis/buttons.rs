@@ -0,0 +1,434 @@
+use core::fmt;
+
+use crate::{
+    constants::ButtonFlags,
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{Event, PacketEventData},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ButtonEventKind {
+    Pressed(ButtonFlags),
+    /// `held` is the seconds since the press
+    Released {
+        button: ButtonFlags,
+        held: f32,
+    },
+    /// A button still down, reported each time the clock advances while it's held
+    Held {
+        button: ButtonFlags,
+        held: f32,
+    },
+    /// A button held for the long press duration, reported once per press
+    LongPress {
+        button: ButtonFlags,
+        held: f32,
+    },
+    /// All buttons of a registered chord are held, after one of them was just pressed
+    Chord(ButtonFlags),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ButtonEvent {
+    pub session_time: f32,
+    pub kind: ButtonEventKind,
+}
+
+/// What a callback is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Trigger {
+    Press(ButtonFlags),
+    Release(ButtonFlags),
+    Held(ButtonFlags),
+    LongPress(ButtonFlags),
+    Chord(ButtonFlags),
+}
+
+impl Trigger {
+    /// Press of `UDP_ACTION_<number>`, `None` outside `1..=12`
+    pub fn udp_action(number: u8) -> Option<Trigger> {
+        ButtonFlags::udp_action(number).map(Trigger::Press)
+    }
+
+    fn matches(&self, kind: &ButtonEventKind) -> bool {
+        match (self, kind) {
+            (Trigger::Press(a), ButtonEventKind::Pressed(b)) => a == b,
+            (Trigger::Release(a), ButtonEventKind::Released { button, .. }) => a == button,
+            (Trigger::Held(a), ButtonEventKind::Held { button, .. }) => a == button,
+            (Trigger::LongPress(a), ButtonEventKind::LongPress { button, .. }) => a == button,
+            (Trigger::Chord(a), ButtonEventKind::Chord(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+type Callback = Box<dyn FnMut(&ButtonEvent) + Send>;
+
+#[derive(Debug, Clone, Copy)]
+struct HeldButton {
+    button: ButtonFlags,
+    since: f32,
+    /// Session time of the latest `Held` event, or the press
+    reported_at: f32,
+    long_press_reported: bool,
+}
+
+/// Turns the `BUTN` event's `button_status` masks into press, release, held, long press
+/// and chord events, and calls the callbacks bound to them.
+///
+/// The game only sends `BUTN` when the mask changes, so long presses are noticed on the
+/// next packet of any kind, with the header's session time as the clock. Held buttons are
+/// reported on each of those packets that moves the clock on. The UDP action
+/// buttons have to be assigned in the game's controls settings to be sent at all.
+pub struct ButtonTracker {
+    long_press: f32,
+    session_uid: Option<u64>,
+    session_time: f32,
+    status: ButtonFlags,
+    held: Vec<HeldButton>,
+    chords: Vec<ButtonFlags>,
+    bindings: Vec<(Trigger, Callback)>,
+}
+
+impl fmt::Debug for ButtonTracker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ButtonTracker")
+            .field("long_press", &self.long_press)
+            .field("session_uid", &self.session_uid)
+            .field("session_time", &self.session_time)
+            .field("status", &self.status)
+            .field("held", &self.held)
+            .field("chords", &self.chords)
+            .field(
+                "bindings",
+                &self.bindings.iter().map(|(t, _)| t).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Default for ButtonTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ButtonTracker {
+    /// Seconds a button is held for a long press
+    pub const DEFAULT_LONG_PRESS: f32 = 1.0;
+
+    pub fn new() -> Self {
+        Self::with_long_press(Self::DEFAULT_LONG_PRESS)
+    }
+
+    pub fn with_long_press(seconds: f32) -> Self {
+        Self {
+            long_press: seconds.max(0.0),
+            session_uid: None,
+            session_time: 0.0,
+            status: ButtonFlags::empty(),
+            held: Vec::new(),
+            chords: Vec::new(),
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Keeps the chords and bindings
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            self.session_uid = Some(session_uid);
+            self.session_time = 0.0;
+            self.status = ButtonFlags::empty();
+            self.held.clear();
+        }
+    }
+
+    /// Held durations restart from the time flashed back to
+    fn rewind_on_flashback(&mut self, session_time: f32) {
+        if session_time < self.session_time {
+            for held in &mut self.held {
+                held.since = held.since.min(session_time);
+                held.reported_at = held.reported_at.min(session_time);
+            }
+        }
+        self.session_time = session_time;
+    }
+
+    /// Reports `buttons` when all of them are held together
+    pub fn add_chord(&mut self, buttons: ButtonFlags) {
+        if buttons.bits().count_ones() > 1 && !self.chords.contains(&buttons) {
+            self.chords.push(buttons);
+        }
+    }
+
+    /// Calls `callback` for every event matching `trigger`, a chord trigger also registers
+    /// the chord
+    pub fn bind(&mut self, trigger: Trigger, callback: impl FnMut(&ButtonEvent) + Send + 'static) {
+        if let Trigger::Chord(buttons) = trigger {
+            self.add_chord(buttons);
+        }
+        self.bindings.push((trigger, Box::new(callback)));
+    }
+
+    pub fn unbind(&mut self, trigger: Trigger) {
+        self.bindings.retain(|(t, _)| *t != trigger);
+    }
+
+    pub fn push_event(&mut self, packet: &PacketEventData) -> Vec<ButtonEvent> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let mut events = Vec::new();
+        if let Some(Event::Buttons(buttons)) = packet.event() {
+            events = self.update(buttons.button_status());
+        }
+        events.extend(self.check_held());
+        self.dispatch(&events);
+        events
+    }
+
+    /// Advances the clock without a button change, to report held buttons and long presses
+    pub fn tick(&mut self, session_uid: u64, session_time: f32) -> Vec<ButtonEvent> {
+        self.reset_on_new_session(session_uid);
+        self.rewind_on_flashback(session_time);
+
+        let events = self.check_held();
+        self.dispatch(&events);
+        events
+    }
+
+    fn update(&mut self, status: ButtonFlags) -> Vec<ButtonEvent> {
+        let now = self.session_time;
+        let event = |kind| ButtonEvent {
+            session_time: now,
+            kind,
+        };
+        let pressed = status.difference(self.status);
+        let released = self.status.difference(status);
+        self.status = status;
+
+        let mut events = Vec::new();
+        for button in released.iter() {
+            if let Some(i) = self.held.iter().position(|h| h.button == button) {
+                let held = self.held.remove(i);
+                events.push(event(ButtonEventKind::Released {
+                    button,
+                    held: now - held.since,
+                }));
+            }
+        }
+        for button in pressed.iter() {
+            self.held.push(HeldButton {
+                button,
+                since: now,
+                reported_at: now,
+                long_press_reported: false,
+            });
+            events.push(event(ButtonEventKind::Pressed(button)));
+        }
+        for &chord in &self.chords {
+            if status.contains(chord) && pressed.intersects(chord) {
+                events.push(event(ButtonEventKind::Chord(chord)));
+            }
+        }
+        events
+    }
+
+    fn check_held(&mut self) -> Vec<ButtonEvent> {
+        let now = self.session_time;
+        let event = |kind| ButtonEvent {
+            session_time: now,
+            kind,
+        };
+
+        let mut events = Vec::new();
+        for h in &mut self.held {
+            let (button, held) = (h.button, now - h.since);
+            if now > h.reported_at {
+                h.reported_at = now;
+                events.push(event(ButtonEventKind::Held { button, held }));
+            }
+            if !h.long_press_reported && held >= self.long_press {
+                h.long_press_reported = true;
+                events.push(event(ButtonEventKind::LongPress { button, held }));
+            }
+        }
+        events
+    }
+
+    fn dispatch(&mut self, events: &[ButtonEvent]) {
+        for event in events {
+            for (trigger, callback) in &mut self.bindings {
+                if trigger.matches(&event.kind) {
+                    callback(event);
+                }
+            }
+        }
+    }
+
+    /// Buttons currently held
+    pub fn status(&self) -> ButtonFlags {
+        self.status
+    }
+
+    /// Seconds `button` has been held, `None` if it isn't
+    pub fn held_for(&self, button: ButtonFlags) -> Option<f32> {
+        self.held
+            .iter()
+            .find(|h| h.button == button)
+            .map(|h| self.session_time - h.since)
+    }
+}
+
+impl PacketConsumer for ButtonTracker {
+    type Event = ButtonEvent;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<ButtonEvent> {
+        match packet {
+            AnyRawPacket::Event(p) => self.push_event(p),
+            p => {
+                let header = p.header();
+                self.tick(header.session_uid, header.session_time)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+    use crate::raw::constants::event::BUTTON_STATUS_EVENT_CODE;
+
+    fn buttons(session_time: f32, status: ButtonFlags) -> PacketEventData {
+        PacketEventData::for_test(
+            1,
+            session_time,
+            BUTTON_STATUS_EVENT_CODE,
+            &status.bits().to_le_bytes(),
+        )
+    }
+
+    fn kinds(events: &[ButtonEvent]) -> Vec<ButtonEventKind> {
+        events.iter().map(|e| e.kind).collect()
+    }
+
+    const CROSS: ButtonFlags = ButtonFlags::CROSS_A;
+    const TRIANGLE: ButtonFlags = ButtonFlags::TRIANGLE_Y;
+
+    #[test]
+    fn presses_releases_and_chords() {
+        let mut tracker = ButtonTracker::new();
+        tracker.add_chord(CROSS | TRIANGLE);
+
+        let events = tracker.push_event(&buttons(1.0, CROSS));
+        assert_eq!(kinds(&events), [ButtonEventKind::Pressed(CROSS)]);
+
+        let events = tracker.push_event(&buttons(1.25, CROSS | TRIANGLE));
+        assert_eq!(
+            kinds(&events),
+            [
+                ButtonEventKind::Pressed(TRIANGLE),
+                ButtonEventKind::Chord(CROSS | TRIANGLE),
+                ButtonEventKind::Held {
+                    button: CROSS,
+                    held: 0.25,
+                },
+            ]
+        );
+
+        let events = tracker.push_event(&buttons(1.5, TRIANGLE));
+        assert_eq!(
+            kinds(&events)[0],
+            ButtonEventKind::Released {
+                button: CROSS,
+                held: 0.5,
+            }
+        );
+        assert_eq!(tracker.status(), TRIANGLE);
+        assert_eq!(tracker.held_for(TRIANGLE), Some(0.25));
+    }
+
+    #[test]
+    fn held_is_reported_while_down_and_long_press_once() {
+        let mut tracker = ButtonTracker::new();
+        tracker.push_event(&buttons(1.0, CROSS));
+
+        let held = |held| ButtonEventKind::Held {
+            button: CROSS,
+            held,
+        };
+        assert_eq!(kinds(&tracker.tick(1, 1.5)), [held(0.5)]);
+        assert!(tracker.tick(1, 1.5).is_empty());
+        assert_eq!(
+            kinds(&tracker.tick(1, 2.0)),
+            [
+                held(1.0),
+                ButtonEventKind::LongPress {
+                    button: CROSS,
+                    held: 1.0,
+                },
+            ]
+        );
+        assert_eq!(kinds(&tracker.tick(1, 2.5)), [held(1.5)]);
+
+        let events = tracker.push_event(&buttons(3.0, ButtonFlags::empty()));
+        assert_eq!(
+            kinds(&events),
+            [ButtonEventKind::Released {
+                button: CROSS,
+                held: 2.0,
+            }]
+        );
+        assert!(tracker.tick(1, 3.5).is_empty());
+    }
+
+    #[test]
+    fn flashback_restarts_held_durations() {
+        let mut tracker = ButtonTracker::new();
+        tracker.push_event(&buttons(5.0, CROSS));
+        tracker.tick(1, 5.5);
+
+        assert!(tracker.tick(1, 4.0).is_empty());
+        assert_eq!(
+            kinds(&tracker.tick(1, 4.5)),
+            [ButtonEventKind::Held {
+                button: CROSS,
+                held: 0.5,
+            }]
+        );
+    }
+
+    #[test]
+    fn bound_callbacks_are_called() {
+        let mut tracker = ButtonTracker::new();
+        let count = |trigger, tracker: &mut ButtonTracker| {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let counter = calls.clone();
+            tracker.bind(trigger, move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            });
+            calls
+        };
+        let action = ButtonFlags::udp_action(1).unwrap();
+        let pressed = count(Trigger::udp_action(1).unwrap(), &mut tracker);
+        let held = count(Trigger::Held(action), &mut tracker);
+        let long_press = count(Trigger::LongPress(action), &mut tracker);
+
+        tracker.push_event(&buttons(1.0, action));
+        for time in [1.5, 2.0, 2.5] {
+            tracker.tick(1, time);
+        }
+        tracker.unbind(Trigger::Held(action));
+        tracker.tick(1, 3.0);
+
+        assert_eq!(pressed.load(Ordering::Relaxed), 1);
+        assert_eq!(held.load(Ordering::Relaxed), 3);
+        assert_eq!(long_press.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,722 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::{
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{
+        CarDamageData, PacketCarDamageData, PacketParticipantsData, PacketSessionData,
+        constants::MAX_NUM_CARS,
+    },
+    roster::{DriverIdentity, Roster},
+};
+
+/// Part of the car whose damage is given as a percentage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DamagePart {
+    FrontLeftWing,
+    FrontRightWing,
+    RearWing,
+    Floor,
+    Diffuser,
+    Sidepod,
+    Gearbox,
+    Engine,
+}
+
+impl DamagePart {
+    pub const ALL: [DamagePart; 8] = [
+        DamagePart::FrontLeftWing,
+        DamagePart::FrontRightWing,
+        DamagePart::RearWing,
+        DamagePart::Floor,
+        DamagePart::Diffuser,
+        DamagePart::Sidepod,
+        DamagePart::Gearbox,
+        DamagePart::Engine,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DamagePart::FrontLeftWing => "front left wing",
+            DamagePart::FrontRightWing => "front right wing",
+            DamagePart::RearWing => "rear wing",
+            DamagePart::Floor => "floor",
+            DamagePart::Diffuser => "diffuser",
+            DamagePart::Sidepod => "sidepod",
+            DamagePart::Gearbox => "gearbox",
+            DamagePart::Engine => "engine",
+        }
+    }
+
+    /// Damage percentage
+    pub fn value(&self, damage: &CarDamageData) -> u8 {
+        match self {
+            DamagePart::FrontLeftWing => damage.front_left_wing_damage,
+            DamagePart::FrontRightWing => damage.front_right_wing_damage,
+            DamagePart::RearWing => damage.rear_wing_damage,
+            DamagePart::Floor => damage.floor_damage,
+            DamagePart::Diffuser => damage.diffuser_damage,
+            DamagePart::Sidepod => damage.sidepod_damage,
+            DamagePart::Gearbox => damage.gear_box_damage,
+            DamagePart::Engine => damage.engine_damage,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DamageFault {
+    Drs,
+    Ers,
+    EngineBlown,
+    EngineSeized,
+}
+
+impl DamageFault {
+    pub const ALL: [DamageFault; 4] = [
+        DamageFault::Drs,
+        DamageFault::Ers,
+        DamageFault::EngineBlown,
+        DamageFault::EngineSeized,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DamageFault::Drs => "DRS fault",
+            DamageFault::Ers => "ERS fault",
+            DamageFault::EngineBlown => "engine blown",
+            DamageFault::EngineSeized => "engine seized",
+        }
+    }
+
+    pub fn is_active(&self, damage: &CarDamageData) -> bool {
+        let flag = match self {
+            DamageFault::Drs => damage.drs_fault,
+            DamageFault::Ers => damage.ers_fault,
+            DamageFault::EngineBlown => damage.engine_blown,
+            DamageFault::EngineSeized => damage.engine_seized,
+        };
+        flag != 0
+    }
+}
+
+/// Power unit component, worn over the sessions it's used in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PowerUnitComponent {
+    /// Internal combustion engine
+    Ice,
+    /// Motor generator unit, heat
+    MguH,
+    /// Motor generator unit, kinetic
+    MguK,
+    /// Energy store
+    Es,
+    /// Control electronics
+    Ce,
+    /// Turbocharger
+    Tc,
+}
+
+impl PowerUnitComponent {
+    pub const ALL: [PowerUnitComponent; 6] = [
+        PowerUnitComponent::Ice,
+        PowerUnitComponent::MguH,
+        PowerUnitComponent::MguK,
+        PowerUnitComponent::Es,
+        PowerUnitComponent::Ce,
+        PowerUnitComponent::Tc,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerUnitComponent::Ice => "ICE",
+            PowerUnitComponent::MguH => "MGU-H",
+            PowerUnitComponent::MguK => "MGU-K",
+            PowerUnitComponent::Es => "ES",
+            PowerUnitComponent::Ce => "CE",
+            PowerUnitComponent::Tc => "TC",
+        }
+    }
+}
+
+/// Wear of each power unit component (percentage)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PowerUnitWear {
+    pub ice: u8,
+    pub mguh: u8,
+    pub mguk: u8,
+    pub es: u8,
+    pub ce: u8,
+    pub tc: u8,
+}
+
+impl PowerUnitWear {
+    pub fn from_damage(damage: &CarDamageData) -> Self {
+        Self {
+            ice: damage.engine_ice_wear,
+            mguh: damage.engine_mguh_wear,
+            mguk: damage.engine_mguk_wear,
+            es: damage.engine_es_wear,
+            ce: damage.engine_ce_wear,
+            tc: damage.engine_tc_wear,
+        }
+    }
+
+    pub fn get(&self, component: PowerUnitComponent) -> u8 {
+        match component {
+            PowerUnitComponent::Ice => self.ice,
+            PowerUnitComponent::MguH => self.mguh,
+            PowerUnitComponent::MguK => self.mguk,
+            PowerUnitComponent::Es => self.es,
+            PowerUnitComponent::Ce => self.ce,
+            PowerUnitComponent::Tc => self.tc,
+        }
+    }
+
+    /// Most worn component
+    pub fn worst(&self) -> (PowerUnitComponent, u8) {
+        PowerUnitComponent::ALL
+            .into_iter()
+            .map(|component| (component, self.get(component)))
+            .max_by_key(|&(_, wear)| wear)
+            .unwrap_or((PowerUnitComponent::Ice, 0))
+    }
+
+    /// Wear added since `earlier`, a fitted new component counts as no wear
+    pub fn since(&self, earlier: &PowerUnitWear) -> PowerUnitWear {
+        PowerUnitWear {
+            ice: self.ice.saturating_sub(earlier.ice),
+            mguh: self.mguh.saturating_sub(earlier.mguh),
+            mguk: self.mguk.saturating_sub(earlier.mguk),
+            es: self.es.saturating_sub(earlier.es),
+            ce: self.ce.saturating_sub(earlier.ce),
+            tc: self.tc.saturating_sub(earlier.tc),
+        }
+    }
+}
+
+/// Power unit wear of a car over one session of the weekend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SessionWear {
+    pub session_uid: u64,
+    pub start: PowerUnitWear,
+    pub end: PowerUnitWear,
+}
+
+/// When events are raised, all in percentage points
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DamageThresholds {
+    /// Increase between two packets taken as a sudden hit
+    pub jump: u8,
+    /// Damage levels reported when crossed upwards, ascending
+    pub levels: Vec<u8>,
+    /// Power unit wear reported when crossed upwards, ascending
+    pub wear_levels: Vec<u8>,
+}
+
+impl Default for DamageThresholds {
+    fn default() -> Self {
+        Self {
+            jump: 10,
+            levels: vec![25, 50, 75],
+            wear_levels: vec![50, 75, 90],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DamageEventKind {
+    /// Damage went up by at least the jump threshold since the previous packet
+    Jump {
+        part: DamagePart,
+        from: u8,
+        to: u8,
+    },
+    /// Damage reached one of the threshold levels
+    Threshold {
+        part: DamagePart,
+        level: u8,
+        value: u8,
+    },
+    /// Damage went down, e.g. a new front wing at a pit stop
+    Repaired {
+        part: DamagePart,
+        from: u8,
+        to: u8,
+    },
+    FaultRaised(DamageFault),
+    FaultCleared(DamageFault),
+    /// Power unit component wear reached one of the wear levels
+    Wear {
+        component: PowerUnitComponent,
+        level: u8,
+        value: u8,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DamageEvent {
+    pub session_time: f32,
+    pub car_idx: u8,
+    pub kind: DamageEventKind,
+}
+
+impl fmt::Display for DamageEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DamageEventKind::Jump { part, from, to } => {
+                write!(f, "{} damage {}% -> {}%", part.name(), from, to)
+            }
+            DamageEventKind::Threshold { part, level, value } => {
+                write!(f, "{} damage {}% (over {}%)", part.name(), value, level)
+            }
+            DamageEventKind::Repaired { part, from, to } => {
+                write!(f, "{} repaired {}% -> {}%", part.name(), from, to)
+            }
+            DamageEventKind::FaultRaised(fault) => write!(f, "{}", fault.name()),
+            DamageEventKind::FaultCleared(fault) => write!(f, "{} cleared", fault.name()),
+            DamageEventKind::Wear {
+                component,
+                level,
+                value,
+            } => write!(f, "{} wear {}% (over {}%)", component.name(), value, level),
+        }
+    }
+}
+
+/// Highest of `levels` at or below `value`
+fn reached(levels: &[u8], value: u8) -> Option<u8> {
+    levels.iter().copied().filter(|&l| l <= value).max()
+}
+
+/// Identifies a driver across the sessions of a weekend, as the roster matches them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum WeekendDriver {
+    Game(u8),
+    Human { network_id: u8, race_number: u8 },
+}
+
+impl WeekendDriver {
+    fn of(identity: &DriverIdentity) -> Self {
+        match identity.is_network_human() {
+            true => WeekendDriver::Human {
+                network_id: identity.network_id,
+                race_number: identity.race_number,
+            },
+            false => WeekendDriver::Game(identity.driver_id),
+        }
+    }
+}
+
+/// Damage and power unit wear of every car, with events when they change.
+///
+/// Damage is followed within a session. Power unit wear carries over the sessions of a
+/// weekend, as told by `weekend_link_identifier` of the session packet, so its history
+/// shows how much of each component a weekend used. Car indices can change between
+/// sessions, so the history is kept by driver, matched across sessions like the [`Roster`]
+/// does within one, and wear is only recorded for cars whose participant is known. Damage going down after a flashback
+/// is taken as the rewind, not a repair.
+#[derive(Debug, Clone)]
+pub struct DamageMonitor {
+    thresholds: DamageThresholds,
+    session_uid: Option<u64>,
+    session_time: f32,
+    damage: Option<[CarDamageData; MAX_NUM_CARS]>,
+    weekend_link_identifier: Option<u32>,
+    /// Drivers of the current session
+    roster: Roster,
+    wear: HashMap<WeekendDriver, Vec<SessionWear>>,
+}
+
+impl Default for DamageMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DamageMonitor {
+    pub fn new() -> Self {
+        Self::with_thresholds(DamageThresholds::default())
+    }
+
+    pub fn with_thresholds(thresholds: DamageThresholds) -> Self {
+        Self {
+            thresholds,
+            session_uid: None,
+            session_time: 0.0,
+            damage: None,
+            weekend_link_identifier: None,
+            roster: Roster::new(),
+            wear: HashMap::new(),
+        }
+    }
+
+    /// Keeps the power unit history, which lasts the weekend
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            self.session_uid = Some(session_uid);
+            self.session_time = 0.0;
+            self.damage = None;
+            self.roster = Roster::new();
+        }
+    }
+
+    fn rewind_on_flashback(&mut self, session_time: f32) {
+        if session_time < self.session_time {
+            self.damage = None;
+        }
+        self.session_time = session_time;
+    }
+
+    pub fn push_session(&mut self, packet: &PacketSessionData) {
+        self.reset_on_new_session(packet.header.session_uid);
+
+        let weekend = packet.weekend_link_identifier;
+        if self.weekend_link_identifier != Some(weekend) {
+            self.weekend_link_identifier = Some(weekend);
+            self.wear.clear();
+        }
+    }
+
+    pub fn push_participants(&mut self, packet: &PacketParticipantsData) {
+        self.reset_on_new_session(packet.header.session_uid);
+        self.roster.push_participants(packet);
+    }
+
+    pub fn push_car_damage(&mut self, packet: &PacketCarDamageData) -> Vec<DamageEvent> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let current = packet.car_damage_data;
+        let mut events = Vec::new();
+        if let Some(previous) = self.damage.replace(current) {
+            for (idx, (before, after)) in previous.iter().zip(current.iter()).enumerate() {
+                let mut push = |kind| {
+                    events.push(DamageEvent {
+                        session_time: header.session_time,
+                        car_idx: idx as u8,
+                        kind,
+                    })
+                };
+                self.compare_damage(before, after, &mut push);
+            }
+        }
+
+        for (idx, damage) in current.iter().enumerate() {
+            let Some(identity) = self.roster.identity(idx as u8) else {
+                continue;
+            };
+            let wear = PowerUnitWear::from_damage(damage);
+            let sessions = self.wear.entry(WeekendDriver::of(identity)).or_default();
+            match sessions.last_mut() {
+                Some(session) if session.session_uid == header.session_uid => session.end = wear,
+                _ => sessions.push(SessionWear {
+                    session_uid: header.session_uid,
+                    start: wear,
+                    end: wear,
+                }),
+            }
+        }
+        events
+    }
+
+    fn compare_damage(
+        &self,
+        before: &CarDamageData,
+        after: &CarDamageData,
+        push: &mut impl FnMut(DamageEventKind),
+    ) {
+        let thresholds = &self.thresholds;
+        for part in DamagePart::ALL {
+            let (from, to) = (part.value(before), part.value(after));
+            if to < from {
+                push(DamageEventKind::Repaired { part, from, to });
+                continue;
+            }
+            if to - from >= thresholds.jump.max(1) {
+                push(DamageEventKind::Jump { part, from, to });
+            }
+            if let Some(level) = reached(&thresholds.levels, to)
+                && reached(&thresholds.levels, from) != Some(level)
+            {
+                push(DamageEventKind::Threshold {
+                    part,
+                    level,
+                    value: to,
+                });
+            }
+        }
+
+        for fault in DamageFault::ALL {
+            match (fault.is_active(before), fault.is_active(after)) {
+                (false, true) => push(DamageEventKind::FaultRaised(fault)),
+                (true, false) => push(DamageEventKind::FaultCleared(fault)),
+                _ => {}
+            }
+        }
+
+        let (before, after) = (
+            PowerUnitWear::from_damage(before),
+            PowerUnitWear::from_damage(after),
+        );
+        for component in PowerUnitComponent::ALL {
+            let (from, to) = (before.get(component), after.get(component));
+            if let Some(level) = reached(&thresholds.wear_levels, to)
+                && to > from
+                && reached(&thresholds.wear_levels, from) != Some(level)
+            {
+                push(DamageEventKind::Wear {
+                    component,
+                    level,
+                    value: to,
+                });
+            }
+        }
+    }
+
+    /// Latest damage of a car
+    pub fn damage(&self, car_idx: u8) -> Option<&CarDamageData> {
+        self.damage.as_ref()?.get(car_idx as usize)
+    }
+
+    pub fn is_faulty(&self, car_idx: u8, fault: DamageFault) -> bool {
+        self.damage(car_idx).is_some_and(|d| fault.is_active(d))
+    }
+
+    /// Driver of a car in the current session
+    pub fn driver(&self, car_idx: u8) -> Option<&DriverIdentity> {
+        self.roster.identity(car_idx)
+    }
+
+    fn sessions(&self, car_idx: u8) -> Option<&Vec<SessionWear>> {
+        self.wear.get(&WeekendDriver::of(self.driver(car_idx)?))
+    }
+
+    /// Latest power unit wear of a car
+    pub fn power_unit_wear(&self, car_idx: u8) -> Option<PowerUnitWear> {
+        Some(self.sessions(car_idx)?.last()?.end)
+    }
+
+    /// Power unit wear of the driver of a car in each session of the weekend seen so far
+    pub fn power_unit_history(&self, car_idx: u8) -> &[SessionWear] {
+        self.sessions(car_idx).map_or(&[], |w| w.as_slice())
+    }
+
+    /// Wear added over the weekend so far
+    pub fn weekend_wear(&self, car_idx: u8) -> Option<PowerUnitWear> {
+        let sessions = self.sessions(car_idx)?;
+        let (first, last) = (sessions.first()?, sessions.last()?);
+        Some(last.end.since(&first.start))
+    }
+
+    /// Components of a car at or above `limit` percent wear, most worn first
+    pub fn components_near_limit(&self, car_idx: u8, limit: u8) -> Vec<(PowerUnitComponent, u8)> {
+        let Some(wear) = self.power_unit_wear(car_idx) else {
+            return Vec::new();
+        };
+        let mut components: Vec<_> = PowerUnitComponent::ALL
+            .into_iter()
+            .map(|component| (component, wear.get(component)))
+            .filter(|&(_, value)| value >= limit)
+            .collect();
+        components.sort_by_key(|&(_, value)| std::cmp::Reverse(value));
+        components
+    }
+
+    /// Sessions left before a component reaches `limit` percent, at the average wear per
+    /// session of this weekend. `None` without any wear to go by.
+    pub fn sessions_until_limit(
+        &self,
+        car_idx: u8,
+        component: PowerUnitComponent,
+        limit: u8,
+    ) -> Option<f32> {
+        let sessions = self.sessions(car_idx)?;
+        let added = self.weekend_wear(car_idx)?.get(component);
+        if added == 0 {
+            return None;
+        }
+        let per_session = added as f32 / sessions.len() as f32;
+        let current = sessions.last()?.end.get(component);
+        Some(limit.saturating_sub(current) as f32 / per_session)
+    }
+}
+
+impl PacketConsumer for DamageMonitor {
+    type Event = DamageEvent;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<DamageEvent> {
+        match packet {
+            AnyRawPacket::Session(p) => {
+                self.push_session(p);
+                Vec::new()
+            }
+            AnyRawPacket::Participants(p) => {
+                self.push_participants(p);
+                Vec::new()
+            }
+            AnyRawPacket::CarDamage(p) => self.push_car_damage(p),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn damage(session_uid: u64, session_time: f32, cars: &[(u8, u8)]) -> PacketCarDamageData {
+        let mut packet: PacketCarDamageData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        packet.header.session_time = session_time;
+        for (data, &(front_left_wing, ice)) in packet.car_damage_data.iter_mut().zip(cars) {
+            data.front_left_wing_damage = front_left_wing;
+            data.engine_ice_wear = ice;
+        }
+        packet
+    }
+
+    fn participants(session_uid: u64, driver_ids: &[u8]) -> PacketParticipantsData {
+        let mut packet: PacketParticipantsData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        packet.num_active_cars = driver_ids.len() as u8;
+        for (participant, &driver_id) in packet.participants.iter_mut().zip(driver_ids) {
+            participant.driver_id = driver_id;
+        }
+        packet
+    }
+
+    fn session(session_uid: u64, weekend: u32) -> PacketSessionData {
+        let mut packet: PacketSessionData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        packet.weekend_link_identifier = weekend;
+        packet
+    }
+
+    fn kinds(events: &[DamageEvent]) -> Vec<DamageEventKind> {
+        events.iter().map(|e| e.kind).collect()
+    }
+
+    #[test]
+    fn damage_jumps_thresholds_and_repairs() {
+        let mut monitor = DamageMonitor::new();
+        assert!(
+            monitor
+                .push_car_damage(&damage(1, 1.0, &[(0, 0)]))
+                .is_empty()
+        );
+
+        let events = monitor.push_car_damage(&damage(1, 2.0, &[(30, 0)]));
+        let part = DamagePart::FrontLeftWing;
+        assert_eq!(
+            kinds(&events),
+            [
+                DamageEventKind::Jump {
+                    part,
+                    from: 0,
+                    to: 30
+                },
+                DamageEventKind::Threshold {
+                    part,
+                    level: 25,
+                    value: 30
+                },
+            ]
+        );
+
+        let mut faulty = damage(1, 3.0, &[(30, 0)]);
+        faulty.car_damage_data[0].drs_fault = 1;
+        let events = monitor.push_car_damage(&faulty);
+        assert_eq!(
+            kinds(&events),
+            [DamageEventKind::FaultRaised(DamageFault::Drs)]
+        );
+        assert!(monitor.is_faulty(0, DamageFault::Drs));
+
+        let events = monitor.push_car_damage(&damage(1, 4.0, &[(0, 0)]));
+        assert_eq!(
+            kinds(&events),
+            [
+                DamageEventKind::Repaired {
+                    part,
+                    from: 30,
+                    to: 0
+                },
+                DamageEventKind::FaultCleared(DamageFault::Drs),
+            ]
+        );
+    }
+
+    #[test]
+    fn flashback_is_not_a_repair() {
+        let mut monitor = DamageMonitor::new();
+        monitor.push_car_damage(&damage(1, 1.0, &[(0, 0)]));
+        monitor.push_car_damage(&damage(1, 2.0, &[(30, 0)]));
+
+        assert!(
+            monitor
+                .push_car_damage(&damage(1, 1.5, &[(0, 0)]))
+                .is_empty()
+        );
+        assert_eq!(monitor.damage(0).map(|d| d.front_left_wing_damage), Some(0));
+    }
+
+    #[test]
+    fn wear_follows_drivers_across_car_indices() {
+        let mut monitor = DamageMonitor::new();
+        monitor.push_session(&session(1, 9));
+        monitor.push_participants(&participants(1, &[0, 3]));
+        monitor.push_car_damage(&damage(1, 1.0, &[(0, 10), (0, 40)]));
+        let events = monitor.push_car_damage(&damage(1, 2.0, &[(0, 20), (0, 52)]));
+        assert_eq!(
+            kinds(&events),
+            [DamageEventKind::Wear {
+                component: PowerUnitComponent::Ice,
+                level: 50,
+                value: 52,
+            }]
+        );
+
+        // The drivers swap car indices in the next session of the weekend
+        monitor.push_session(&session(2, 9));
+        monitor.push_participants(&participants(2, &[3, 0]));
+        monitor.push_car_damage(&damage(2, 1.0, &[(0, 52), (0, 20)]));
+        monitor.push_car_damage(&damage(2, 2.0, &[(0, 60), (0, 30)]));
+
+        assert_eq!(monitor.driver(1).map(|d| d.driver_id), Some(0));
+        assert_eq!(monitor.power_unit_history(1).len(), 2);
+        assert_eq!(monitor.weekend_wear(1).map(|w| w.ice), Some(20));
+        assert_eq!(monitor.weekend_wear(0).map(|w| w.ice), Some(20));
+        assert_eq!(
+            monitor.sessions_until_limit(1, PowerUnitComponent::Ice, 50),
+            Some(2.0)
+        );
+        assert_eq!(
+            monitor.components_near_limit(0, 50),
+            [(PowerUnitComponent::Ice, 60)]
+        );
+    }
+
+    #[test]
+    fn wear_needs_participants_and_resets_each_weekend() {
+        let mut monitor = DamageMonitor::new();
+        monitor.push_session(&session(1, 9));
+        monitor.push_car_damage(&damage(1, 1.0, &[(0, 10)]));
+        assert_eq!(monitor.power_unit_wear(0), None);
+
+        monitor.push_participants(&participants(1, &[0]));
+        monitor.push_car_damage(&damage(1, 2.0, &[(0, 10)]));
+        assert_eq!(monitor.power_unit_wear(0).map(|w| w.ice), Some(10));
+
+        monitor.push_session(&session(2, 10));
+        monitor.push_participants(&participants(2, &[0]));
+        assert!(monitor.power_unit_history(0).is_empty());
+    }
+}
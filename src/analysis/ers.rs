@@ -0,0 +1,409 @@
+use crate::{
+    constants::ErsDeployMode,
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{PacketCarStatusData, PacketLapData, constants::MAX_NUM_CARS},
+};
+
+/// Number of [`ErsDeployMode`]s
+const NUM_DEPLOY_MODES: usize = 4;
+
+/// Energy flow over one stretch of track, in Joules
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ErsBucket {
+    pub deployed: f32,
+    /// MGU-K and MGU-H combined
+    pub harvested: f32,
+    /// Change of the energy store, negative when energy was spent
+    pub store_delta: f32,
+}
+
+impl ErsBucket {
+    pub fn net(&self) -> f32 {
+        self.harvested - self.deployed
+    }
+}
+
+/// Energy balance of a completed lap
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErsLap {
+    pub car_idx: u8,
+    pub lap_num: u8,
+    /// Energy store at the start and end of the lap (J)
+    pub store_at_start: f32,
+    pub store_at_end: f32,
+    pub deployed: f32,
+    pub harvested_mguk: f32,
+    pub harvested_mguh: f32,
+    /// Metres driven in each [`ErsDeployMode`], indexed by its id
+    pub deploy_mode_distance: [f32; NUM_DEPLOY_MODES],
+    /// Length of each bucket of `buckets` (m)
+    pub bucket_length: f32,
+    /// Deployment map, bucket `i` covers `i * bucket_length` to `(i + 1) * bucket_length`
+    pub buckets: Vec<ErsBucket>,
+}
+
+impl ErsLap {
+    pub fn harvested(&self) -> f32 {
+        self.harvested_mguk + self.harvested_mguh
+    }
+
+    /// Harvested minus deployed energy
+    pub fn net(&self) -> f32 {
+        self.harvested() - self.deployed
+    }
+
+    /// Per bucket difference of this lap minus `other`, e.g. where this lap deployed more.
+    /// Both laps must use the same bucket length.
+    pub fn compare(&self, other: &ErsLap) -> Vec<ErsBucket> {
+        let len = self.buckets.len().max(other.buckets.len());
+        let bucket = |lap: &ErsLap, i: usize| lap.buckets.get(i).copied().unwrap_or_default();
+
+        (0..len)
+            .map(|i| {
+                let (a, b) = (bucket(self, i), bucket(other, i));
+                ErsBucket {
+                    deployed: a.deployed - b.deployed,
+                    harvested: a.harvested - b.harvested,
+                    store_delta: a.store_delta - b.store_delta,
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ErsSample {
+    store: f32,
+    deployed: f32,
+    harvested_mguk: f32,
+    harvested_mguh: f32,
+    deploy_mode: Option<ErsDeployMode>,
+    lap_distance: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CarErsState {
+    lap_num: u8,
+    lap_distance: f32,
+    last_sample: Option<ErsSample>,
+    /// The lap in progress has been followed since its start
+    tracking: bool,
+    store_at_start: f32,
+    totals: ErsSample,
+    deploy_mode_distance: [f32; NUM_DEPLOY_MODES],
+    buckets: Vec<ErsBucket>,
+    laps: Vec<ErsLap>,
+}
+
+impl CarErsState {
+    fn start_lap(&mut self) {
+        self.tracking = self.last_sample.is_some();
+        self.store_at_start = self.last_sample.map_or(0.0, |s| s.store);
+        self.totals = ErsSample::default();
+        self.deploy_mode_distance = Default::default();
+        self.buckets.clear();
+    }
+}
+
+/// Per lap ERS energy balances and deployment maps.
+///
+/// The `*_this_lap` counters of the car status packets are differentiated between samples
+/// and binned by `lap_distance` from the lap packets, so the map shows where on track
+/// energy was deployed and harvested. A counter going down is taken as its reset at the
+/// line. Laps interrupted by a flashback, or joined part way, are not reported.
+#[derive(Debug, Clone)]
+pub struct ErsAnalyser {
+    session_uid: Option<u64>,
+    session_time: f32,
+    bucket_length: f32,
+    cars: [CarErsState; MAX_NUM_CARS],
+}
+
+impl Default for ErsAnalyser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ErsAnalyser {
+    pub const DEFAULT_BUCKET_LENGTH: f32 = 100.0;
+
+    pub fn new() -> Self {
+        Self::with_bucket_length(Self::DEFAULT_BUCKET_LENGTH)
+    }
+
+    /// Deployment map resolution in metres
+    pub fn with_bucket_length(metres: f32) -> Self {
+        Self {
+            session_uid: None,
+            session_time: 0.0,
+            bucket_length: metres.max(1.0),
+            cars: Default::default(),
+        }
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::with_bucket_length(self.bucket_length);
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    fn rewind_on_flashback(&mut self, session_time: f32) {
+        if session_time < self.session_time {
+            for car in &mut self.cars {
+                car.tracking = false;
+                car.last_sample = None;
+            }
+        }
+        self.session_time = session_time;
+    }
+
+    /// Completed laps of a car, in lap order
+    pub fn laps(&self, car_idx: u8) -> &[ErsLap] {
+        self.cars
+            .get(car_idx as usize)
+            .map_or(&[], |car| car.laps.as_slice())
+    }
+
+    pub fn lap(&self, car_idx: u8, lap_num: u8) -> Option<&ErsLap> {
+        self.laps(car_idx).iter().find(|lap| lap.lap_num == lap_num)
+    }
+
+    /// Deployment map of `lap_num` minus that of `other_lap_num`, see [`ErsLap::compare`]
+    pub fn compare(&self, car_idx: u8, lap_num: u8, other_lap_num: u8) -> Option<Vec<ErsBucket>> {
+        Some(
+            self.lap(car_idx, lap_num)?
+                .compare(self.lap(car_idx, other_lap_num)?),
+        )
+    }
+
+    pub fn push_car_status(&mut self, packet: &PacketCarStatusData) {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let statuses = packet.car_status_data;
+        for (car, status) in self.cars.iter_mut().zip(statuses.iter()) {
+            let sample = ErsSample {
+                store: status.ers_store_energy,
+                deployed: status.ers_deployed_this_lap,
+                harvested_mguk: status.ers_harvested_this_lap_mguk,
+                harvested_mguh: status.ers_harvested_this_lap_mguh,
+                deploy_mode: status.ers_deploy_mode(),
+                lap_distance: car.lap_distance,
+            };
+
+            let Some(last) = car.last_sample.replace(sample) else {
+                continue;
+            };
+            if !car.tracking {
+                continue;
+            }
+
+            let counter = |now: f32, before: f32| match now >= before {
+                true => now - before,
+                false => now,
+            };
+            let deployed = counter(sample.deployed, last.deployed);
+            let mguk = counter(sample.harvested_mguk, last.harvested_mguk);
+            let mguh = counter(sample.harvested_mguh, last.harvested_mguh);
+
+            car.totals.deployed += deployed;
+            car.totals.harvested_mguk += mguk;
+            car.totals.harvested_mguh += mguh;
+
+            let distance = sample.lap_distance - last.lap_distance;
+            if distance > 0.0
+                && let Some(mode) = last.deploy_mode
+                && let Some(mode) = car.deploy_mode_distance.get_mut(mode.id() as usize)
+            {
+                *mode += distance;
+            }
+
+            let idx = (sample.lap_distance.max(0.0) / self.bucket_length) as usize;
+            if car.buckets.len() <= idx {
+                car.buckets.resize(idx + 1, ErsBucket::default());
+            }
+            let bucket = &mut car.buckets[idx];
+            bucket.deployed += deployed;
+            bucket.harvested += mguk + mguh;
+            bucket.store_delta += sample.store - last.store;
+        }
+    }
+
+    /// Returns the laps completed by this packet
+    pub fn push_lap_data(&mut self, packet: &PacketLapData) -> Vec<ErsLap> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let mut laps = Vec::new();
+        let lap_data = packet.lap_data;
+        for (idx, (car, lap)) in self.cars.iter_mut().zip(lap_data.iter()).enumerate() {
+            car.lap_distance = lap.lap_distance;
+
+            let lap_num = lap.current_lap_num;
+            if lap_num < car.lap_num {
+                car.laps.retain(|ers| ers.lap_num < lap_num);
+                car.tracking = false;
+            } else if lap_num > car.lap_num {
+                if car.tracking
+                    && let Some(last) = car.last_sample
+                {
+                    let ers = ErsLap {
+                        car_idx: idx as u8,
+                        lap_num: car.lap_num,
+                        store_at_start: car.store_at_start,
+                        store_at_end: last.store,
+                        deployed: car.totals.deployed,
+                        harvested_mguk: car.totals.harvested_mguk,
+                        harvested_mguh: car.totals.harvested_mguh,
+                        deploy_mode_distance: car.deploy_mode_distance,
+                        bucket_length: self.bucket_length,
+                        buckets: std::mem::take(&mut car.buckets),
+                    };
+                    car.laps.push(ers.clone());
+                    laps.push(ers);
+                }
+                if car.lap_num > 0 {
+                    car.start_lap();
+                }
+            }
+            car.lap_num = lap_num;
+        }
+
+        laps
+    }
+}
+
+impl PacketConsumer for ErsAnalyser {
+    type Event = ErsLap;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<ErsLap> {
+        match packet {
+            AnyRawPacket::CarStatus(p) => {
+                self.push_car_status(p);
+                Vec::new()
+            }
+            AnyRawPacket::Lap(p) => self.push_lap_data(p),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Feed {
+        analyser: ErsAnalyser,
+        session_time: f32,
+    }
+
+    impl Feed {
+        fn new() -> Self {
+            Self {
+                analyser: ErsAnalyser::new(),
+                session_time: 0.0,
+            }
+        }
+
+        /// Car 0 at `lap_distance` into `lap_num` with `(deployed, mguk)` this lap so far
+        fn sample(
+            &mut self,
+            dt: f32,
+            lap_num: u8,
+            lap_distance: f32,
+            (deployed, mguk): (f32, f32),
+            deploy_mode: ErsDeployMode,
+        ) -> Vec<ErsLap> {
+            self.session_time += dt;
+
+            let mut lap: PacketLapData = bytemuck::Zeroable::zeroed();
+            lap.header.session_uid = 1;
+            lap.header.session_time = self.session_time;
+            lap.lap_data[0].current_lap_num = lap_num;
+            lap.lap_data[0].lap_distance = lap_distance;
+            let laps = self.analyser.push_lap_data(&lap);
+
+            let mut status: PacketCarStatusData = bytemuck::Zeroable::zeroed();
+            status.header.session_uid = 1;
+            status.header.session_time = self.session_time;
+            let car = &mut status.car_status_data[0];
+            car.ers_store_energy = 4_000_000.0 - deployed + mguk;
+            car.ers_deployed_this_lap = deployed;
+            car.ers_harvested_this_lap_mguk = mguk;
+            car.ers_deploy_mode = deploy_mode.id();
+            self.analyser.push_car_status(&status);
+            laps
+        }
+
+        /// A full lap `lap_num` deploying `deploy` J in each 100 m up to 300 m
+        fn lap(&mut self, lap_num: u8, deploy: f32) {
+            self.sample(1.0, lap_num, 0.0, (0.0, 0.0), ErsDeployMode::Medium);
+            for i in 1..=3 {
+                let distance = i as f32 * 100.0 - 50.0;
+                self.sample(
+                    1.0,
+                    lap_num,
+                    distance,
+                    (deploy * i as f32, 100.0 * i as f32),
+                    ErsDeployMode::Medium,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reports_the_balance_and_map_of_laps_followed_from_the_line() {
+        let mut feed = Feed::new();
+        feed.sample(1.0, 1, 200.0, (5000.0, 0.0), ErsDeployMode::Hotlap);
+        feed.lap(2, 1000.0);
+        let laps = feed.sample(1.0, 3, 10.0, (0.0, 0.0), ErsDeployMode::Medium);
+
+        assert_eq!(laps.len(), 1);
+        let lap = &laps[0];
+        assert_eq!(lap.lap_num, 2);
+        assert_eq!(lap.deployed, 3000.0);
+        assert_eq!(lap.harvested(), 300.0);
+        assert_eq!(lap.net(), -2700.0);
+        assert_eq!(lap.store_at_start, 3_995_000.0);
+        assert_eq!(lap.store_at_end, 3_997_300.0);
+        assert_eq!(lap.deploy_mode_distance, [0.0, 250.0, 0.0, 0.0]);
+
+        let deployed: Vec<_> = lap.buckets.iter().map(|b| b.deployed).collect();
+        assert_eq!(deployed, [1000.0, 1000.0, 1000.0]);
+        assert_eq!(feed.analyser.laps(0), laps.as_slice());
+    }
+
+    #[test]
+    fn skips_laps_interrupted_by_a_flashback() {
+        let mut feed = Feed::new();
+        feed.sample(1.0, 1, 200.0, (0.0, 0.0), ErsDeployMode::Medium);
+        feed.lap(2, 1000.0);
+        feed.sample(-2.0, 2, 150.0, (1500.0, 150.0), ErsDeployMode::Medium);
+        assert!(
+            feed.sample(2.0, 3, 10.0, (0.0, 0.0), ErsDeployMode::Medium)
+                .is_empty()
+        );
+
+        feed.lap(3, 500.0);
+        let laps = feed.sample(1.0, 4, 10.0, (0.0, 0.0), ErsDeployMode::Medium);
+        assert_eq!(laps[0].lap_num, 3);
+    }
+
+    #[test]
+    fn compares_deployment_maps() {
+        let mut feed = Feed::new();
+        feed.sample(1.0, 1, 200.0, (0.0, 0.0), ErsDeployMode::Medium);
+        feed.lap(2, 1000.0);
+        feed.lap(3, 1200.0);
+        feed.sample(1.0, 4, 10.0, (0.0, 0.0), ErsDeployMode::Medium);
+
+        let diff = feed.analyser.compare(0, 3, 2).unwrap();
+        let deployed: Vec<_> = diff.iter().map(|b| b.deployed).collect();
+        assert_eq!(deployed, [200.0, 200.0, 200.0]);
+        assert!(feed.analyser.compare(0, 3, 7).is_none());
+    }
+}
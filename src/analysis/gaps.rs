@@ -0,0 +1,339 @@
+use std::time::Duration;
+
+use crate::{
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{PacketLapData, PacketSessionData, constants::MAX_NUM_CARS},
+};
+
+/// Live timing of one car relative to the cars ahead
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CarGap {
+    pub car_idx: u8,
+    pub position: u8,
+    /// Time since the leader passed this car's current position, `None` when
+    /// the leader's trace doesn't cover it (e.g. joined mid-session)
+    pub gap_to_leader: Option<Duration>,
+    /// Same as `gap_to_leader`, relative to the car one position ahead
+    pub interval: Option<Duration>,
+    pub laps_behind_leader: u32,
+    pub laps_behind_car_ahead: u32,
+}
+
+/// Session times at which a car passed each checkpoint
+#[derive(Debug, Clone, Default)]
+struct CarTrace {
+    /// Index of the first recorded checkpoint
+    first: usize,
+    times: Vec<f32>,
+    /// Latest (session_time, total_distance) sample
+    last: Option<(f32, f32)>,
+}
+
+impl CarTrace {
+    fn record(&mut self, time: f32, distance: f32, spacing: f32) {
+        let Some((last_time, last_distance)) = self.last.replace((time, distance)) else {
+            if self.times.is_empty() {
+                self.first = (distance.max(0.0) / spacing).ceil() as usize;
+            }
+            return;
+        };
+
+        if distance < last_distance {
+            // Reset to track or flashback, forget the checkpoints not yet passed again
+            let passed = (distance.max(0.0) / spacing).floor() as usize + 1;
+            self.times.truncate(passed.saturating_sub(self.first));
+            return;
+        }
+
+        loop {
+            let checkpoint = self.first + self.times.len();
+            let at = checkpoint as f32 * spacing;
+            if at > distance {
+                break;
+            }
+            if at < last_distance {
+                // Joined or resumed past this checkpoint, restart the trace here
+                self.first = (last_distance / spacing).ceil() as usize;
+                self.times.clear();
+                continue;
+            }
+
+            let fraction = (at - last_distance) / (distance - last_distance).max(f32::EPSILON);
+            self.times.push(last_time + (time - last_time) * fraction);
+        }
+    }
+
+    /// Session time at which this car was at `distance`
+    fn time_at(&self, distance: f32, spacing: f32) -> Option<f32> {
+        let (last_time, last_distance) = self.last?;
+        if distance > last_distance || distance < self.first as f32 * spacing {
+            return None;
+        }
+
+        let k = (distance / spacing).floor() as usize;
+        let i = k.checked_sub(self.first)?;
+        let t0 = *self.times.get(i)?;
+        let d0 = k as f32 * spacing;
+        let (t1, d1) = match self.times.get(i + 1) {
+            Some(&t1) => (t1, d0 + spacing),
+            None => (last_time, last_distance),
+        };
+
+        let fraction = (distance - d0) / (d1 - d0).max(f32::EPSILON);
+        Some(t0 + (t1 - t0) * fraction.clamp(0.0, 1.0))
+    }
+
+    fn rewind(&mut self, time: f32) {
+        while self.times.last().is_some_and(|&t| t > time) {
+            self.times.pop();
+        }
+        self.last = None;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CarSample {
+    car_idx: u8,
+    position: u8,
+    lap_num: u8,
+    lap_distance: f32,
+    total_distance: f32,
+}
+
+/// Continuous gap and interval computation from track position.
+///
+/// The game's `delta_to_car_in_front` only updates at the timing lines. This records
+/// when each car passes checkpoints spaced evenly along `total_distance`, and measures
+/// each car's gap as the time since the car ahead passed the same point on track.
+/// Lapped cars get a full-distance time gap plus the number of laps they are down.
+#[derive(Debug, Clone)]
+pub struct GapTracker {
+    spacing: f32,
+    session_uid: Option<u64>,
+    session_time: f32,
+    track_length: Option<f32>,
+    traces: [CarTrace; MAX_NUM_CARS],
+    gaps: Vec<CarGap>,
+}
+
+impl Default for GapTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GapTracker {
+    pub const DEFAULT_CHECKPOINT_SPACING: f32 = 50.0;
+
+    pub fn new() -> Self {
+        Self::with_checkpoint_spacing(Self::DEFAULT_CHECKPOINT_SPACING)
+    }
+
+    /// Checkpoint spacing in metres, smaller spacing gives smoother gaps at a memory cost
+    pub fn with_checkpoint_spacing(metres: f32) -> Self {
+        Self {
+            spacing: metres.max(1.0),
+            session_uid: None,
+            session_time: 0.0,
+            track_length: None,
+            traces: Default::default(),
+            gaps: Vec::new(),
+        }
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::with_checkpoint_spacing(self.spacing);
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    /// Latest gaps in race order
+    pub fn gaps(&self) -> &[CarGap] {
+        &self.gaps
+    }
+
+    pub fn gap(&self, car_idx: u8) -> Option<&CarGap> {
+        self.gaps.iter().find(|g| g.car_idx == car_idx)
+    }
+
+    pub fn push_session(&mut self, packet: &PacketSessionData) {
+        self.reset_on_new_session(packet.header.session_uid);
+        self.track_length = Some(packet.track_length as f32).filter(|&l| l > 0.0);
+    }
+
+    pub fn push_lap_data(&mut self, packet: &PacketLapData) -> &[CarGap] {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+
+        let now = header.session_time;
+        if now < self.session_time {
+            for trace in &mut self.traces {
+                trace.rewind(now);
+            }
+        }
+        self.session_time = now;
+
+        let lap_data = packet.lap_data;
+        let mut order = Vec::with_capacity(MAX_NUM_CARS);
+        for (idx, (trace, lap)) in self.traces.iter_mut().zip(lap_data.iter()).enumerate() {
+            let participating = lap
+                .result_status()
+                .is_some_and(|status| status.is_participating());
+            if !participating || lap.car_position == 0 {
+                continue;
+            }
+
+            trace.record(now, lap.total_distance, self.spacing);
+            order.push(CarSample {
+                car_idx: idx as u8,
+                position: lap.car_position,
+                lap_num: lap.current_lap_num,
+                lap_distance: lap.lap_distance,
+                total_distance: lap.total_distance,
+            });
+        }
+        order.sort_by_key(|car| car.position);
+
+        self.gaps = order
+            .iter()
+            .enumerate()
+            .map(|(i, car)| {
+                let leader = &order[0];
+                let ahead = &order[i.saturating_sub(1)];
+                CarGap {
+                    car_idx: car.car_idx,
+                    position: car.position,
+                    gap_to_leader: self.time_behind(leader, car, now),
+                    interval: self.time_behind(ahead, car, now),
+                    laps_behind_leader: self.laps_behind(leader, car),
+                    laps_behind_car_ahead: self.laps_behind(ahead, car),
+                }
+            })
+            .collect();
+
+        &self.gaps
+    }
+
+    fn time_behind(&self, ahead: &CarSample, car: &CarSample, now: f32) -> Option<Duration> {
+        if ahead.car_idx == car.car_idx {
+            return Some(Duration::ZERO);
+        }
+
+        let passed =
+            self.traces[ahead.car_idx as usize].time_at(car.total_distance, self.spacing)?;
+        Duration::try_from_secs_f32((now - passed).max(0.0)).ok()
+    }
+
+    fn laps_behind(&self, ahead: &CarSample, car: &CarSample) -> u32 {
+        match self.track_length {
+            Some(length) => ((ahead.total_distance - car.total_distance) / length).max(0.0) as u32,
+            None => {
+                let laps = ahead.lap_num.saturating_sub(car.lap_num) as u32;
+                laps.saturating_sub((car.lap_distance > ahead.lap_distance) as u32)
+            }
+        }
+    }
+}
+
+impl PacketConsumer for GapTracker {
+    type Event = CarGap;
+
+    /// Returns the updated gaps of every car after each lap packet
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<CarGap> {
+        match packet {
+            AnyRawPacket::Session(p) => {
+                self.push_session(p);
+                Vec::new()
+            }
+            AnyRawPacket::Lap(p) => self.push_lap_data(p).to_vec(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(position, lap_num, total_distance)` per car
+    fn lap_packet(session_time: f32, cars: &[(u8, u8, f32)]) -> PacketLapData {
+        let mut packet: PacketLapData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.header.session_time = session_time;
+        for (lap, &(position, lap_num, total_distance)) in packet.lap_data.iter_mut().zip(cars) {
+            lap.car_position = position;
+            lap.current_lap_num = lap_num;
+            lap.total_distance = total_distance;
+            lap.lap_distance = total_distance % 1000.0;
+            lap.result_status = 2;
+        }
+        packet
+    }
+
+    fn secs(gap: Option<Duration>) -> Option<f32> {
+        gap.map(|d| (d.as_secs_f32() * 1000.0).round() / 1000.0)
+    }
+
+    #[test]
+    fn measures_the_time_since_the_car_ahead_passed_the_same_point() {
+        let mut tracker = GapTracker::with_checkpoint_spacing(10.0);
+        tracker.push_lap_data(&lap_packet(
+            0.0,
+            &[(1, 1, 100.0), (3, 1, 0.0), (2, 1, 40.0)],
+        ));
+        tracker.push_lap_data(&lap_packet(
+            1.0,
+            &[(1, 1, 150.0), (3, 1, 50.0), (2, 1, 95.0)],
+        ));
+        let gaps = tracker.push_lap_data(&lap_packet(
+            2.0,
+            &[(1, 1, 200.0), (3, 1, 100.0), (2, 1, 145.0)],
+        ));
+
+        let order: Vec<_> = gaps.iter().map(|g| g.car_idx).collect();
+        assert_eq!(order, [0, 2, 1]);
+        assert_eq!(gaps[0].gap_to_leader, Some(Duration::ZERO));
+        assert_eq!(secs(gaps[1].gap_to_leader), Some(1.1));
+        assert_eq!(secs(gaps[2].gap_to_leader), Some(2.0));
+        assert_eq!(secs(gaps[2].interval), Some(0.9));
+    }
+
+    #[test]
+    fn has_no_gap_before_the_car_ahead_was_seen_at_that_point() {
+        let mut tracker = GapTracker::with_checkpoint_spacing(10.0);
+        tracker.push_lap_data(&lap_packet(0.0, &[(1, 1, 500.0), (2, 1, 100.0)]));
+        let gaps = tracker.push_lap_data(&lap_packet(1.0, &[(1, 1, 550.0), (2, 1, 150.0)]));
+
+        assert_eq!(gaps[1].laps_behind_leader, 0);
+        assert_eq!(tracker.gap(1).unwrap().gap_to_leader, None);
+    }
+
+    #[test]
+    fn counts_laps_down_from_the_track_length() {
+        let mut session: PacketSessionData = bytemuck::Zeroable::zeroed();
+        session.header.session_uid = 1;
+        session.track_length = 1000;
+
+        let mut tracker = GapTracker::with_checkpoint_spacing(10.0);
+        tracker.push_session(&session);
+        tracker.push_lap_data(&lap_packet(0.0, &[(1, 3, 2100.0), (2, 1, 50.0)]));
+        let gaps = tracker.push_lap_data(&lap_packet(1.0, &[(1, 3, 2150.0), (2, 1, 100.0)]));
+
+        assert_eq!(gaps[1].laps_behind_leader, 2);
+        assert_eq!(gaps[1].laps_behind_car_ahead, 2);
+    }
+
+    #[test]
+    fn forgets_checkpoints_after_a_flashback() {
+        let mut tracker = GapTracker::with_checkpoint_spacing(10.0);
+        tracker.push_lap_data(&lap_packet(0.0, &[(1, 1, 100.0), (2, 1, 0.0)]));
+        tracker.push_lap_data(&lap_packet(1.0, &[(1, 1, 150.0), (2, 1, 50.0)]));
+        tracker.push_lap_data(&lap_packet(2.0, &[(1, 1, 200.0), (2, 1, 100.0)]));
+
+        // Back to 1s, then the leader drives the same stretch slower
+        tracker.push_lap_data(&lap_packet(1.0, &[(1, 1, 150.0), (2, 1, 50.0)]));
+        let gaps = tracker.push_lap_data(&lap_packet(3.0, &[(1, 1, 200.0), (2, 1, 180.0)]));
+        assert_eq!(secs(gaps[1].gap_to_leader), Some(0.8));
+    }
+}
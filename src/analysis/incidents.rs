@@ -0,0 +1,581 @@
+use core::fmt;
+
+use crate::{
+    constants::{FiaFlag, SafetyCarEventType, SafetyCarType},
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{
+        CarDamageData, Event, PacketCarDamageData, PacketCarStatusData, PacketEventData,
+        PacketLapData, constants::MAX_NUM_CARS,
+    },
+    timing::LapTime,
+};
+
+/// Increase of a car's bodywork damage (percentage points)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DamageDelta {
+    pub car_idx: u8,
+    pub front_left_wing: u8,
+    pub front_right_wing: u8,
+    pub rear_wing: u8,
+    pub floor: u8,
+    pub diffuser: u8,
+    pub sidepod: u8,
+}
+
+impl DamageDelta {
+    fn between(car_idx: u8, before: &CarDamageData, after: &CarDamageData) -> Self {
+        Self {
+            car_idx,
+            front_left_wing: after
+                .front_left_wing_damage
+                .saturating_sub(before.front_left_wing_damage),
+            front_right_wing: after
+                .front_right_wing_damage
+                .saturating_sub(before.front_right_wing_damage),
+            rear_wing: after
+                .rear_wing_damage
+                .saturating_sub(before.rear_wing_damage),
+            floor: after.floor_damage.saturating_sub(before.floor_damage),
+            diffuser: after.diffuser_damage.saturating_sub(before.diffuser_damage),
+            sidepod: after.sidepod_damage.saturating_sub(before.sidepod_damage),
+        }
+    }
+
+    fn parts(&self) -> [(&'static str, u8); 6] {
+        [
+            ("front left wing", self.front_left_wing),
+            ("front right wing", self.front_right_wing),
+            ("rear wing", self.rear_wing),
+            ("floor", self.floor),
+            ("diffuser", self.diffuser),
+            ("sidepod", self.sidepod),
+        ]
+    }
+
+    /// Largest increase of any part
+    pub fn max(&self) -> u8 {
+        self.parts().iter().map(|(_, d)| *d).max().unwrap_or(0)
+    }
+
+    fn merge(&mut self, other: &DamageDelta) {
+        self.front_left_wing = self.front_left_wing.saturating_add(other.front_left_wing);
+        self.front_right_wing = self.front_right_wing.saturating_add(other.front_right_wing);
+        self.rear_wing = self.rear_wing.saturating_add(other.rear_wing);
+        self.floor = self.floor.saturating_add(other.floor);
+        self.diffuser = self.diffuser.saturating_add(other.diffuser);
+        self.sidepod = self.sidepod.saturating_add(other.sidepod);
+    }
+}
+
+impl fmt::Display for DamageDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (part, delta) in self.parts().into_iter().filter(|(_, d)| *d > 0) {
+            if !first {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} +{}%", part, delta)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum IncidentKind {
+    /// `COLL` event
+    Collision,
+    /// Sudden bodywork damage without a collision event
+    Damage,
+    /// Period during which at least one car was shown a yellow flag
+    YellowFlag,
+    /// `SCAR` deployment, `None` for a type from a newer game version
+    SafetyCar {
+        safety_car_type: Option<SafetyCarType>,
+    },
+    /// `RDFL` event
+    RedFlag,
+}
+
+impl fmt::Display for IncidentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncidentKind::Collision => write!(f, "Collision"),
+            IncidentKind::Damage => write!(f, "Damage"),
+            IncidentKind::YellowFlag => write!(f, "Yellow flag"),
+            IncidentKind::SafetyCar {
+                safety_car_type: Some(SafetyCarType::Virtual),
+            } => write!(f, "Virtual safety car"),
+            IncidentKind::SafetyCar { .. } => write!(f, "Safety car"),
+            IncidentKind::RedFlag => write!(f, "Red flag"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Incident {
+    pub kind: IncidentKind,
+    pub session_time: f32,
+    /// Seconds until a flag period or safety car ended, `None` while it lasts
+    pub duration: Option<f32>,
+    /// Lap of the first car involved, or of the leader for session wide incidents
+    pub lap_num: u8,
+    pub lap_distance: f32,
+    pub car_idxs: Vec<u8>,
+    pub damage: Vec<DamageDelta>,
+}
+
+impl Incident {
+    fn involves(&self, car_idx: u8) -> bool {
+        self.car_idxs.contains(&car_idx)
+    }
+
+    fn add_damage(&mut self, delta: DamageDelta) {
+        match self.damage.iter_mut().find(|d| d.car_idx == delta.car_idx) {
+            Some(total) => total.merge(&delta),
+            None => self.damage.push(delta),
+        }
+    }
+}
+
+impl fmt::Display for Incident {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = LapTime::from_millis((self.session_time.max(0.0) * 1000.0) as u32);
+        write!(
+            f,
+            "[{}] Lap {} at {:.0} m: {}",
+            time, self.lap_num, self.lap_distance, self.kind
+        )?;
+        if !self.car_idxs.is_empty() {
+            let cars: Vec<String> = self.car_idxs.iter().map(|c| format!("#{}", c)).collect();
+            write!(f, " involving {}", cars.join(", "))?;
+        }
+        if let Some(duration) = self.duration {
+            write!(f, " for {:.1} s", duration)?;
+        }
+        for delta in &self.damage {
+            write!(f, "\n    #{} damage: {}", delta.car_idx, delta)?;
+        }
+        Ok(())
+    }
+}
+
+/// Incidents of a session in chronological order
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct IncidentReport {
+    pub incidents: Vec<Incident>,
+}
+
+impl IncidentReport {
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl fmt::Display for IncidentReport {
+    /// One incident per line, followed by the damage of each car involved
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for incident in &self.incidents {
+            writeln!(f, "{}", incident)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds [`Incident`]s from collision, flag and safety car events and damage jumps.
+///
+/// Damage is compared between consecutive car damage packets. Damage within
+/// [`IncidentDetector::MERGE_WINDOW`] seconds of a collision involving the car, before or
+/// after the event, is added to that collision. Otherwise a jump of at least the damage
+/// threshold opens a damage incident.
+/// An incident is emitted once nothing can be merged into it anymore, i.e. after the
+/// merge window, or when its flag or safety car period has ended.
+#[derive(Debug, Clone)]
+pub struct IncidentDetector {
+    session_uid: Option<u64>,
+    session_time: f32,
+    damage_threshold: u8,
+    positions: [(u8, f32); MAX_NUM_CARS],
+    leader: Option<u8>,
+    damage: Option<[CarDamageData; MAX_NUM_CARS]>,
+    open: Vec<Incident>,
+    closed: Vec<Incident>,
+}
+
+impl Default for IncidentDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IncidentDetector {
+    /// Seconds after a collision or damage jump during which further damage is merged in
+    pub const MERGE_WINDOW: f32 = 2.0;
+    pub const DEFAULT_DAMAGE_THRESHOLD: u8 = 5;
+
+    pub fn new() -> Self {
+        Self::with_damage_threshold(Self::DEFAULT_DAMAGE_THRESHOLD)
+    }
+
+    /// Minimum increase in percentage points of any bodywork part that counts as damage
+    pub fn with_damage_threshold(percent: u8) -> Self {
+        Self {
+            session_uid: None,
+            session_time: 0.0,
+            damage_threshold: percent.max(1),
+            positions: [(0, 0.0); MAX_NUM_CARS],
+            leader: None,
+            damage: None,
+            open: Vec::new(),
+            closed: Vec::new(),
+        }
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::with_damage_threshold(self.damage_threshold);
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    fn rewind_on_flashback(&mut self, session_time: f32) {
+        if session_time < self.session_time {
+            self.open
+                .retain(|incident| incident.session_time <= session_time);
+            self.closed
+                .retain(|incident| incident.session_time <= session_time);
+            self.damage = None;
+        }
+        self.session_time = session_time;
+    }
+
+    /// Incidents emitted so far
+    pub fn incidents(&self) -> &[Incident] {
+        &self.closed
+    }
+
+    /// Every incident of the session, including those still open
+    pub fn report(&self) -> IncidentReport {
+        let mut incidents: Vec<Incident> = self.closed.iter().chain(&self.open).cloned().collect();
+        incidents.sort_by(|a, b| a.session_time.total_cmp(&b.session_time));
+        IncidentReport { incidents }
+    }
+
+    /// Emits every open incident, e.g. at the end of the session
+    pub fn finish(&mut self) -> Vec<Incident> {
+        let open = std::mem::take(&mut self.open);
+        self.closed.extend(open.iter().cloned());
+        open
+    }
+
+    fn incident(&self, kind: IncidentKind, car_idxs: Vec<u8>) -> Incident {
+        let (lap_num, lap_distance) = car_idxs
+            .first()
+            .copied()
+            .or(self.leader)
+            .and_then(|idx| self.positions.get(idx as usize).copied())
+            .unwrap_or_default();
+
+        Incident {
+            kind,
+            session_time: self.session_time,
+            duration: None,
+            lap_num,
+            lap_distance,
+            car_idxs,
+            damage: Vec::new(),
+        }
+    }
+
+    fn close_expired(&mut self) -> Vec<Incident> {
+        let now = self.session_time;
+        let (expired, open): (Vec<_>, Vec<_>) =
+            self.open
+                .drain(..)
+                .partition(|incident| match incident.kind {
+                    IncidentKind::Collision | IncidentKind::Damage => {
+                        now - incident.session_time > Self::MERGE_WINDOW
+                    }
+                    _ => incident.duration.is_some(),
+                });
+        self.open = open;
+        self.closed.extend(expired.iter().cloned());
+        expired
+    }
+
+    /// Ends the open period of a kind, if any
+    fn end_period(&mut self, matches: impl Fn(&IncidentKind) -> bool) {
+        let now = self.session_time;
+        for incident in &mut self.open {
+            if matches(&incident.kind) && incident.duration.is_none() {
+                incident.duration = Some(now - incident.session_time);
+            }
+        }
+    }
+
+    pub fn push_lap_data(&mut self, packet: &PacketLapData) -> Vec<Incident> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let lap_data = packet.lap_data;
+        for (idx, lap) in lap_data.iter().enumerate() {
+            self.positions[idx] = (lap.current_lap_num, lap.lap_distance);
+            if lap.car_position == 1 {
+                self.leader = Some(idx as u8);
+            }
+        }
+
+        self.close_expired()
+    }
+
+    pub fn push_event(&mut self, packet: &PacketEventData) -> Vec<Incident> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        match packet.event() {
+            Some(Event::Collision(collision)) => {
+                let cars = vec![collision.vehicle1_idx, collision.vehicle2_idx];
+                let mut incident = self.incident(IncidentKind::Collision, cars);
+
+                // The damage may have been seen before the event
+                let now = self.session_time;
+                self.open.retain(|open| {
+                    let merge = open.kind == IncidentKind::Damage
+                        && now - open.session_time <= Self::MERGE_WINDOW
+                        && open.car_idxs.iter().any(|&car| incident.involves(car));
+                    if merge {
+                        open.damage
+                            .iter()
+                            .for_each(|delta| incident.add_damage(*delta));
+                    }
+                    !merge
+                });
+                self.open.push(incident);
+            }
+            Some(Event::SafetyCar(safety_car)) => match safety_car.event_type() {
+                Some(SafetyCarEventType::Deployed) => {
+                    let kind = IncidentKind::SafetyCar {
+                        safety_car_type: safety_car.safety_car_type(),
+                    };
+                    let incident = self.incident(kind, Vec::new());
+                    self.open.push(incident);
+                }
+                Some(SafetyCarEventType::Returned | SafetyCarEventType::ResumeRace) => {
+                    self.end_period(|kind| matches!(kind, IncidentKind::SafetyCar { .. }))
+                }
+                _ => {}
+            },
+            Some(Event::RedFlag) => {
+                let incident = self.incident(IncidentKind::RedFlag, Vec::new());
+                self.closed.push(incident.clone());
+                let mut incidents = self.close_expired();
+                incidents.push(incident);
+                return incidents;
+            }
+            Some(Event::SessionEnded) => {
+                self.end_period(|_| true);
+                return self.finish();
+            }
+            _ => {}
+        }
+
+        self.close_expired()
+    }
+
+    pub fn push_car_status(&mut self, packet: &PacketCarStatusData) -> Vec<Incident> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let statuses = packet.car_status_data;
+        let yellow: Vec<u8> = statuses
+            .iter()
+            .enumerate()
+            .filter(|(_, status)| status.vehicle_fia_flags() == Some(FiaFlag::Yellow))
+            .map(|(idx, _)| idx as u8)
+            .collect();
+
+        let open = self
+            .open
+            .iter_mut()
+            .find(|i| i.kind == IncidentKind::YellowFlag && i.duration.is_none());
+        match (open, yellow.is_empty()) {
+            (Some(period), false) => {
+                for car in yellow {
+                    if !period.involves(car) {
+                        period.car_idxs.push(car);
+                    }
+                }
+            }
+            (Some(_), true) => self.end_period(|kind| *kind == IncidentKind::YellowFlag),
+            (None, false) => {
+                let incident = self.incident(IncidentKind::YellowFlag, yellow);
+                self.open.push(incident);
+            }
+            (None, true) => {}
+        }
+
+        self.close_expired()
+    }
+
+    pub fn push_car_damage(&mut self, packet: &PacketCarDamageData) -> Vec<Incident> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let current = packet.car_damage_data;
+        if let Some(previous) = self.damage.replace(current) {
+            for (idx, (before, after)) in previous.iter().zip(current.iter()).enumerate() {
+                let delta = DamageDelta::between(idx as u8, before, after);
+                if delta.max() == 0 {
+                    continue;
+                }
+
+                let now = self.session_time;
+                let recent = self.open.iter_mut().rev().find(|incident| {
+                    matches!(
+                        incident.kind,
+                        IncidentKind::Collision | IncidentKind::Damage
+                    ) && incident.involves(idx as u8)
+                        && now - incident.session_time <= Self::MERGE_WINDOW
+                });
+                match recent {
+                    Some(incident) => incident.add_damage(delta),
+                    None if delta.max() >= self.damage_threshold => {
+                        let mut incident = self.incident(IncidentKind::Damage, vec![idx as u8]);
+                        incident.add_damage(delta);
+                        self.open.push(incident);
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        self.close_expired()
+    }
+}
+
+impl PacketConsumer for IncidentDetector {
+    type Event = Incident;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<Incident> {
+        match packet {
+            AnyRawPacket::Lap(p) => self.push_lap_data(p),
+            AnyRawPacket::Event(p) => self.push_event(p),
+            AnyRawPacket::CarStatus(p) => self.push_car_status(p),
+            AnyRawPacket::CarDamage(p) => self.push_car_damage(p),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::constants::event::*;
+
+    fn event(session_time: f32, code: &[u8; 4], details: &[u8]) -> PacketEventData {
+        PacketEventData::for_test(1, session_time, code, details)
+    }
+
+    /// Front left wing damage per car
+    fn damage(session_time: f32, wings: &[u8]) -> PacketCarDamageData {
+        let mut packet: PacketCarDamageData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.header.session_time = session_time;
+        for (car, &wing) in packet.car_damage_data.iter_mut().zip(wings) {
+            car.front_left_wing_damage = wing;
+        }
+        packet
+    }
+
+    fn lap_packet(session_time: f32) -> PacketLapData {
+        let mut packet: PacketLapData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.header.session_time = session_time;
+        for (idx, lap) in packet.lap_data.iter_mut().enumerate() {
+            lap.current_lap_num = 7;
+            lap.lap_distance = 1200.0;
+            lap.car_position = idx as u8 + 1;
+        }
+        packet
+    }
+
+    #[test]
+    fn merges_damage_around_a_collision_into_it() {
+        let mut detector = IncidentDetector::new();
+        detector.push_lap_data(&lap_packet(10.0));
+        detector.push_car_damage(&damage(10.0, &[0, 0, 0]));
+        detector.push_car_damage(&damage(10.5, &[0, 20, 0]));
+        assert!(
+            detector
+                .push_event(&event(11.0, COLLISION_EVENT_CODE, &[1, 2]))
+                .is_empty()
+        );
+        detector.push_car_damage(&damage(12.0, &[0, 25, 3]));
+
+        let incidents = detector.push_lap_data(&lap_packet(13.5));
+        assert_eq!(incidents.len(), 1);
+        let collision = &incidents[0];
+        assert_eq!(collision.kind, IncidentKind::Collision);
+        assert_eq!(collision.car_idxs, [1, 2]);
+        assert_eq!((collision.lap_num, collision.lap_distance), (7, 1200.0));
+
+        let wings: Vec<_> = collision
+            .damage
+            .iter()
+            .map(|d| (d.car_idx, d.front_left_wing))
+            .collect();
+        assert_eq!(wings, [(1, 25), (2, 3)]);
+        assert_eq!(detector.incidents(), incidents.as_slice());
+    }
+
+    #[test]
+    fn ignores_damage_below_the_threshold() {
+        let mut detector = IncidentDetector::with_damage_threshold(10);
+        detector.push_car_damage(&damage(1.0, &[0]));
+        detector.push_car_damage(&damage(2.0, &[9]));
+        detector.push_car_damage(&damage(3.0, &[19]));
+
+        let incidents = detector.finish();
+        assert_eq!(incidents.len(), 1);
+        assert_eq!(incidents[0].kind, IncidentKind::Damage);
+        assert_eq!(incidents[0].session_time, 3.0);
+    }
+
+    #[test]
+    fn times_safety_car_periods_and_reports_them() {
+        let mut detector = IncidentDetector::new();
+        detector.push_lap_data(&lap_packet(60.0));
+        detector.push_event(&event(62.0, SAFETY_CAR_EVENT_CODE, &[2, 0]));
+        let incidents = detector.push_event(&event(92.5, SAFETY_CAR_EVENT_CODE, &[2, 2]));
+        assert_eq!(incidents.len(), 1);
+        assert_eq!(incidents[0].duration, Some(30.5));
+
+        detector.push_event(&event(100.0, COLLISION_EVENT_CODE, &[0, 3]));
+        let report = detector.report();
+        assert_eq!(
+            report.to_string(),
+            "[01:02.000] Lap 7 at 1200 m: Virtual safety car for 30.5 s\n\
+             [01:40.000] Lap 7 at 1200 m: Collision involving #0, #3\n"
+        );
+    }
+
+    #[test]
+    fn drops_incidents_after_the_point_flashed_back_to() {
+        let mut detector = IncidentDetector::new();
+        detector.push_event(&event(10.0, COLLISION_EVENT_CODE, &[0, 1]));
+        detector.push_event(&event(20.0, RED_FLAG_EVENT_CODE, &[]));
+        detector.push_lap_data(&lap_packet(15.0));
+
+        let kinds: Vec<_> = detector.incidents().iter().map(|i| i.kind).collect();
+        assert_eq!(kinds, [IncidentKind::Collision]);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{LapData, PacketCarStatusData, PacketLapData, constants::MAX_NUM_CARS},
+    timing::LapTime,
+};
+
+/// A completed lap reconstructed from the lap packet stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lap {
+    pub car_idx: u8,
+    pub lap_num: u8,
+    pub lap_time_in_ms: u32,
+    pub sector_times_in_ms: [u32; 3],
+    pub valid: bool,
+    /// Car entered the pit lane during this lap
+    pub pit_in: bool,
+    /// Car left the pit lane during this lap
+    pub pit_out: bool,
+    /// Actual compound in use, `None` if no car status packet had been seen
+    pub actual_tyre_compound: Option<u8>,
+    /// Visual compound in use, `None` if no car status packet had been seen
+    pub visual_tyre_compound: Option<u8>,
+}
+
+impl Lap {
+    pub fn lap_time(&self) -> LapTime {
+        LapTime::from_millis(self.lap_time_in_ms)
+    }
+
+    pub fn sector_times(&self) -> [LapTime; 3] {
+        self.sector_times_in_ms.map(LapTime::from_millis)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct CarLapState {
+    last: Option<LapData>,
+    pit_in: bool,
+    pit_out: bool,
+    /// (actual, visual) compound when the lap started
+    compound_at_start: Option<(u8, u8)>,
+}
+
+/// Detects lap completion for every car from the lap packets.
+///
+/// `PacketSessionHistoryData` only covers one car per packet, but every lap packet
+/// carries all cars, so this sees each car cross the line as it happens. A lap is
+/// complete when `current_lap_num` increments; the sectors come from the last sample
+/// of the finished lap, and sector 3 is the remainder of `last_lap_time_in_ms`.
+///
+/// After a flashback the lap number can go backwards, so a lap may be emitted again
+/// once it has been re-driven. Consumers should key laps by car and lap number.
+#[derive(Debug, Clone)]
+pub struct LapBuilder {
+    session_uid: Option<u64>,
+    cars: [CarLapState; MAX_NUM_CARS],
+    /// Latest (actual, visual) compound per car
+    compounds: [Option<(u8, u8)>; MAX_NUM_CARS],
+}
+
+impl Default for LapBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LapBuilder {
+    pub fn new() -> Self {
+        Self {
+            session_uid: None,
+            cars: [CarLapState::default(); MAX_NUM_CARS],
+            compounds: [None; MAX_NUM_CARS],
+        }
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::new();
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    pub fn push_car_status(&mut self, packet: &PacketCarStatusData) {
+        self.reset_on_new_session(packet.header.session_uid);
+
+        let cars = packet.car_status_data;
+        for (compound, status) in self.compounds.iter_mut().zip(cars.iter()) {
+            *compound = Some((status.actual_tyre_compound, status.visual_tyre_compound));
+        }
+    }
+
+    pub fn push_lap_data(&mut self, packet: &PacketLapData) -> Vec<Lap> {
+        self.reset_on_new_session(packet.header.session_uid);
+
+        let mut laps = Vec::new();
+        let lap_data = packet.lap_data;
+
+        for (idx, current) in lap_data.iter().enumerate() {
+            let compound = self.compounds[idx];
+            let state = &mut self.cars[idx];
+
+            let Some(previous) = state.last.replace(*current) else {
+                state.compound_at_start = compound;
+                continue;
+            };
+
+            let in_pits = current.pit_status().is_some_and(|s| s.is_in_pits());
+            let was_in_pits = previous.pit_status().is_some_and(|s| s.is_in_pits());
+            state.pit_in |= in_pits && !was_in_pits;
+            state.pit_out |= !in_pits && was_in_pits;
+
+            if current.current_lap_num == previous.current_lap_num {
+                continue;
+            }
+
+            let completed = current.current_lap_num > previous.current_lap_num
+                && previous.current_lap_num > 0
+                && current.last_lap_time_in_ms > 0;
+
+            if completed {
+                let lap_time = current.last_lap_time_in_ms;
+                let sector1 = LapTime::from(previous.sector1_time()).as_millis();
+                let sector2 = LapTime::from(previous.sector2_time()).as_millis();
+
+                // An out lap runs on the tyres fitted during the stop
+                let (actual, visual) = match state.pit_out {
+                    true => compound.unzip(),
+                    false => state.compound_at_start.or(compound).unzip(),
+                };
+
+                laps.push(Lap {
+                    car_idx: idx as u8,
+                    lap_num: previous.current_lap_num,
+                    lap_time_in_ms: lap_time,
+                    sector_times_in_ms: [
+                        sector1,
+                        sector2,
+                        lap_time.saturating_sub(sector1 + sector2),
+                    ],
+                    valid: previous.current_lap_invalid == 0,
+                    pit_in: state.pit_in,
+                    pit_out: state.pit_out,
+                    actual_tyre_compound: actual,
+                    visual_tyre_compound: visual,
+                });
+            }
+
+            state.pit_in = false;
+            state.pit_out = false;
+            state.compound_at_start = compound;
+        }
+
+        laps
+    }
+}
+
+impl PacketConsumer for LapBuilder {
+    type Event = Lap;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<Lap> {
+        match packet {
+            AnyRawPacket::CarStatus(p) => {
+                self.push_car_status(p);
+                Vec::new()
+            }
+            AnyRawPacket::Lap(p) => self.push_lap_data(p),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lap_packet(session_uid: u64, cars: &[(u8, u32, u8)]) -> PacketLapData {
+        let mut packet: PacketLapData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        for (lap, &(lap_num, last_lap_time_in_ms, pit_status)) in
+            packet.lap_data.iter_mut().zip(cars)
+        {
+            lap.current_lap_num = lap_num;
+            lap.last_lap_time_in_ms = last_lap_time_in_ms;
+            lap.pit_status = pit_status;
+            lap.sector1_time_minutes_part = 1;
+            lap.sector1_time_ms_part = 500;
+            lap.sector2_time_ms_part = 30_000;
+        }
+        packet
+    }
+
+    fn status_packet(session_uid: u64, compound: (u8, u8)) -> PacketCarStatusData {
+        let mut packet: PacketCarStatusData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        for status in packet.car_status_data.iter_mut() {
+            status.actual_tyre_compound = compound.0;
+            status.visual_tyre_compound = compound.1;
+        }
+        packet
+    }
+
+    #[test]
+    fn emits_a_lap_when_the_lap_number_increments() {
+        let mut builder = LapBuilder::new();
+        assert!(
+            builder
+                .push_lap_data(&lap_packet(1, &[(1, 0, 0)]))
+                .is_empty()
+        );
+        assert!(
+            builder
+                .push_lap_data(&lap_packet(1, &[(1, 0, 0)]))
+                .is_empty()
+        );
+
+        let laps = builder.push_lap_data(&lap_packet(1, &[(2, 120_000, 0)]));
+        let lap = laps.iter().find(|l| l.car_idx == 0).unwrap();
+        assert_eq!(lap.lap_num, 1);
+        assert_eq!(lap.lap_time_in_ms, 120_000);
+        assert_eq!(lap.sector_times_in_ms, [60_500, 30_000, 29_500]);
+        assert!(lap.valid);
+        assert_eq!(lap.actual_tyre_compound, None);
+    }
+
+    #[test]
+    fn flags_pit_laps_and_takes_the_compound_fitted_at_the_stop() {
+        let mut builder = LapBuilder::new();
+        builder.push_car_status(&status_packet(1, (16, 16)));
+        builder.push_lap_data(&lap_packet(1, &[(1, 0, 0)]));
+        builder.push_lap_data(&lap_packet(1, &[(1, 0, 1)]));
+        let in_lap = builder.push_lap_data(&lap_packet(1, &[(2, 95_000, 1)]));
+        assert!(in_lap[0].pit_in);
+        assert_eq!(in_lap[0].visual_tyre_compound, Some(16));
+
+        builder.push_car_status(&status_packet(1, (18, 18)));
+        builder.push_lap_data(&lap_packet(1, &[(2, 95_000, 0)]));
+        let out_lap = builder.push_lap_data(&lap_packet(1, &[(3, 99_000, 0)]));
+        assert!(out_lap[0].pit_out && !out_lap[0].pit_in);
+        assert_eq!(out_lap[0].visual_tyre_compound, Some(18));
+    }
+
+    #[test]
+    fn ignores_flashbacks_and_new_sessions() {
+        let mut builder = LapBuilder::new();
+        builder.push_lap_data(&lap_packet(1, &[(3, 90_000, 0)]));
+        assert!(
+            builder
+                .push_lap_data(&lap_packet(1, &[(2, 90_000, 0)]))
+                .is_empty()
+        );
+        assert!(
+            builder
+                .push_lap_data(&lap_packet(2, &[(3, 90_000, 0)]))
+                .is_empty()
+        );
+    }
+}
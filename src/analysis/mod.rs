@@ -0,0 +1,19 @@
+pub mod buttons;
+pub mod damage;
+pub mod ers;
+pub mod gaps;
+pub mod incidents;
+pub mod laps;
+pub mod penalties;
+pub mod positions;
+pub mod time_trial;
+
+pub use buttons::*;
+pub use damage::*;
+pub use ers::*;
+pub use gaps::*;
+pub use incidents::*;
+pub use laps::*;
+pub use penalties::*;
+pub use positions::*;
+pub use time_trial::*;
@@ -0,0 +1,469 @@
+use crate::{
+    constants::{InfringementType, PenaltyType},
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{Event, LapData, PacketEventData, PacketLapData, constants::MAX_NUM_CARS},
+};
+
+/// What happened in a [`LedgerEntry`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LedgerEvent {
+    /// `PENA` event that only warns, or invalidates laps
+    Infringement {
+        infringement_type: InfringementType,
+        penalty_type: PenaltyType,
+        other_car_idx: Option<u8>,
+    },
+    /// `PENA` event with a penalty to serve, or applied directly
+    PenaltyIssued {
+        infringement_type: InfringementType,
+        penalty_type: PenaltyType,
+        other_car_idx: Option<u8>,
+        /// Time gained, or seconds of a time penalty
+        time: u8,
+        places_gained: u8,
+    },
+    /// `DTSV` or `SGSV` event
+    PenaltyServed {
+        penalty_type: PenaltyType,
+        /// Stop time of a stop go, in seconds
+        stop_time: Option<f32>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LedgerEntry {
+    pub car_idx: u8,
+    pub lap_num: u8,
+    pub session_time: f32,
+    pub event: LedgerEvent,
+}
+
+/// Latest stewarding counters of a driver, from the lap packets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PenaltyCounters {
+    /// Accumulated time penalties in seconds
+    pub penalties: u8,
+    pub total_warnings: u8,
+    pub corner_cutting_warnings: u8,
+    pub num_unserved_drive_through_pens: u8,
+    pub num_unserved_stop_go_pens: u8,
+}
+
+impl From<&LapData> for PenaltyCounters {
+    fn from(lap: &LapData) -> Self {
+        Self {
+            penalties: lap.penalties,
+            total_warnings: lap.total_warnings,
+            corner_cutting_warnings: lap.corner_cutting_warnings,
+            num_unserved_drive_through_pens: lap.num_unserved_drive_through_pens,
+            num_unserved_stop_go_pens: lap.num_unserved_stop_go_pens,
+        }
+    }
+}
+
+/// Inconsistency between the penalty events and the lap data counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// Drive throughs still to serve when the chequered flag was shown
+    UnservedDriveThroughAtFlag { car_idx: u8, count: u8 },
+    /// Stop go penalties still to serve when the chequered flag was shown
+    UnservedStopGoAtFlag { car_idx: u8, count: u8 },
+    /// More drive throughs or stop gos served than issued, an event was missed
+    ServedNotIssued {
+        car_idx: u8,
+        penalty_type: PenaltyType,
+        issued: u32,
+        served: u32,
+    },
+    /// Drive throughs or stop gos issued that were neither served nor are still pending
+    IssuedNotAccounted {
+        car_idx: u8,
+        penalty_type: PenaltyType,
+        issued: u32,
+        served: u32,
+        unserved: u8,
+    },
+}
+
+/// Whether a penalty type has to be served or changes the result, as opposed to a
+/// warning or a lap invalidation
+fn is_penalty(penalty_type: PenaltyType) -> bool {
+    use PenaltyType::*;
+
+    matches!(
+        penalty_type,
+        DriveThrough
+            | StopGo
+            | GridPenalty
+            | TimePenalty
+            | Disqualified
+            | RemovedFromFormationLap
+            | ParkedTooLongTimer
+            | TyreRegulations
+            | BlackFlagTimer
+    )
+}
+
+/// Record of every infringement, penalty issued and penalty served in a session.
+///
+/// The timeline comes from the `PENA`, `DTSV` and `SGSV` events, and the lap data counters
+/// are kept alongside to cross-check them, see [`PenaltyLedger::discrepancies`]. Entries
+/// recorded after the point a flashback returns to are removed.
+#[derive(Debug, Clone, Default)]
+pub struct PenaltyLedger {
+    session_uid: Option<u64>,
+    session_time: f32,
+    entries: Vec<LedgerEntry>,
+    lap_nums: [u8; MAX_NUM_CARS],
+    counters: [Option<PenaltyCounters>; MAX_NUM_CARS],
+    /// Session time of the chequered flag and the counters at that point
+    counters_at_flag: Option<(f32, [Option<PenaltyCounters>; MAX_NUM_CARS])>,
+}
+
+impl PenaltyLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::new();
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    fn rewind_on_flashback(&mut self, session_time: f32) {
+        if session_time < self.session_time {
+            self.entries
+                .retain(|entry| entry.session_time <= session_time);
+            if self
+                .counters_at_flag
+                .is_some_and(|(flag_time, _)| flag_time > session_time)
+            {
+                self.counters_at_flag = None;
+            }
+        }
+        self.session_time = session_time;
+    }
+
+    /// Every entry of the session in chronological order
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    /// Entries of one driver in chronological order
+    pub fn timeline(&self, car_idx: u8) -> Vec<LedgerEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.car_idx == car_idx)
+            .copied()
+            .collect()
+    }
+
+    /// Latest lap data counters, `None` until a lap packet is received
+    pub fn counters(&self, car_idx: u8) -> Option<PenaltyCounters> {
+        self.counters.get(car_idx as usize).copied().flatten()
+    }
+
+    pub fn push_event(&mut self, packet: &PacketEventData) -> Vec<LedgerEntry> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let (car_idx, lap_num, event) = match packet.event() {
+            Some(Event::Penalty(penalty)) => {
+                // Ids from a newer game version can't be typed, and are skipped
+                let (Some(penalty_type), Some(infringement_type)) = (
+                    PenaltyType::try_from_id(penalty.penalty_type),
+                    InfringementType::try_from_id(penalty.infringement_type),
+                ) else {
+                    return Vec::new();
+                };
+                let other_car_idx =
+                    Some(penalty.other_vehicle_idx).filter(|&idx| (idx as usize) < MAX_NUM_CARS);
+
+                let event = match is_penalty(penalty_type) {
+                    true => LedgerEvent::PenaltyIssued {
+                        infringement_type,
+                        penalty_type,
+                        other_car_idx,
+                        time: penalty.time,
+                        places_gained: penalty.places_gained,
+                    },
+                    false => LedgerEvent::Infringement {
+                        infringement_type,
+                        penalty_type,
+                        other_car_idx,
+                    },
+                };
+                (penalty.vehicle_idx, penalty.lap_num, event)
+            }
+            Some(Event::DriveThroughServed(served)) => {
+                let event = LedgerEvent::PenaltyServed {
+                    penalty_type: PenaltyType::DriveThrough,
+                    stop_time: None,
+                };
+                (served.vehicle_idx, self.lap_num(served.vehicle_idx), event)
+            }
+            Some(Event::StopGoServed(served)) => {
+                let event = LedgerEvent::PenaltyServed {
+                    penalty_type: PenaltyType::StopGo,
+                    stop_time: Some(served.stop_time),
+                };
+                (served.vehicle_idx, self.lap_num(served.vehicle_idx), event)
+            }
+            Some(Event::ChequeredFlag) => {
+                self.counters_at_flag = Some((self.session_time, self.counters));
+                return Vec::new();
+            }
+            _ => return Vec::new(),
+        };
+
+        let entry = LedgerEntry {
+            car_idx,
+            lap_num,
+            session_time: header.session_time,
+            event,
+        };
+        self.entries.push(entry);
+        vec![entry]
+    }
+
+    pub fn push_lap_data(&mut self, packet: &PacketLapData) {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let lap_data = packet.lap_data;
+        for (idx, lap) in lap_data.iter().enumerate() {
+            self.lap_nums[idx] = lap.current_lap_num;
+            if lap
+                .result_status()
+                .is_some_and(|status| status.is_participating())
+            {
+                self.counters[idx] = Some(PenaltyCounters::from(lap));
+            }
+        }
+    }
+
+    fn lap_num(&self, car_idx: u8) -> u8 {
+        self.lap_nums.get(car_idx as usize).copied().unwrap_or(0)
+    }
+
+    /// Cross-checks the events against the counters, and the counters at the chequered flag
+    pub fn discrepancies(&self) -> Vec<Discrepancy> {
+        let mut discrepancies = Vec::new();
+
+        for car_idx in 0..MAX_NUM_CARS as u8 {
+            let flag = self
+                .counters_at_flag
+                .and_then(|(_, counters)| counters[car_idx as usize]);
+            if let Some(flag) = flag {
+                if flag.num_unserved_drive_through_pens > 0 {
+                    discrepancies.push(Discrepancy::UnservedDriveThroughAtFlag {
+                        car_idx,
+                        count: flag.num_unserved_drive_through_pens,
+                    });
+                }
+                if flag.num_unserved_stop_go_pens > 0 {
+                    discrepancies.push(Discrepancy::UnservedStopGoAtFlag {
+                        car_idx,
+                        count: flag.num_unserved_stop_go_pens,
+                    });
+                }
+            }
+
+            let Some(counters) = self.counters(car_idx) else {
+                continue;
+            };
+            for (penalty_type, unserved) in [
+                (
+                    PenaltyType::DriveThrough,
+                    counters.num_unserved_drive_through_pens,
+                ),
+                (PenaltyType::StopGo, counters.num_unserved_stop_go_pens),
+            ] {
+                let (issued, served) = self.count(car_idx, penalty_type);
+                if served > issued {
+                    discrepancies.push(Discrepancy::ServedNotIssued {
+                        car_idx,
+                        penalty_type,
+                        issued,
+                        served,
+                    });
+                } else if issued > served + unserved as u32 {
+                    discrepancies.push(Discrepancy::IssuedNotAccounted {
+                        car_idx,
+                        penalty_type,
+                        issued,
+                        served,
+                        unserved,
+                    });
+                }
+            }
+        }
+
+        discrepancies
+    }
+
+    /// Number of (issued, served) penalties of a type for a driver
+    fn count(&self, car_idx: u8, penalty_type: PenaltyType) -> (u32, u32) {
+        self.entries
+            .iter()
+            .filter(|entry| entry.car_idx == car_idx)
+            .fold((0, 0), |(issued, served), entry| match entry.event {
+                LedgerEvent::PenaltyIssued {
+                    penalty_type: t, ..
+                } if t == penalty_type => (issued + 1, served),
+                LedgerEvent::PenaltyServed {
+                    penalty_type: t, ..
+                } if t == penalty_type => (issued, served + 1),
+                _ => (issued, served),
+            })
+    }
+}
+
+impl PacketConsumer for PenaltyLedger {
+    type Event = LedgerEntry;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<LedgerEntry> {
+        match packet {
+            AnyRawPacket::Event(p) => self.push_event(p),
+            AnyRawPacket::Lap(p) => {
+                self.push_lap_data(p);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::constants::event::*;
+
+    /// `(penalty_type, infringement_type, vehicle_idx)` on lap 4
+    fn penalty(session_time: f32, penalty: (u8, u8, u8)) -> PacketEventData {
+        let (penalty_type, infringement_type, car) = penalty;
+        let details = [penalty_type, infringement_type, car, 255, 5, 4, 0];
+        PacketEventData::for_test(1, session_time, PENALTY_EVENT_CODE, &details)
+    }
+
+    fn drive_through_served(session_time: f32, car: u8) -> PacketEventData {
+        PacketEventData::for_test(1, session_time, DRIVE_THROUGH_SERVED_EVENT_CODE, &[car])
+    }
+
+    /// `num_unserved_drive_through_pens` per car
+    fn lap_packet(session_time: f32, unserved: &[u8]) -> PacketLapData {
+        let mut packet: PacketLapData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.header.session_time = session_time;
+        for (lap, &unserved) in packet.lap_data.iter_mut().zip(unserved) {
+            lap.current_lap_num = 5;
+            lap.result_status = 2;
+            lap.num_unserved_drive_through_pens = unserved;
+        }
+        packet
+    }
+
+    #[test]
+    fn records_infringements_penalties_and_serving() {
+        let mut ledger = PenaltyLedger::new();
+        ledger.push_lap_data(&lap_packet(1.0, &[0, 0]));
+        ledger.push_event(&penalty(2.0, (5, 7, 1)));
+        ledger.push_event(&penalty(3.0, (0, 12, 1)));
+        let served = ledger.push_event(&drive_through_served(4.0, 1));
+
+        assert_eq!(served[0].lap_num, 5);
+        let timeline = ledger.timeline(1);
+        assert!(matches!(
+            timeline[0].event,
+            LedgerEvent::Infringement {
+                penalty_type: PenaltyType::Warning,
+                other_car_idx: None,
+                ..
+            }
+        ));
+        assert!(matches!(
+            timeline[1].event,
+            LedgerEvent::PenaltyIssued {
+                penalty_type: PenaltyType::DriveThrough,
+                infringement_type: InfringementType::IgnoringYellowFlags,
+                time: 5,
+                ..
+            }
+        ));
+        assert_eq!(timeline[1].lap_num, 4);
+        assert!(matches!(
+            timeline[2].event,
+            LedgerEvent::PenaltyServed {
+                penalty_type: PenaltyType::DriveThrough,
+                stop_time: None
+            }
+        ));
+        assert!(ledger.timeline(0).is_empty());
+        assert!(ledger.discrepancies().is_empty());
+    }
+
+    #[test]
+    fn flags_penalties_unserved_at_the_flag_and_missed_events() {
+        let mut ledger = PenaltyLedger::new();
+        ledger.push_event(&penalty(1.0, (0, 12, 0)));
+        ledger.push_lap_data(&lap_packet(2.0, &[1, 0]));
+        ledger.push_event(&drive_through_served(3.0, 1));
+        ledger.push_event(&PacketEventData::for_test(
+            1,
+            4.0,
+            CHEQUERED_FLAG_EVENT_CODE,
+            &[],
+        ));
+
+        assert_eq!(
+            ledger.discrepancies(),
+            [
+                Discrepancy::UnservedDriveThroughAtFlag {
+                    car_idx: 0,
+                    count: 1
+                },
+                Discrepancy::ServedNotIssued {
+                    car_idx: 1,
+                    penalty_type: PenaltyType::DriveThrough,
+                    issued: 0,
+                    served: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn forgets_entries_after_the_point_flashed_back_to() {
+        let mut ledger = PenaltyLedger::new();
+        ledger.push_event(&penalty(10.0, (5, 7, 0)));
+        ledger.push_event(&penalty(20.0, (4, 3, 0)));
+        ledger.push_lap_data(&lap_packet(15.0, &[0]));
+
+        assert_eq!(ledger.entries().len(), 1);
+        assert_eq!(ledger.entries()[0].session_time, 10.0);
+    }
+
+    #[test]
+    fn forgets_the_flag_after_the_point_flashed_back_to() {
+        let mut ledger = PenaltyLedger::new();
+        ledger.push_lap_data(&lap_packet(10.0, &[1]));
+        ledger.push_event(&PacketEventData::for_test(
+            1,
+            20.0,
+            CHEQUERED_FLAG_EVENT_CODE,
+            &[],
+        ));
+        ledger.push_lap_data(&lap_packet(15.0, &[1]));
+
+        assert!(
+            !ledger
+                .discrepancies()
+                .iter()
+                .any(|d| matches!(d, Discrepancy::UnservedDriveThroughAtFlag { .. }))
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{
+    constants::ResultStatus,
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{Event, LapData, PacketEventData, PacketLapData, constants::MAX_NUM_CARS},
+};
+
+/// Why two cars swapped places
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PositionChangeKind {
+    /// Overtake on track
+    OnTrack,
+    /// One of the cars was in the pit lane
+    PitCycle,
+    /// The car losing the place served a drive through or stop go, or was disqualified
+    Penalty,
+    /// The car losing the place retired
+    Retirement,
+}
+
+/// Two cars swapping places in the running order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionChange {
+    /// Car gaining the place
+    pub car_idx: u8,
+    /// Car losing the place
+    pub other_car_idx: u8,
+    /// New position of `car_idx`
+    pub position: u8,
+    /// New position of `other_car_idx`
+    pub other_position: u8,
+    /// Lap of `car_idx` when the change was seen
+    pub lap_num: u8,
+    /// Lap distance of `car_idx` when the change was seen
+    pub lap_distance: f32,
+    pub session_time: f32,
+    pub kind: PositionChangeKind,
+    /// An `OVTK` event was received for this pass
+    pub confirmed: bool,
+}
+
+/// Overtake event as (session_time, overtaking_idx, overtaken_idx)
+type OvertakeEvent = (f32, u8, u8);
+
+/// Detects position changes by comparing `car_position` between consecutive lap packets.
+///
+/// Each pair of cars whose order flipped is one [`PositionChange`], so a car dropping three
+/// places in a pit stop produces three changes. On track passes are held back for up to
+/// [`PositionTracker::CORRELATION_WINDOW`] seconds of session time to be matched with an
+/// `OVTK` event, which may arrive before or after the lap packet showing the new order.
+/// Passes the game reports no event for are then emitted unconfirmed.
+#[derive(Debug, Clone, Default)]
+pub struct PositionTracker {
+    session_uid: Option<u64>,
+    session_time: f32,
+    previous: Option<[LapData; MAX_NUM_CARS]>,
+    pending: Vec<PositionChange>,
+    overtakes: Vec<OvertakeEvent>,
+}
+
+impl PositionTracker {
+    /// Seconds within which an `OVTK` event and a change of order are considered the same pass
+    pub const CORRELATION_WINDOW: f32 = 3.0;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::new();
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    /// After a flashback the order goes back to an earlier state, which is not a change
+    fn rewind_on_flashback(&mut self, session_time: f32) {
+        if session_time < self.session_time {
+            self.previous = None;
+            self.pending.clear();
+            self.overtakes.clear();
+        }
+        self.session_time = session_time;
+    }
+
+    /// Emits the pending passes that can no longer be matched with an event
+    fn expire(&mut self, changes: &mut Vec<PositionChange>) {
+        let now = self.session_time;
+        let (expired, pending): (Vec<_>, Vec<_>) = self
+            .pending
+            .drain(..)
+            .partition(|change| now - change.session_time > Self::CORRELATION_WINDOW);
+        self.pending = pending;
+        changes.extend(expired);
+        self.overtakes
+            .retain(|(time, _, _)| now - time <= Self::CORRELATION_WINDOW);
+    }
+
+    pub fn push_event(&mut self, packet: &PacketEventData) -> Vec<PositionChange> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let mut changes = Vec::new();
+        if let Some(Event::Overtake(overtake)) = packet.event() {
+            let (car, other) = (
+                overtake.overtaking_vehicle_idx,
+                overtake.being_overtaken_vehicle_idx,
+            );
+            match self
+                .pending
+                .iter()
+                .position(|c| c.car_idx == car && c.other_car_idx == other)
+            {
+                Some(i) => {
+                    let mut change = self.pending.remove(i);
+                    change.confirmed = true;
+                    changes.push(change);
+                }
+                None => self.overtakes.push((header.session_time, car, other)),
+            }
+        }
+
+        self.expire(&mut changes);
+        changes
+    }
+
+    pub fn push_lap_data(&mut self, packet: &PacketLapData) -> Vec<PositionChange> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let current = packet.lap_data;
+        let mut changes = Vec::new();
+
+        if let Some(previous) = self.previous.replace(current) {
+            for (a, (a_now, a_before)) in current.iter().zip(previous.iter()).enumerate() {
+                for (b, (b_now, b_before)) in current.iter().zip(previous.iter()).enumerate() {
+                    let ranked = |lap: &LapData| {
+                        lap.result_status()
+                            .is_some_and(|status| status.is_participating())
+                            && lap.car_position > 0
+                    };
+                    if a == b || !(ranked(a_now) && ranked(a_before) && ranked(b_before)) {
+                        continue;
+                    }
+                    // a was behind b and is now ahead of it
+                    let ahead_now =
+                        b_now.car_position == 0 || a_now.car_position < b_now.car_position;
+                    if a_before.car_position <= b_before.car_position || !ahead_now {
+                        continue;
+                    }
+
+                    let kind = classify(a_now, a_before, b_now, b_before);
+                    let mut change = PositionChange {
+                        car_idx: a as u8,
+                        other_car_idx: b as u8,
+                        position: a_now.car_position,
+                        other_position: b_now.car_position,
+                        lap_num: a_now.current_lap_num,
+                        lap_distance: a_now.lap_distance,
+                        session_time: header.session_time,
+                        kind,
+                        confirmed: false,
+                    };
+
+                    if kind != PositionChangeKind::OnTrack {
+                        changes.push(change);
+                        continue;
+                    }
+
+                    match self
+                        .overtakes
+                        .iter()
+                        .position(|&(_, car, other)| car == a as u8 && other == b as u8)
+                    {
+                        Some(i) => {
+                            self.overtakes.remove(i);
+                            change.confirmed = true;
+                            changes.push(change);
+                        }
+                        None => self.pending.push(change),
+                    }
+                }
+            }
+        }
+
+        self.expire(&mut changes);
+        changes
+    }
+
+    /// Emits every pass still waiting for an `OVTK` event unconfirmed, e.g. at the end of
+    /// the session
+    pub fn finish(&mut self) -> Vec<PositionChange> {
+        self.overtakes.clear();
+        std::mem::take(&mut self.pending)
+    }
+}
+
+fn classify(
+    car_now: &LapData,
+    car_before: &LapData,
+    other_now: &LapData,
+    other_before: &LapData,
+) -> PositionChangeKind {
+    match other_now.result_status() {
+        Some(ResultStatus::Disqualified) => return PositionChangeKind::Penalty,
+        Some(status) if !status.is_running() => return PositionChangeKind::Retirement,
+        _ => {}
+    }
+
+    let in_pits = |now: &LapData, before: &LapData| {
+        let pitting = |lap: &LapData| lap.pit_status().is_some_and(|s| s.is_in_pits());
+        pitting(now) || pitting(before)
+    };
+    let serving_penalty = |now: &LapData, before: &LapData| {
+        before.pit_stop_should_serve_pen != 0
+            || now.num_unserved_drive_through_pens < before.num_unserved_drive_through_pens
+            || now.num_unserved_stop_go_pens < before.num_unserved_stop_go_pens
+    };
+
+    if in_pits(other_now, other_before) {
+        match serving_penalty(other_now, other_before) {
+            true => PositionChangeKind::Penalty,
+            false => PositionChangeKind::PitCycle,
+        }
+    } else if in_pits(car_now, car_before) {
+        PositionChangeKind::PitCycle
+    } else {
+        PositionChangeKind::OnTrack
+    }
+}
+
+impl PacketConsumer for PositionTracker {
+    type Event = PositionChange;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<PositionChange> {
+        match packet {
+            AnyRawPacket::Event(p) => self.push_event(p),
+            AnyRawPacket::Lap(p) => self.push_lap_data(p),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::constants::event::OVERTAKE_EVENT_CODE;
+
+    /// `(car_position, pit_status)` per car
+    fn lap_packet(session_time: f32, cars: &[(u8, u8)]) -> PacketLapData {
+        let mut packet: PacketLapData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.header.session_time = session_time;
+        for (lap, &(position, pit_status)) in packet.lap_data.iter_mut().zip(cars) {
+            lap.car_position = position;
+            lap.pit_status = pit_status;
+            lap.current_lap_num = 3;
+            lap.result_status = 2;
+        }
+        packet
+    }
+
+    fn overtake(session_time: f32, car: u8, other: u8) -> PacketEventData {
+        PacketEventData::for_test(1, session_time, OVERTAKE_EVENT_CODE, &[car, other])
+    }
+
+    #[test]
+    fn confirms_a_pass_with_an_overtake_event_either_side_of_it() {
+        let mut tracker = PositionTracker::new();
+        tracker.push_lap_data(&lap_packet(10.0, &[(1, 0), (2, 0), (3, 0)]));
+        assert!(
+            tracker
+                .push_lap_data(&lap_packet(11.0, &[(1, 0), (3, 0), (2, 0)]))
+                .is_empty()
+        );
+
+        let changes = tracker.push_event(&overtake(12.0, 2, 1));
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].car_idx, changes[0].other_car_idx), (2, 1));
+        assert_eq!((changes[0].position, changes[0].other_position), (2, 3));
+        assert_eq!(changes[0].kind, PositionChangeKind::OnTrack);
+        assert!(changes[0].confirmed);
+
+        // Event first, then the new order
+        assert!(tracker.push_event(&overtake(13.0, 1, 2)).is_empty());
+        let changes = tracker.push_lap_data(&lap_packet(14.0, &[(1, 0), (2, 0), (3, 0)]));
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].confirmed);
+    }
+
+    #[test]
+    fn emits_unmatched_passes_after_the_window_or_on_finish() {
+        let mut tracker = PositionTracker::new();
+        tracker.push_lap_data(&lap_packet(10.0, &[(1, 0), (2, 0)]));
+        tracker.push_lap_data(&lap_packet(11.0, &[(2, 0), (1, 0)]));
+        assert!(
+            tracker
+                .push_lap_data(&lap_packet(13.0, &[(2, 0), (1, 0)]))
+                .is_empty()
+        );
+
+        let changes = tracker.push_lap_data(&lap_packet(14.5, &[(2, 0), (1, 0)]));
+        assert_eq!(changes.len(), 1);
+        assert!(!changes[0].confirmed);
+
+        tracker.push_lap_data(&lap_packet(15.0, &[(1, 0), (2, 0)]));
+        let changes = tracker.finish();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].car_idx, 0);
+        assert!(tracker.finish().is_empty());
+    }
+
+    #[test]
+    fn classifies_pit_cycles_without_waiting_for_an_event() {
+        let mut tracker = PositionTracker::new();
+        tracker.push_lap_data(&lap_packet(10.0, &[(1, 0), (2, 0)]));
+        let changes = tracker.push_lap_data(&lap_packet(11.0, &[(2, 1), (1, 0)]));
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, PositionChangeKind::PitCycle);
+    }
+
+    #[test]
+    fn ignores_the_order_restored_by_a_flashback() {
+        let mut tracker = PositionTracker::new();
+        tracker.push_lap_data(&lap_packet(10.0, &[(1, 0), (2, 0)]));
+        tracker.push_lap_data(&lap_packet(11.0, &[(2, 0), (1, 0)]));
+        assert!(
+            tracker
+                .push_lap_data(&lap_packet(9.0, &[(1, 0), (2, 0)]))
+                .is_empty()
+        );
+        assert!(tracker.finish().is_empty());
+    }
+}
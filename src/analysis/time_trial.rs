@@ -0,0 +1,565 @@
+use crate::{
+    constants::TeamId,
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{
+        PacketCarTelemetryData, PacketLapData, PacketTimeTrialData, TimeTrialDataSet,
+        constants::MAX_NUM_CARS,
+    },
+    timing::LapTime,
+};
+
+/// `*_car_idx` of a ghost that isn't on track
+const NO_CAR: u8 = 255;
+
+/// Lap and sector times of a time trial data set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectorTimes {
+    pub sector1: LapTime,
+    pub sector2: LapTime,
+    pub sector3: LapTime,
+    pub lap: LapTime,
+}
+
+/// Signed milliseconds against a reference, positive when slower
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectorDeltas {
+    pub sector1: i64,
+    pub sector2: i64,
+    pub sector3: i64,
+    pub lap: i64,
+}
+
+impl SectorTimes {
+    pub fn delta(&self, reference: &SectorTimes) -> SectorDeltas {
+        let delta = |time: LapTime, reference: LapTime| {
+            time.as_millis() as i64 - reference.as_millis() as i64
+        };
+        SectorDeltas {
+            sector1: delta(self.sector1, reference.sector1),
+            sector2: delta(self.sector2, reference.sector2),
+            sector3: delta(self.sector3, reference.sector3),
+            lap: delta(self.lap, reference.lap),
+        }
+    }
+}
+
+/// A decoded [`TimeTrialDataSet`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeTrialLap {
+    pub car_idx: u8,
+    pub team: Option<TeamId>,
+    pub times: SectorTimes,
+    pub traction_control: bool,
+    pub gearbox_assist: bool,
+    pub anti_lock_brakes: bool,
+    pub equal_car_performance: bool,
+    pub custom_setup: bool,
+    pub valid: bool,
+}
+
+impl TimeTrialLap {
+    /// `None` for an empty data set, i.e. no lap set yet
+    fn from_data_set(set: &TimeTrialDataSet) -> Option<Self> {
+        let times = SectorTimes {
+            sector1: LapTime::from_millis(set.sector1_time_in_ms),
+            sector2: LapTime::from_millis(set.sector2_time_in_ms),
+            sector3: LapTime::from_millis(set.sector3_time_in_ms),
+            lap: LapTime::from_millis(set.lap_time_in_ms),
+        };
+        (!times.lap.is_zero()).then(|| Self {
+            car_idx: set.car_idx,
+            team: TeamId::try_from_id(set.team_id),
+            times,
+            traction_control: set.traction_control(),
+            gearbox_assist: set.gearbox_assist(),
+            anti_lock_brakes: set.anti_lock_brakes(),
+            equal_car_performance: set.equal_car_performance(),
+            custom_setup: set.custom_setup(),
+            valid: set.valid(),
+        })
+    }
+
+    /// Same assists and car performance setting, so the laps are comparable
+    pub fn same_conditions(&self, other: &TimeTrialLap) -> bool {
+        self.traction_control == other.traction_control
+            && self.gearbox_assist == other.gearbox_assist
+            && self.anti_lock_brakes == other.anti_lock_brakes
+            && self.equal_car_performance == other.equal_car_performance
+    }
+}
+
+/// Lap the player is compared against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reference {
+    PersonalBest,
+    Rival,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeTrialEvent {
+    NewSessionBest {
+        lap: TimeTrialLap,
+        previous: Option<TimeTrialLap>,
+    },
+    NewPersonalBest {
+        lap: TimeTrialLap,
+        previous: Option<TimeTrialLap>,
+    },
+}
+
+/// Live state of a car at one point of its lap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TracePoint {
+    pub lap_distance: f32,
+    /// Seconds since the start of the lap
+    pub lap_time: f32,
+    /// km/h
+    pub speed: u16,
+    pub throttle: f32,
+    pub brake: f32,
+}
+
+/// Player and ghost at the same lap distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GhostComparison {
+    pub player: TracePoint,
+    pub ghost: TracePoint,
+    /// Seconds the player is behind the ghost at this point, negative when ahead
+    pub delta: f32,
+}
+
+/// Time lost or gained over a stretch of the lap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentDelta {
+    pub start: f32,
+    pub end: f32,
+    /// Seconds lost to the ghost over this stretch, negative when gained
+    pub time_lost: f32,
+}
+
+/// Samples of a car's current lap, every `spacing` metres
+#[derive(Debug, Clone, Default)]
+struct LapTrace {
+    lap_num: u8,
+    points: Vec<TracePoint>,
+}
+
+impl LapTrace {
+    fn record(&mut self, lap_num: u8, point: TracePoint, spacing: f32) {
+        if lap_num != self.lap_num {
+            self.lap_num = lap_num;
+            self.points.clear();
+        }
+        // Flashback or reset to track, forget what will be driven again
+        while self
+            .points
+            .last()
+            .is_some_and(|p| p.lap_distance > point.lap_distance)
+        {
+            self.points.pop();
+        }
+        if point.lap_distance < 0.0 {
+            return;
+        }
+        let due = self
+            .points
+            .last()
+            .is_none_or(|p| point.lap_distance - p.lap_distance >= spacing);
+        if due {
+            self.points.push(point);
+        }
+    }
+
+    /// Interpolated state at a lap distance within the trace
+    fn at(&self, lap_distance: f32) -> Option<TracePoint> {
+        let after = self
+            .points
+            .iter()
+            .position(|p| p.lap_distance >= lap_distance)?;
+        let next = self.points[after];
+        let Some(previous) = after.checked_sub(1).map(|i| self.points[i]) else {
+            return (next.lap_distance == lap_distance).then_some(next);
+        };
+
+        let span = (next.lap_distance - previous.lap_distance).max(f32::EPSILON);
+        let fraction = (lap_distance - previous.lap_distance) / span;
+        let lerp = |a: f32, b: f32| a + (b - a) * fraction;
+        Some(TracePoint {
+            lap_distance,
+            lap_time: lerp(previous.lap_time, next.lap_time),
+            speed: lerp(previous.speed as f32, next.speed as f32).round() as u16,
+            throttle: lerp(previous.throttle, next.throttle),
+            brake: lerp(previous.brake, next.brake),
+        })
+    }
+}
+
+/// Compares the player's time trial laps against their personal best and the rival.
+///
+/// Sector deltas come from the time trial packet, which also tells when a new session or
+/// personal best is set. During a lap the player and the ghost cars (the lap packet's
+/// `time_trial_pb_car_idx` and `time_trial_rival_car_idx`) are sampled along lap distance,
+/// so the player's lap can be compared with a ghost's at the same point on track.
+#[derive(Debug, Clone)]
+pub struct TimeTrialAnalyser {
+    spacing: f32,
+    session_uid: Option<u64>,
+    player_idx: u8,
+    pb_idx: Option<u8>,
+    rival_idx: Option<u8>,
+    session_best: Option<TimeTrialLap>,
+    personal_best: Option<TimeTrialLap>,
+    rival: Option<TimeTrialLap>,
+    traces: [LapTrace; MAX_NUM_CARS],
+    /// Latest (speed, throttle, brake) of each car
+    telemetry: [(u16, f32, f32); MAX_NUM_CARS],
+}
+
+impl Default for TimeTrialAnalyser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeTrialAnalyser {
+    /// Metres between trace samples
+    pub const DEFAULT_SPACING: f32 = 5.0;
+
+    pub fn new() -> Self {
+        Self::with_spacing(Self::DEFAULT_SPACING)
+    }
+
+    pub fn with_spacing(spacing: f32) -> Self {
+        Self {
+            spacing: spacing.max(0.1),
+            session_uid: None,
+            player_idx: 0,
+            pb_idx: None,
+            rival_idx: None,
+            session_best: None,
+            personal_best: None,
+            rival: None,
+            traces: Default::default(),
+            telemetry: [(0, 0.0, 0.0); MAX_NUM_CARS],
+        }
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::with_spacing(self.spacing);
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    pub fn push_time_trial(&mut self, packet: &PacketTimeTrialData) -> Vec<TimeTrialEvent> {
+        self.reset_on_new_session(packet.header.session_uid);
+
+        let session_best = TimeTrialLap::from_data_set(&packet.player_session_best_data_set);
+        let personal_best = TimeTrialLap::from_data_set(&packet.personal_best_data_set);
+        self.rival = TimeTrialLap::from_data_set(&packet.rival_data_set);
+
+        let improved = |lap: Option<TimeTrialLap>, previous: Option<TimeTrialLap>| {
+            lap.filter(|lap| previous.is_none_or(|p| lap.times.lap < p.times.lap))
+        };
+        let mut events = Vec::new();
+        if let Some(lap) = improved(session_best, self.session_best) {
+            events.push(TimeTrialEvent::NewSessionBest {
+                lap,
+                previous: self.session_best,
+            });
+        }
+        // The personal best is already set when joining, which isn't an improvement
+        if self.personal_best.is_some()
+            && let Some(lap) = improved(personal_best, self.personal_best)
+        {
+            events.push(TimeTrialEvent::NewPersonalBest {
+                lap,
+                previous: self.personal_best,
+            });
+        }
+
+        self.session_best = session_best.or(self.session_best);
+        self.personal_best = personal_best.or(self.personal_best);
+        events
+    }
+
+    pub fn push_car_telemetry(&mut self, packet: &PacketCarTelemetryData) {
+        self.reset_on_new_session(packet.header.session_uid);
+
+        let car_telemetry = packet.car_telemetry_data;
+        for (latest, car) in self.telemetry.iter_mut().zip(car_telemetry.iter()) {
+            *latest = (car.speed, car.throttle, car.brake);
+        }
+    }
+
+    pub fn push_lap_data(&mut self, packet: &PacketLapData) {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+
+        let ghost =
+            |idx: u8| Some(idx).filter(|&idx| idx != NO_CAR && (idx as usize) < MAX_NUM_CARS);
+        self.player_idx = header.player_car_index;
+        self.pb_idx = ghost(packet.time_trial_pb_car_idx);
+        self.rival_idx = ghost(packet.time_trial_rival_car_idx);
+
+        let lap_data = packet.lap_data;
+        for car_idx in [Some(self.player_idx), self.pb_idx, self.rival_idx]
+            .into_iter()
+            .flatten()
+            .filter(|&idx| (idx as usize) < MAX_NUM_CARS)
+        {
+            let lap = &lap_data[car_idx as usize];
+            let (speed, throttle, brake) = self.telemetry[car_idx as usize];
+            let point = TracePoint {
+                lap_distance: lap.lap_distance,
+                lap_time: lap.current_lap_time_in_ms as f32 / 1000.0,
+                speed,
+                throttle,
+                brake,
+            };
+            self.traces[car_idx as usize].record(lap.current_lap_num, point, self.spacing);
+        }
+    }
+
+    /// Player's best lap of this session
+    pub fn session_best(&self) -> Option<&TimeTrialLap> {
+        self.session_best.as_ref()
+    }
+
+    pub fn personal_best(&self) -> Option<&TimeTrialLap> {
+        self.personal_best.as_ref()
+    }
+
+    pub fn rival(&self) -> Option<&TimeTrialLap> {
+        self.rival.as_ref()
+    }
+
+    fn reference(&self, reference: Reference) -> Option<&TimeTrialLap> {
+        match reference {
+            Reference::PersonalBest => self.personal_best.as_ref(),
+            Reference::Rival => self.rival.as_ref(),
+        }
+    }
+
+    /// Session best against the personal best or rival lap
+    pub fn sector_deltas(&self, reference: Reference) -> Option<SectorDeltas> {
+        let session_best = self.session_best.as_ref()?;
+        Some(session_best.times.delta(&self.reference(reference)?.times))
+    }
+
+    fn ghost_trace(&self, reference: Reference) -> Option<&LapTrace> {
+        let idx = match reference {
+            Reference::PersonalBest => self.pb_idx,
+            Reference::Rival => self.rival_idx,
+        }?;
+        Some(&self.traces[idx as usize])
+    }
+
+    /// Player and ghost at each sampled point of the player's current lap that the ghost
+    /// has also driven
+    pub fn compare(&self, reference: Reference) -> Vec<GhostComparison> {
+        let (Some(ghost), Some(trace)) = (
+            self.ghost_trace(reference),
+            self.traces.get(self.player_idx as usize),
+        ) else {
+            return Vec::new();
+        };
+        trace
+            .points
+            .iter()
+            .filter_map(|&player| {
+                let ghost = ghost.at(player.lap_distance)?;
+                Some(GhostComparison {
+                    player,
+                    ghost,
+                    delta: player.lap_time - ghost.lap_time,
+                })
+            })
+            .collect()
+    }
+
+    /// Seconds the player is behind the ghost at their latest sample
+    pub fn live_delta(&self, reference: Reference) -> Option<f32> {
+        self.compare(reference).last().map(|c| c.delta)
+    }
+
+    /// Time lost to the ghost over consecutive stretches of `length` metres
+    pub fn segment_deltas(&self, reference: Reference, length: f32) -> Vec<SegmentDelta> {
+        let comparisons = self.compare(reference);
+        let mut segments: Vec<SegmentDelta> = Vec::new();
+        let Some(first) = comparisons.first() else {
+            return segments;
+        };
+
+        let length = length.max(self.spacing);
+        let segment = |c: &GhostComparison| (c.player.lap_distance / length).floor();
+        let mut start = *first;
+        for current in &comparisons[1..] {
+            if segment(current) > segment(&start) {
+                segments.push(SegmentDelta {
+                    start: start.player.lap_distance,
+                    end: current.player.lap_distance,
+                    time_lost: current.delta - start.delta,
+                });
+                start = *current;
+            }
+        }
+        if let Some(last) = comparisons
+            .last()
+            .filter(|l| l.player.lap_distance > start.player.lap_distance)
+        {
+            segments.push(SegmentDelta {
+                start: start.player.lap_distance,
+                end: last.player.lap_distance,
+                time_lost: last.delta - start.delta,
+            });
+        }
+        segments
+    }
+
+    /// Stretch of `length` metres where the most time was lost
+    pub fn worst_segment(&self, reference: Reference, length: f32) -> Option<SegmentDelta> {
+        self.segment_deltas(reference, length)
+            .into_iter()
+            .max_by(|a, b| a.time_lost.total_cmp(&b.time_lost))
+    }
+}
+
+impl PacketConsumer for TimeTrialAnalyser {
+    type Event = TimeTrialEvent;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<TimeTrialEvent> {
+        match packet {
+            AnyRawPacket::TimeTrial(p) => self.push_time_trial(p),
+            AnyRawPacket::Lap(p) => {
+                self.push_lap_data(p);
+                Vec::new()
+            }
+            AnyRawPacket::CarTelemetry(p) => {
+                self.push_car_telemetry(p);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_set(lap: u32, sectors: [u32; 3]) -> TimeTrialDataSet {
+        let mut set: TimeTrialDataSet = bytemuck::Zeroable::zeroed();
+        set.lap_time_in_ms = lap;
+        set.sector1_time_in_ms = sectors[0];
+        set.sector2_time_in_ms = sectors[1];
+        set.sector3_time_in_ms = sectors[2];
+        set.valid = 1;
+        set
+    }
+
+    fn time_trial(session_best: u32, personal_best: u32) -> PacketTimeTrialData {
+        let mut packet: PacketTimeTrialData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        let sectors = |lap: u32| [lap / 3, lap / 3, lap - 2 * (lap / 3)];
+        packet.player_session_best_data_set = data_set(session_best, sectors(session_best));
+        packet.personal_best_data_set = data_set(personal_best, sectors(personal_best));
+        packet.rival_data_set = data_set(90_000, [30_000, 30_000, 30_000]);
+        packet
+    }
+
+    /// Player in car 0, personal best ghost in car 1 running 10 m ahead at twice the pace
+    fn lap_data(player_idx: u8, step: u32) -> PacketLapData {
+        let mut packet: PacketLapData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.header.player_car_index = player_idx;
+        packet.time_trial_pb_car_idx = 1;
+        packet.time_trial_rival_car_idx = NO_CAR;
+        for (lap, (distance, time)) in packet
+            .lap_data
+            .iter_mut()
+            .zip([(10 * step, 1000 * step), (10 * step + 10, 500 * step + 500)])
+        {
+            lap.current_lap_num = 1;
+            lap.lap_distance = distance as f32;
+            lap.current_lap_time_in_ms = time;
+        }
+        packet
+    }
+
+    #[test]
+    fn personal_best_on_joining_is_not_an_improvement() {
+        let mut analyser = TimeTrialAnalyser::new();
+        let events = analyser.push_time_trial(&time_trial(0, 91_000));
+        assert!(events.is_empty());
+
+        let events = analyser.push_time_trial(&time_trial(92_000, 91_000));
+        assert!(matches!(
+            &events[..],
+            [TimeTrialEvent::NewSessionBest { previous: None, .. }]
+        ));
+
+        let events = analyser.push_time_trial(&time_trial(90_500, 90_500));
+        assert!(matches!(
+            &events[..],
+            [
+                TimeTrialEvent::NewSessionBest {
+                    previous: Some(_),
+                    ..
+                },
+                TimeTrialEvent::NewPersonalBest { previous: Some(previous), .. },
+            ] if previous.times.lap == LapTime::from_millis(91_000)
+        ));
+
+        let deltas = analyser.sector_deltas(Reference::Rival).unwrap();
+        assert_eq!(deltas.lap, 500);
+        assert_eq!(deltas.sector1, 166);
+    }
+
+    #[test]
+    fn player_lap_is_compared_with_the_ghost() {
+        let mut analyser = TimeTrialAnalyser::new();
+        for step in 0..=10 {
+            analyser.push_lap_data(&lap_data(0, step));
+        }
+
+        let comparisons = analyser.compare(Reference::PersonalBest);
+        assert_eq!(comparisons.len(), 10);
+        assert_eq!(comparisons[0].player.lap_distance, 10.0);
+        assert_eq!(comparisons[0].delta, 0.5);
+        assert_eq!(analyser.live_delta(Reference::PersonalBest), Some(5.0));
+        assert!(analyser.compare(Reference::Rival).is_empty());
+
+        let segments = analyser.segment_deltas(Reference::PersonalBest, 50.0);
+        assert_eq!(
+            segments,
+            [
+                SegmentDelta {
+                    start: 10.0,
+                    end: 50.0,
+                    time_lost: 2.0,
+                },
+                SegmentDelta {
+                    start: 50.0,
+                    end: 100.0,
+                    time_lost: 2.5,
+                },
+            ]
+        );
+        assert_eq!(
+            analyser.worst_segment(Reference::PersonalBest, 50.0),
+            Some(segments[1])
+        );
+    }
+
+    #[test]
+    fn unknown_player_index_compares_nothing() {
+        let mut analyser = TimeTrialAnalyser::new();
+        for step in 0..=10 {
+            analyser.push_lap_data(&lap_data(NO_CAR, step));
+        }
+
+        assert!(analyser.compare(Reference::PersonalBest).is_empty());
+        assert_eq!(analyser.live_delta(Reference::PersonalBest), None);
+    }
+}
@@ -1,6 +1,9 @@
 pub mod packet;
 
+pub mod analysis;
 pub mod model;
 pub mod raw;
+pub mod store;
+pub mod strategy;
 
 pub use model::*;
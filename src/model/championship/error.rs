@@ -0,0 +1,30 @@
+use core::fmt;
+
+#[derive(Debug)]
+pub enum ChampionshipError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl std::error::Error for ChampionshipError {}
+
+impl fmt::Display for ChampionshipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChampionshipError::Io(e) => write!(f, "I/O error: {}", e),
+            ChampionshipError::Json(e) => write!(f, "JSON error: {}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ChampionshipError {
+    fn from(e: std::io::Error) -> Self {
+        ChampionshipError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ChampionshipError {
+    fn from(e: serde_json::Error) -> Self {
+        ChampionshipError::Json(e)
+    }
+}
@@ -0,0 +1,9 @@
+#[cfg(feature = "serde")]
+pub mod error;
+pub mod standings;
+pub mod tracker;
+
+#[cfg(feature = "serde")]
+pub use error::*;
+pub use standings::*;
+pub use tracker::*;
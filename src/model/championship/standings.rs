@@ -0,0 +1,103 @@
+use std::cmp::Ordering;
+
+use crate::{
+    constants::{Platform, TeamId},
+    raw::{ParticipantData, constants::NETWORK_HUMAN_DRIVER_ID},
+};
+
+/// Identifies a driver across sessions, where car indices and network ids change
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DriverKey {
+    /// Game driver, by `driver_id`
+    Driver(u8),
+    /// Network human, by name, platform and race number
+    Human {
+        name: String,
+        platform: Option<Platform>,
+        race_number: u8,
+    },
+}
+
+impl DriverKey {
+    pub fn of(participant: &ParticipantData) -> Self {
+        match participant.driver_id {
+            NETWORK_HUMAN_DRIVER_ID => Self::Human {
+                name: participant.name_str().to_owned(),
+                platform: participant.platform(),
+                race_number: participant.race_number,
+            },
+            id => Self::Driver(id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriverStanding {
+    pub position: usize,
+    pub driver: DriverKey,
+    /// Latest name and team
+    pub name: String,
+    pub team: Option<TeamId>,
+    pub points: u32,
+    pub wins: u32,
+    /// Number of race finishes in each position, P1 first, for the countback
+    pub finishes: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstructorStanding {
+    pub position: usize,
+    pub team: TeamId,
+    pub points: u32,
+    pub wins: u32,
+    /// Number of race finishes of either car in each position, P1 first
+    pub finishes: Vec<u32>,
+}
+
+/// Records a finish in the countback table
+pub(crate) fn add_finish(finishes: &mut Vec<u32>, position: u8) {
+    let Some(idx) = (position as usize).checked_sub(1) else {
+        return;
+    };
+    if finishes.len() <= idx {
+        finishes.resize(idx + 1, 0);
+    }
+    finishes[idx] += 1;
+}
+
+/// Orders by points, then by countback: most wins, then most second places and so on
+pub(crate) fn compare(points: (u32, &[u32]), other: (u32, &[u32])) -> Ordering {
+    other.0.cmp(&points.0).then_with(|| {
+        let len = points.1.len().max(other.1.len());
+        (0..len)
+            .map(|i| {
+                let count = |finishes: &[u32]| finishes.get(i).copied().unwrap_or(0);
+                count(other.1).cmp(&count(points.1))
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finishes_grow_to_the_position() {
+        let mut finishes = Vec::new();
+        add_finish(&mut finishes, 3);
+        add_finish(&mut finishes, 1);
+        add_finish(&mut finishes, 0);
+        assert_eq!(finishes, [1, 0, 1]);
+    }
+
+    #[test]
+    fn points_then_countback() {
+        assert_eq!(compare((30, &[]), (25, &[1])), Ordering::Less);
+        assert_eq!(compare((25, &[0, 2]), (25, &[1])), Ordering::Greater);
+        assert_eq!(compare((25, &[1, 0, 1]), (25, &[1])), Ordering::Less);
+        assert_eq!(compare((25, &[1]), (25, &[1, 0, 0])), Ordering::Equal);
+    }
+}
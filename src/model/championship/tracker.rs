@@ -0,0 +1,564 @@
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::{fs, path::Path};
+
+#[cfg(feature = "serde")]
+use super::ChampionshipError;
+use super::{ConstructorStanding, DriverKey, DriverStanding, add_finish, compare};
+use crate::{
+    constants::{Formula, SessionType, TeamId, TrackId},
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{PacketFinalClassificationData, PacketParticipantsData, PacketSessionData},
+    results::{PointsSystem, RaceResult},
+    weekend::{WeekendPlan, is_race},
+};
+
+/// A driver's result in a scored session
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionEntry {
+    pub driver: DriverKey,
+    pub name: String,
+    pub team: Option<TeamId>,
+    pub position: u8,
+    /// Took the chequered flag, i.e. the position counts for the countback
+    pub classified: bool,
+    pub points: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionRecord {
+    pub session_link_identifier: u32,
+    pub session_type: Option<SessionType>,
+    pub sprint: bool,
+    pub entries: Vec<SessionEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Weekend {
+    pub weekend_link_identifier: u32,
+    pub track_id: Option<TrackId>,
+    pub sessions: Vec<SessionRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Season {
+    pub season_link_identifier: u32,
+    pub weekends: Vec<Weekend>,
+}
+
+/// Session the packets are currently from
+#[derive(Debug, Clone, Copy)]
+struct CurrentSession {
+    session_uid: u64,
+    season_link_identifier: u32,
+    weekend_link_identifier: u32,
+    session_link_identifier: u32,
+    session_type: Option<SessionType>,
+    track_id: Option<TrackId>,
+    formula: Option<Formula>,
+    sprint: bool,
+}
+
+/// Championship standings built from race classifications, across game sessions and runs.
+///
+/// Sessions are grouped into weekends and seasons with the link identifiers of the session
+/// packet, which the game keeps across saves. A classification replaces any earlier one of
+/// the same session, so restarts and repeated packets are only counted once. Sprints score
+/// with `sprint_points` and are left out of the countback. Network humans are told apart
+/// by name, platform and race number, see [`DriverKey`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Championship {
+    pub points: PointsSystem,
+    pub sprint_points: PointsSystem,
+    seasons: Vec<Season>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    current: Option<CurrentSession>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    participants: Option<PacketParticipantsData>,
+}
+
+impl Default for Championship {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Championship {
+    pub fn new() -> Self {
+        Self::with_points(PointsSystem::grand_prix(), PointsSystem::sprint())
+    }
+
+    pub fn with_points(points: PointsSystem, sprint_points: PointsSystem) -> Self {
+        Self {
+            points,
+            sprint_points,
+            seasons: Vec::new(),
+            current: None,
+            participants: None,
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ChampionshipError> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Writes to a temporary file first, so an interrupted save keeps the previous state
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ChampionshipError> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    pub fn push_session(&mut self, packet: &PacketSessionData) {
+        self.current = Some(CurrentSession {
+            session_uid: packet.header.session_uid,
+            season_link_identifier: packet.season_link_identifier,
+            weekend_link_identifier: packet.weekend_link_identifier,
+            session_link_identifier: packet.session_link_identifier,
+            session_type: SessionType::try_from_id(packet.session_type),
+            track_id: u8::try_from(packet.track_id)
+                .ok()
+                .and_then(TrackId::try_from_id),
+            formula: packet.formula(),
+            sprint: WeekendPlan::from_session(packet).is_sprint_race(),
+        });
+    }
+
+    pub fn push_participants(&mut self, packet: &PacketParticipantsData) {
+        self.participants = Some(*packet);
+    }
+
+    /// Scores a race classification, `None` if it isn't from a race or the session and
+    /// participants of its session haven't been received
+    pub fn push_final_classification(
+        &mut self,
+        packet: &PacketFinalClassificationData,
+    ) -> Option<&SessionRecord> {
+        let session_uid = packet.header.session_uid;
+        let current = self.current.filter(|c| c.session_uid == session_uid)?;
+        let participants = self
+            .participants
+            .filter(|p| p.header.session_uid == session_uid)?;
+        if !current.session_type.is_some_and(is_race) {
+            return None;
+        }
+
+        let points = match current.sprint {
+            true => &self.sprint_points,
+            false => &self.points,
+        };
+        let formula = current.formula?;
+        let result = RaceResult::new(packet, &participants, formula, points);
+
+        let participants_data = participants.participants;
+        let entries = result
+            .rows
+            .iter()
+            .map(|row| SessionEntry {
+                driver: DriverKey::of(&participants_data[row.car_idx as usize]),
+                name: row.name.clone(),
+                team: row.team,
+                position: row.position,
+                classified: row.gap.is_some(),
+                points: row.points,
+            })
+            .collect();
+
+        let record = SessionRecord {
+            session_link_identifier: current.session_link_identifier,
+            session_type: current.session_type,
+            sprint: current.sprint,
+            entries,
+        };
+        Some(self.insert(&current, record))
+    }
+
+    fn insert(&mut self, current: &CurrentSession, record: SessionRecord) -> &SessionRecord {
+        let season = match self
+            .seasons
+            .iter()
+            .position(|s| s.season_link_identifier == current.season_link_identifier)
+        {
+            Some(i) => &mut self.seasons[i],
+            None => {
+                self.seasons.push(Season {
+                    season_link_identifier: current.season_link_identifier,
+                    weekends: Vec::new(),
+                });
+                self.seasons.last_mut().unwrap()
+            }
+        };
+
+        let weekend = match season
+            .weekends
+            .iter()
+            .position(|w| w.weekend_link_identifier == current.weekend_link_identifier)
+        {
+            Some(i) => &mut season.weekends[i],
+            None => {
+                season.weekends.push(Weekend {
+                    weekend_link_identifier: current.weekend_link_identifier,
+                    track_id: current.track_id,
+                    sessions: Vec::new(),
+                });
+                season.weekends.last_mut().unwrap()
+            }
+        };
+
+        let sessions = &mut weekend.sessions;
+        match sessions
+            .iter()
+            .position(|s| s.session_link_identifier == record.session_link_identifier)
+        {
+            Some(i) => {
+                sessions[i] = record;
+                &sessions[i]
+            }
+            None => {
+                sessions.push(record);
+                sessions.last().unwrap()
+            }
+        }
+    }
+
+    /// Seasons in the order they were first scored
+    pub fn seasons(&self) -> &[Season] {
+        &self.seasons
+    }
+
+    pub fn season(&self, season_link_identifier: u32) -> Option<&Season> {
+        self.seasons
+            .iter()
+            .find(|s| s.season_link_identifier == season_link_identifier)
+    }
+
+    /// Season of the session the packets are currently from
+    pub fn current_season(&self) -> Option<&Season> {
+        self.season(self.current?.season_link_identifier)
+    }
+
+    fn entries(season: &Season) -> impl Iterator<Item = (&SessionRecord, &SessionEntry)> {
+        season
+            .weekends
+            .iter()
+            .flat_map(|w| &w.sessions)
+            .flat_map(|s| s.entries.iter().map(move |e| (s, e)))
+    }
+
+    pub fn driver_standings(&self, season_link_identifier: u32) -> Vec<DriverStanding> {
+        let Some(season) = self.season(season_link_identifier) else {
+            return Vec::new();
+        };
+
+        let mut drivers: Vec<DriverStanding> = Vec::new();
+        let mut index: HashMap<DriverKey, usize> = HashMap::new();
+        for (session, entry) in Self::entries(season) {
+            let idx = *index.entry(entry.driver.clone()).or_insert_with(|| {
+                drivers.push(DriverStanding {
+                    position: 0,
+                    driver: entry.driver.clone(),
+                    name: String::new(),
+                    team: None,
+                    points: 0,
+                    wins: 0,
+                    finishes: Vec::new(),
+                });
+                drivers.len() - 1
+            });
+
+            let standing = &mut drivers[idx];
+            standing.name = entry.name.clone();
+            standing.team = entry.team.or(standing.team);
+            standing.points += entry.points;
+            if entry.classified && !session.sprint {
+                add_finish(&mut standing.finishes, entry.position);
+                standing.wins += (entry.position == 1) as u32;
+            }
+        }
+
+        drivers.sort_by(|a, b| {
+            compare((a.points, &a.finishes), (b.points, &b.finishes))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        for (i, standing) in drivers.iter_mut().enumerate() {
+            standing.position = i + 1;
+        }
+        drivers
+    }
+
+    pub fn constructor_standings(&self, season_link_identifier: u32) -> Vec<ConstructorStanding> {
+        let Some(season) = self.season(season_link_identifier) else {
+            return Vec::new();
+        };
+
+        let mut teams: Vec<ConstructorStanding> = Vec::new();
+        for (session, entry) in Self::entries(season) {
+            let Some(team) = entry.team else {
+                continue;
+            };
+            let idx = match teams.iter().position(|t| t.team == team) {
+                Some(idx) => idx,
+                None => {
+                    teams.push(ConstructorStanding {
+                        position: 0,
+                        team,
+                        points: 0,
+                        wins: 0,
+                        finishes: Vec::new(),
+                    });
+                    teams.len() - 1
+                }
+            };
+
+            let standing = &mut teams[idx];
+            standing.points += entry.points;
+            if entry.classified && !session.sprint {
+                add_finish(&mut standing.finishes, entry.position);
+                standing.wins += (entry.position == 1) as u32;
+            }
+        }
+
+        teams.sort_by(|a, b| {
+            compare((a.points, &a.finishes), (b.points, &b.finishes))
+                .then_with(|| a.team.name().cmp(b.team.name()))
+        });
+        for (i, standing) in teams.iter_mut().enumerate() {
+            standing.position = i + 1;
+        }
+        teams
+    }
+}
+
+impl PacketConsumer for Championship {
+    type Event = SessionRecord;
+
+    /// Yields the record of each scored race classification
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<SessionRecord> {
+        match packet {
+            AnyRawPacket::Session(p) => {
+                self.push_session(p);
+                Vec::new()
+            }
+            AnyRawPacket::Participants(p) => {
+                self.push_participants(p);
+                Vec::new()
+            }
+            AnyRawPacket::FinalClassification(p) => self
+                .push_final_classification(p)
+                .cloned()
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::Platform, raw::constants::NETWORK_HUMAN_DRIVER_ID};
+
+    const SEASON: u32 = 7;
+
+    /// Sprint weekend of a sprint (`Race`) then the main race (`Race2`)
+    fn session(session_uid: u64, session_type: u8, weekend: u32) -> PacketSessionData {
+        let mut packet: PacketSessionData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        packet.session_type = session_type;
+        packet.season_link_identifier = SEASON;
+        packet.weekend_link_identifier = weekend;
+        packet.session_link_identifier = session_uid as u32;
+        packet.num_sessions_in_weekend = 3;
+        packet.weekend_structure[..3].copy_from_slice(&[5, 15, 16]);
+        packet
+    }
+
+    /// Cars 0 and 1 are game drivers of Mercedes, car 2 a human of Ferrari
+    fn participants(session_uid: u64) -> PacketParticipantsData {
+        let mut packet: PacketParticipantsData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        for (idx, (driver_id, team_id, name)) in [
+            (0, 0, "SAINZ"),
+            (3, 0, "ALONSO"),
+            (NETWORK_HUMAN_DRIVER_ID, 1, "Player"),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let participant = &mut packet.participants[idx];
+            participant.driver_id = driver_id;
+            participant.team_id = team_id;
+            participant.race_number = idx as u8 + 1;
+            participant.platform = Platform::Steam.id();
+            participant.name[..name.len()].copy_from_slice(name.as_bytes());
+        }
+        packet
+    }
+
+    /// Finishing positions of cars 0, 1 and 2
+    fn classification(session_uid: u64, positions: [u8; 3]) -> PacketFinalClassificationData {
+        let mut packet: PacketFinalClassificationData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        packet.num_cars = 3;
+        for (data, position) in packet.classification_data.iter_mut().zip(positions) {
+            data.position = position;
+            data.num_laps = 10;
+            data.result_status = 3;
+            data.total_race_time = position as f64;
+        }
+        packet
+    }
+
+    fn score(
+        championship: &mut Championship,
+        session_uid: u64,
+        session_type: u8,
+        positions: [u8; 3],
+    ) {
+        championship.push_session(&session(session_uid, session_type, 1));
+        championship.push_participants(&participants(session_uid));
+        championship.push_final_classification(&classification(session_uid, positions));
+    }
+
+    fn standings(championship: &Championship) -> Vec<(String, u32, u32)> {
+        championship
+            .driver_standings(SEASON)
+            .into_iter()
+            .map(|s| (s.name, s.points, s.wins))
+            .collect()
+    }
+
+    #[test]
+    fn sprint_and_race_are_scored_separately() {
+        let mut championship = Championship::new();
+        score(&mut championship, 1, 15, [3, 2, 1]);
+        score(&mut championship, 2, 16, [1, 3, 2]);
+
+        let season = championship.current_season().unwrap();
+        assert_eq!(season.weekends.len(), 1);
+        assert!(season.weekends[0].sessions[0].sprint);
+        assert!(!season.weekends[0].sessions[1].sprint);
+
+        // The sprint win doesn't count as a win
+        assert_eq!(
+            standings(&championship),
+            [
+                ("SAINZ".to_string(), 6 + 25, 1),
+                ("Player".to_string(), 8 + 18, 0),
+                ("ALONSO".to_string(), 7 + 15, 0),
+            ]
+        );
+        let constructors = championship.constructor_standings(SEASON);
+        assert_eq!(constructors[0].team, TeamId::Mercedes);
+        assert_eq!(constructors[0].points, 6 + 25 + 7 + 15);
+        assert_eq!(constructors[1].points, 8 + 18);
+    }
+
+    #[test]
+    fn repeated_classification_replaces_the_session() {
+        let mut championship = Championship::new();
+        score(&mut championship, 2, 16, [1, 2, 3]);
+        score(&mut championship, 2, 16, [3, 2, 1]);
+
+        let standings = standings(&championship);
+        assert_eq!(standings[0], ("Player".to_string(), 25, 1));
+        assert_eq!(standings[2], ("SAINZ".to_string(), 15, 0));
+    }
+
+    #[test]
+    fn classification_needs_a_race_with_its_session_and_participants() {
+        let mut championship = Championship::new();
+        assert!(
+            championship
+                .push_final_classification(&classification(1, [1, 2, 3]))
+                .is_none()
+        );
+
+        championship.push_session(&session(1, 5, 1));
+        championship.push_participants(&participants(1));
+        assert!(
+            championship
+                .push_final_classification(&classification(1, [1, 2, 3]))
+                .is_none()
+        );
+
+        championship.push_session(&session(2, 16, 1));
+        assert!(
+            championship
+                .push_final_classification(&classification(2, [1, 2, 3]))
+                .is_none()
+        );
+        championship.push_participants(&participants(2));
+        let record = championship.push_final_classification(&classification(2, [1, 2, 3]));
+        assert_eq!(
+            record.map(|r| r.entries[2].driver.clone()),
+            Some(DriverKey::Human {
+                name: "Player".to_string(),
+                platform: Some(Platform::Steam),
+                race_number: 3,
+            })
+        );
+        assert!(championship.driver_standings(SEASON + 1).is_empty());
+    }
+
+    #[test]
+    fn humans_of_the_same_name_are_kept_apart() {
+        let mut championship = Championship::new();
+        championship.push_session(&session(2, 16, 1));
+        let mut packet = participants(2);
+        packet.participants[0].driver_id = NETWORK_HUMAN_DRIVER_ID;
+        packet.participants[0].name = packet.participants[2].name;
+        championship.push_participants(&packet);
+        championship.push_final_classification(&classification(2, [1, 2, 3]));
+
+        let players: Vec<_> = championship
+            .driver_standings(SEASON)
+            .into_iter()
+            .filter(|s| s.name == "Player")
+            .map(|s| s.points)
+            .collect();
+        assert_eq!(players, [25, 15]);
+    }
+
+    #[test]
+    fn consumes_packets_and_yields_scored_sessions() {
+        let mut championship = Championship::new();
+        let packets = [
+            AnyRawPacket::Session(session(2, 16, 1)),
+            AnyRawPacket::Participants(participants(2)),
+            AnyRawPacket::FinalClassification(classification(2, [1, 2, 3])),
+        ];
+        let records: Vec<_> = packets
+            .iter()
+            .flat_map(|packet| championship.consume(packet))
+            .collect();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].entries[0].driver, DriverKey::Driver(0));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn save_and_load_keep_the_seasons() {
+        let mut championship = Championship::new();
+        score(&mut championship, 2, 16, [1, 2, 3]);
+
+        let path = std::env::temp_dir().join(format!("championship-{}.json", std::process::id()));
+        championship.save(&path).unwrap();
+        let loaded = Championship::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.seasons(), championship.seasons());
+        assert_eq!(loaded.points, championship.points);
+    }
+}
@@ -0,0 +1,25 @@
+use crate::utils::define_appendix;
+
+define_appendix!(BrakingAssist {
+    0 => Off,
+    1 => Low,
+    2 => Medium,
+    3 => High
+});
+
+define_appendix!(GearboxAssist {
+    1 => Manual,
+    2 => ManualSuggestedGear => "Manual & Suggested Gear",
+    3 => Auto
+});
+
+define_appendix!(DynamicRacingLine {
+    0 => Off,
+    1 => CornersOnly => "Corners Only",
+    2 => Full
+});
+
+define_appendix!(DynamicRacingLineType {
+    0 => TwoD => "2D",
+    1 => ThreeD => "3D"
+});
@@ -1,6 +1,7 @@
 use bitflags::bitflags;
 
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct ButtonFlags: u32 {
         const CROSS_A        = 0x00000001;
         const TRIANGLE_Y     = 0x00000002;
@@ -36,3 +37,15 @@ bitflags! {
         const UDP_ACTION_12  = 0x80000000;
     }
 }
+
+impl ButtonFlags {
+    /// `UDP_ACTION_1` to `UDP_ACTION_12`, `None` outside `1..=12`
+    pub fn udp_action(number: u8) -> Option<ButtonFlags> {
+        match number {
+            1..=12 => Some(ButtonFlags::from_bits_retain(
+                ButtonFlags::UDP_ACTION_1.bits() << (number - 1),
+            )),
+            _ => None,
+        }
+    }
+}
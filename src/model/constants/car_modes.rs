@@ -0,0 +1,21 @@
+use crate::utils::define_appendix;
+
+define_appendix!(TractionControl {
+    0 => Off,
+    1 => Medium,
+    2 => Full
+});
+
+define_appendix!(FuelMix {
+    0 => Lean,
+    1 => Standard,
+    2 => Rich,
+    3 => Max
+});
+
+define_appendix!(ErsDeployMode {
+    0 => None,
+    1 => Medium,
+    2 => Hotlap,
+    3 => Overtake
+});
@@ -0,0 +1,98 @@
+use crate::utils::define_appendix;
+
+define_appendix!(SessionLength {
+    0 => None,
+    2 => VeryShort => "Very Short",
+    3 => Short,
+    4 => Medium,
+    5 => MediumLong => "Medium Long",
+    6 => Long,
+    7 => Full
+});
+
+define_appendix!(SpeedUnit {
+    0 => Mph => "MPH",
+    1 => Kph => "KPH"
+});
+
+define_appendix!(TemperatureUnit {
+    0 => Celsius,
+    1 => Fahrenheit
+});
+
+define_appendix!(RecoveryMode {
+    0 => None,
+    1 => Flashbacks,
+    2 => AutoRecovery => "Auto-recovery"
+});
+
+define_appendix!(FlashbackLimit {
+    0 => Low,
+    1 => Medium,
+    2 => High,
+    3 => Unlimited
+});
+
+define_appendix!(SurfaceSimulation {
+    0 => Simplified,
+    1 => Realistic
+});
+
+define_appendix!(LowFuelMode {
+    0 => Easy,
+    1 => Hard
+});
+
+define_appendix!(RaceStarts {
+    0 => Manual,
+    1 => Assisted
+});
+
+define_appendix!(TyreTemperatureSimulation {
+    0 => SurfaceOnly => "Surface Only",
+    1 => SurfaceAndCarcass => "Surface & Carcass"
+});
+
+define_appendix!(CarDamage {
+    0 => Off,
+    1 => Reduced,
+    2 => Standard,
+    3 => Simulation
+});
+
+define_appendix!(CarDamageRate {
+    0 => Reduced,
+    1 => Standard,
+    2 => Simulation
+});
+
+define_appendix!(Collisions {
+    0 => Off,
+    1 => PlayerToPlayerOff => "Player-to-Player Off",
+    2 => On
+});
+
+define_appendix!(CornerCuttingStringency {
+    0 => Regular,
+    1 => Strict
+});
+
+define_appendix!(PitStopExperience {
+    0 => Automatic,
+    1 => Broadcast,
+    2 => Immersive
+});
+
+// How often safety cars and red flags are called
+define_appendix!(EventFrequency {
+    0 => Off,
+    1 => Reduced,
+    2 => Standard,
+    3 => Increased
+});
+
+// Presentation of the safety car and formation lap
+define_appendix!(Experience {
+    0 => Broadcast,
+    1 => Immersive
+});
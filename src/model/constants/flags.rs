@@ -0,0 +1,9 @@
+use crate::utils::define_appendix;
+
+// The packets use -1 for an invalid or unknown flag, which has no variant here
+define_appendix!(FiaFlag {
+    0 => NoFlag => "None",
+    1 => Green,
+    2 => Blue,
+    3 => Yellow
+});
@@ -0,0 +1,12 @@
+use crate::utils::define_appendix;
+
+define_appendix!(Formula {
+    0 => F1Modern => "F1 Modern",
+    1 => F1Classic => "F1 Classic",
+    2 => F2,
+    3 => F1Generic => "F1 Generic",
+    4 => Beta,
+    6 => Esports,
+    8 => F1World => "F1 World",
+    9 => F1Elimination => "F1 Elimination"
+});
@@ -0,0 +1,11 @@
+use bitflags::bitflags;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct LapValidFlags: u8 {
+        const LAP      = 0x01;
+        const SECTOR_1 = 0x02;
+        const SECTOR_2 = 0x04;
+        const SECTOR_3 = 0x08;
+    }
+}
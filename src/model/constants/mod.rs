@@ -1,23 +1,45 @@
+pub mod assists;
 pub mod button_flags;
+pub mod car_modes;
+pub mod difficulty;
 pub mod drivers;
+pub mod flags;
+pub mod formulas;
 pub mod game_modes;
 pub mod infringements;
+pub mod lap_valid_flags;
 pub mod nationalities;
 pub mod penalties;
+pub mod platforms;
+pub mod race_control;
 pub mod rulesets;
 pub mod sessions;
+pub mod statuses;
 pub mod surfaces;
 pub mod teams;
 pub mod tracks;
+pub mod tyres;
+pub mod weather;
 
+pub use assists::*;
 pub use button_flags::*;
+pub use car_modes::*;
+pub use difficulty::*;
 pub use drivers::*;
+pub use flags::*;
+pub use formulas::*;
 pub use game_modes::*;
 pub use infringements::*;
+pub use lap_valid_flags::*;
 pub use nationalities::*;
 pub use penalties::*;
+pub use platforms::*;
+pub use race_control::*;
 pub use rulesets::*;
 pub use sessions::*;
+pub use statuses::*;
 pub use surfaces::*;
 pub use teams::*;
 pub use tracks::*;
+pub use tyres::*;
+pub use weather::*;
@@ -0,0 +1,9 @@
+use crate::utils::define_appendix;
+
+define_appendix!(Platform {
+    1 => Steam,
+    3 => PlayStation,
+    4 => Xbox,
+    6 => Origin,
+    255 => Unknown
+});
@@ -0,0 +1,23 @@
+use crate::utils::define_appendix;
+
+// Used by both the session packet and the SCAR event
+define_appendix!(SafetyCarType {
+    0 => None,
+    1 => Full,
+    2 => Virtual,
+    3 => FormationLap => "Formation Lap"
+});
+
+define_appendix!(SafetyCarEventType {
+    0 => Deployed,
+    1 => Returning,
+    2 => Returned,
+    3 => ResumeRace => "Resume Race"
+});
+
+define_appendix!(DrsDisabledReason {
+    0 => WetTrack => "Wet Track",
+    1 => SafetyCarDeployed => "Safety Car Deployed",
+    2 => RedFlag => "Red Flag",
+    3 => MinLapNotReached => "Min Lap Not Reached"
+});
@@ -0,0 +1,99 @@
+use crate::utils::define_appendix;
+
+define_appendix!(ResultStatus {
+    0 => Invalid,
+    1 => Inactive,
+    2 => Active,
+    3 => Finished,
+    4 => DidNotFinish => "Did Not Finish",
+    5 => Disqualified,
+    6 => NotClassified => "Not Classified",
+    7 => Retired
+});
+
+impl ResultStatus {
+    /// The car has taken part in the session, whether or not it is still running
+    pub fn is_participating(&self) -> bool {
+        !matches!(self, Self::Invalid | Self::Inactive)
+    }
+
+    /// The car is running or has taken the chequered flag
+    pub fn is_running(&self) -> bool {
+        matches!(self, Self::Active | Self::Finished)
+    }
+}
+
+define_appendix!(ResultReason {
+    0 => Invalid,
+    1 => Retired,
+    2 => Finished,
+    3 => TerminalDamage => "Terminal Damage",
+    4 => Inactive,
+    5 => NotEnoughLapsCompleted => "Not Enough Laps Completed",
+    6 => BlackFlagged => "Black Flagged",
+    7 => RedFlagged => "Red Flagged",
+    8 => MechanicalFailure => "Mechanical Failure",
+    9 => SessionSkipped => "Session Skipped",
+    10 => SessionSimulated => "Session Simulated"
+});
+
+define_appendix!(DriverStatus {
+    0 => InGarage => "In Garage",
+    1 => FlyingLap => "Flying Lap",
+    2 => InLap => "In Lap",
+    3 => OutLap => "Out Lap",
+    4 => OnTrack => "On Track"
+});
+
+define_appendix!(PitStatus {
+    0 => None,
+    1 => Pitting,
+    2 => InPitArea => "In Pit Area"
+});
+
+impl PitStatus {
+    pub fn is_in_pits(&self) -> bool {
+        !matches!(self, Self::None)
+    }
+}
+
+define_appendix!(ReadyStatus {
+    0 => NotReady => "Not Ready",
+    1 => Ready,
+    2 => Spectating
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_round_trip_and_unknown_ids_are_rejected() {
+        for id in 0..=7 {
+            assert_eq!(ResultStatus::try_from_id(id).map(|s| s.id()), Some(id));
+        }
+        assert_eq!(ResultStatus::try_from_id(8), None);
+        assert_eq!(
+            ResultReason::try_from_id(10),
+            Some(ResultReason::SessionSimulated)
+        );
+        assert_eq!(ResultReason::try_from_id(11), None);
+        assert_eq!(DriverStatus::from(1).name(), "Flying Lap");
+        assert_eq!(ReadyStatus::Spectating.name(), "Spectating");
+    }
+
+    #[test]
+    fn result_status_predicates() {
+        assert!(!ResultStatus::Inactive.is_participating());
+        assert!(ResultStatus::Retired.is_participating());
+        assert!(!ResultStatus::Retired.is_running());
+        assert!(ResultStatus::Finished.is_running());
+    }
+
+    #[test]
+    fn pit_status_in_pits() {
+        assert!(!PitStatus::None.is_in_pits());
+        assert!(PitStatus::Pitting.is_in_pits());
+        assert!(PitStatus::InPitArea.is_in_pits());
+    }
+}
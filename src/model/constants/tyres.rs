@@ -0,0 +1,325 @@
+use crate::constants::Formula;
+
+/// RGB colour used to draw a compound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Colour {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Colour {
+    pub const fn new(red: u8, green: u8, blue: u8) -> Self {
+        Self { red, green, blue }
+    }
+
+    /// `#rrggbb`
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+const SOFT: Colour = Colour::new(218, 41, 28);
+const MEDIUM: Colour = Colour::new(255, 210, 0);
+const HARD: Colour = Colour::new(240, 240, 240);
+const INTERMEDIATE: Colour = Colour::new(67, 176, 42);
+const WET: Colour = Colour::new(0, 103, 173);
+const SUPER_SOFT: Colour = Colour::new(200, 80, 190);
+const CLASSIC_DRY: Colour = Colour::new(90, 90, 90);
+
+/// Compound codes used by a formula. The codes overlap between formulas, so they can't be
+/// decoded without it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompoundSet {
+    Modern,
+    Classic,
+    F2,
+}
+
+impl From<Formula> for CompoundSet {
+    fn from(formula: Formula) -> Self {
+        match formula {
+            Formula::F1Classic => Self::Classic,
+            Formula::F2 => Self::F2,
+            _ => Self::Modern,
+        }
+    }
+}
+
+/// Compound actually fitted, `actual_tyre_compound`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ActualTyreCompound {
+    C0,
+    C1,
+    C2,
+    C3,
+    C4,
+    C5,
+    C6,
+    Intermediate,
+    Wet,
+    ClassicDry,
+    ClassicWet,
+    F2SuperSoft,
+    F2Soft,
+    F2Medium,
+    F2Hard,
+    F2Wet,
+}
+
+impl ActualTyreCompound {
+    /// `None` if the id is unknown for the formula
+    pub fn try_from_id(formula: Formula, id: u8) -> Option<Self> {
+        let compound = match (CompoundSet::from(formula), id) {
+            (CompoundSet::Modern, 16) => Self::C5,
+            (CompoundSet::Modern, 17) => Self::C4,
+            (CompoundSet::Modern, 18) => Self::C3,
+            (CompoundSet::Modern, 19) => Self::C2,
+            (CompoundSet::Modern, 20) => Self::C1,
+            (CompoundSet::Modern, 21) => Self::C0,
+            (CompoundSet::Modern, 22) => Self::C6,
+            (CompoundSet::Modern, 7) => Self::Intermediate,
+            (CompoundSet::Modern, 8) => Self::Wet,
+            (CompoundSet::Classic, 9) => Self::ClassicDry,
+            (CompoundSet::Classic, 10) => Self::ClassicWet,
+            (CompoundSet::F2, 11) => Self::F2SuperSoft,
+            (CompoundSet::F2, 12) => Self::F2Soft,
+            (CompoundSet::F2, 13) => Self::F2Medium,
+            (CompoundSet::F2, 14) => Self::F2Hard,
+            (CompoundSet::F2, 15) => Self::F2Wet,
+            _ => return None,
+        };
+        Some(compound)
+    }
+
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::C5 => 16,
+            Self::C4 => 17,
+            Self::C3 => 18,
+            Self::C2 => 19,
+            Self::C1 => 20,
+            Self::C0 => 21,
+            Self::C6 => 22,
+            Self::Intermediate => 7,
+            Self::Wet => 8,
+            Self::ClassicDry => 9,
+            Self::ClassicWet => 10,
+            Self::F2SuperSoft => 11,
+            Self::F2Soft => 12,
+            Self::F2Medium => 13,
+            Self::F2Hard => 14,
+            Self::F2Wet => 15,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::C0 => "C0",
+            Self::C1 => "C1",
+            Self::C2 => "C2",
+            Self::C3 => "C3",
+            Self::C4 => "C4",
+            Self::C5 => "C5",
+            Self::C6 => "C6",
+            Self::Intermediate => "Intermediate",
+            Self::Wet | Self::ClassicWet | Self::F2Wet => "Wet",
+            Self::ClassicDry => "Dry",
+            Self::F2SuperSoft => "Super Soft",
+            Self::F2Soft => "Soft",
+            Self::F2Medium => "Medium",
+            Self::F2Hard => "Hard",
+        }
+    }
+
+    pub fn letter(&self) -> &'static str {
+        match self {
+            Self::Intermediate => "I",
+            Self::Wet | Self::ClassicWet | Self::F2Wet => "W",
+            Self::ClassicDry => "D",
+            Self::F2SuperSoft => "SS",
+            Self::F2Soft => "S",
+            Self::F2Medium => "M",
+            Self::F2Hard => "H",
+            _ => self.name(),
+        }
+    }
+
+    /// The C compounds have no colour of their own, they are shown as soft, medium or
+    /// hard depending on the weekend's nomination. They get the band they usually fall in.
+    pub fn colour(&self) -> Colour {
+        match self {
+            Self::C0 | Self::C1 | Self::F2Hard => HARD,
+            Self::C2 | Self::C3 | Self::F2Medium => MEDIUM,
+            Self::C4 | Self::C5 | Self::C6 | Self::F2Soft => SOFT,
+            Self::Intermediate => INTERMEDIATE,
+            Self::Wet | Self::ClassicWet | Self::F2Wet => WET,
+            Self::ClassicDry => CLASSIC_DRY,
+            Self::F2SuperSoft => SUPER_SOFT,
+        }
+    }
+
+    pub fn is_wet(&self) -> bool {
+        matches!(
+            self,
+            Self::Intermediate | Self::Wet | Self::ClassicWet | Self::F2Wet
+        )
+    }
+}
+
+/// Compound as shown to the player, `visual_tyre_compound`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum VisualTyreCompound {
+    Soft,
+    Medium,
+    Hard,
+    Intermediate,
+    Wet,
+    ClassicDry,
+    ClassicWet,
+    F2SuperSoft,
+    F2Soft,
+    F2Medium,
+    F2Hard,
+    F2Wet,
+}
+
+impl VisualTyreCompound {
+    /// `None` if the id is unknown for the formula
+    pub fn try_from_id(formula: Formula, id: u8) -> Option<Self> {
+        let compound = match (CompoundSet::from(formula), id) {
+            (CompoundSet::Modern, 16) => Self::Soft,
+            (CompoundSet::Modern, 17) => Self::Medium,
+            (CompoundSet::Modern, 18) => Self::Hard,
+            (CompoundSet::Modern, 7) => Self::Intermediate,
+            (CompoundSet::Modern, 8) => Self::Wet,
+            (CompoundSet::Classic, 9) => Self::ClassicDry,
+            (CompoundSet::Classic, 10) => Self::ClassicWet,
+            (CompoundSet::F2, 19) => Self::F2SuperSoft,
+            (CompoundSet::F2, 20) => Self::F2Soft,
+            (CompoundSet::F2, 21) => Self::F2Medium,
+            (CompoundSet::F2, 22) => Self::F2Hard,
+            (CompoundSet::F2, 15) => Self::F2Wet,
+            _ => return None,
+        };
+        Some(compound)
+    }
+
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::Soft => 16,
+            Self::Medium => 17,
+            Self::Hard => 18,
+            Self::Intermediate => 7,
+            Self::Wet => 8,
+            Self::ClassicDry => 9,
+            Self::ClassicWet => 10,
+            Self::F2SuperSoft => 19,
+            Self::F2Soft => 20,
+            Self::F2Medium => 21,
+            Self::F2Hard => 22,
+            Self::F2Wet => 15,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Soft | Self::F2Soft => "Soft",
+            Self::Medium | Self::F2Medium => "Medium",
+            Self::Hard | Self::F2Hard => "Hard",
+            Self::Intermediate => "Intermediate",
+            Self::Wet | Self::ClassicWet | Self::F2Wet => "Wet",
+            Self::ClassicDry => "Dry",
+            Self::F2SuperSoft => "Super Soft",
+        }
+    }
+
+    pub fn letter(&self) -> &'static str {
+        match self {
+            Self::Soft | Self::F2Soft => "S",
+            Self::Medium | Self::F2Medium => "M",
+            Self::Hard | Self::F2Hard => "H",
+            Self::Intermediate => "I",
+            Self::Wet | Self::ClassicWet | Self::F2Wet => "W",
+            Self::ClassicDry => "D",
+            Self::F2SuperSoft => "SS",
+        }
+    }
+
+    pub fn colour(&self) -> Colour {
+        match self {
+            Self::Soft | Self::F2Soft => SOFT,
+            Self::Medium | Self::F2Medium => MEDIUM,
+            Self::Hard | Self::F2Hard => HARD,
+            Self::Intermediate => INTERMEDIATE,
+            Self::Wet | Self::ClassicWet | Self::F2Wet => WET,
+            Self::ClassicDry => CLASSIC_DRY,
+            Self::F2SuperSoft => SUPER_SOFT,
+        }
+    }
+
+    pub fn is_wet(&self) -> bool {
+        matches!(
+            self,
+            Self::Intermediate | Self::Wet | Self::ClassicWet | Self::F2Wet
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_depend_on_formula() {
+        assert_eq!(
+            ActualTyreCompound::try_from_id(Formula::F1Modern, 16),
+            Some(ActualTyreCompound::C5)
+        );
+        assert_eq!(ActualTyreCompound::try_from_id(Formula::F2, 16), None);
+        assert_eq!(
+            ActualTyreCompound::try_from_id(Formula::F2, 15),
+            Some(ActualTyreCompound::F2Wet)
+        );
+        assert_eq!(
+            VisualTyreCompound::try_from_id(Formula::F2, 19),
+            Some(VisualTyreCompound::F2SuperSoft)
+        );
+        assert_eq!(VisualTyreCompound::try_from_id(Formula::F1Modern, 19), None);
+        assert_eq!(
+            VisualTyreCompound::try_from_id(Formula::F1Classic, 9),
+            Some(VisualTyreCompound::ClassicDry)
+        );
+        assert_eq!(
+            VisualTyreCompound::try_from_id(Formula::Esports, 17),
+            Some(VisualTyreCompound::Medium)
+        );
+    }
+
+    #[test]
+    fn ids_round_trip() {
+        for formula in [Formula::F1Modern, Formula::F1Classic, Formula::F2] {
+            for id in 0..=u8::MAX {
+                if let Some(compound) = ActualTyreCompound::try_from_id(formula, id) {
+                    assert_eq!(compound.id(), id);
+                }
+                if let Some(compound) = VisualTyreCompound::try_from_id(formula, id) {
+                    assert_eq!(compound.id(), id);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn display_helpers() {
+        assert_eq!(ActualTyreCompound::C3.letter(), "C3");
+        assert_eq!(ActualTyreCompound::F2SuperSoft.letter(), "SS");
+        assert_eq!(VisualTyreCompound::F2Soft.name(), "Soft");
+        assert_eq!(VisualTyreCompound::Soft.colour().hex(), "#da291c");
+        assert!(ActualTyreCompound::Intermediate.is_wet());
+        assert!(!VisualTyreCompound::Hard.is_wet());
+    }
+}
@@ -0,0 +1,21 @@
+use crate::utils::define_appendix;
+
+define_appendix!(Weather {
+    0 => Clear,
+    1 => LightCloud => "Light Cloud",
+    2 => Overcast,
+    3 => LightRain => "Light Rain",
+    4 => HeavyRain => "Heavy Rain",
+    5 => Storm
+});
+
+define_appendix!(TemperatureTrend {
+    0 => Up,
+    1 => Down,
+    2 => NoChange => "No Change"
+});
+
+define_appendix!(ForecastAccuracy {
+    0 => Perfect,
+    1 => Approximate
+});
@@ -0,0 +1,323 @@
+use core::fmt;
+
+use crate::{
+    constants::{NationalityId, Platform, ReadyStatus, TeamId},
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{LobbyInfoData, PacketLobbyInfoData},
+};
+
+/// `team_id` of a player who hasn't picked a team yet
+const NO_TEAM: u8 = 255;
+
+/// A player in the multiplayer lobby
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LobbyPlayer {
+    pub name: String,
+    /// `None` until a team is selected
+    pub team: Option<TeamId>,
+    pub nationality: Option<NationalityId>,
+    pub platform: Option<Platform>,
+    pub car_number: u8,
+    pub ai_controlled: bool,
+    /// The player's UDP setting is public, otherwise their telemetry is restricted
+    pub public_telemetry: bool,
+    pub show_online_names: bool,
+    /// F1 World tech level
+    pub tech_level: u16,
+    pub ready_status: Option<ReadyStatus>,
+}
+
+impl LobbyPlayer {
+    pub fn is_ready(&self) -> bool {
+        self.ready_status == Some(ReadyStatus::Ready)
+    }
+
+    pub fn is_spectating(&self) -> bool {
+        self.ready_status == Some(ReadyStatus::Spectating)
+    }
+}
+
+impl From<&LobbyInfoData> for LobbyPlayer {
+    fn from(info: &LobbyInfoData) -> Self {
+        Self {
+            name: info.name_str().to_string(),
+            team: match info.team_id {
+                NO_TEAM => None,
+                id => TeamId::try_from_id(id),
+            },
+            nationality: NationalityId::try_from_id(info.nationality),
+            platform: info.platform(),
+            car_number: info.car_number,
+            ai_controlled: info.ai_controlled == 1,
+            public_telemetry: info.your_telemetry == 1,
+            show_online_names: info.show_online_names == 1,
+            tech_level: info.tech_level,
+            ready_status: info.ready_status(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LobbyEvent {
+    Joined(LobbyPlayer),
+    Left(LobbyPlayer),
+    TeamChanged {
+        player: LobbyPlayer,
+        from: Option<TeamId>,
+    },
+    /// Ready, not ready or spectating
+    ReadyStatusChanged {
+        player: LobbyPlayer,
+        from: Option<ReadyStatus>,
+    },
+}
+
+/// State of the lobby against the usual pre-race checks, human players only
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LobbyChecklist {
+    pub players: usize,
+    pub not_ready: Vec<String>,
+    pub spectating: Vec<String>,
+    pub without_team: Vec<String>,
+    pub restricted_telemetry: Vec<String>,
+}
+
+impl LobbyChecklist {
+    /// Every player who isn't spectating is ready
+    pub fn all_ready(&self) -> bool {
+        self.not_ready.is_empty()
+    }
+
+    pub fn all_public_telemetry(&self) -> bool {
+        self.restricted_telemetry.is_empty()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.all_ready() && self.without_team.is_empty() && self.all_public_telemetry()
+    }
+}
+
+impl fmt::Display for LobbyChecklist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} players", self.players)?;
+        for (label, names) in [
+            ("Players ready", &self.not_ready),
+            ("Teams selected", &self.without_team),
+            ("Telemetry public", &self.restricted_telemetry),
+        ] {
+            match names.is_empty() {
+                true => writeln!(f, "[x] {}", label)?,
+                false => writeln!(f, "[ ] {}: {}", label, names.join(", "))?,
+            }
+        }
+        if !self.spectating.is_empty() {
+            writeln!(f, "Spectating: {}", self.spectating.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+fn names<'a>(players: impl Iterator<Item = &'a LobbyPlayer>) -> Vec<String> {
+    players.map(|p| p.name.clone()).collect()
+}
+
+/// Players in the multiplayer lobby, from the lobby info packets.
+///
+/// The lobby has no car indices to go by, so players are followed by name and
+/// platform, in slot order when several share both (e.g. with online names hidden).
+#[derive(Debug, Clone, Default)]
+pub struct LobbyTracker {
+    players: Vec<LobbyPlayer>,
+}
+
+impl LobbyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_lobby_info(&mut self, packet: &PacketLobbyInfoData) -> Vec<LobbyEvent> {
+        let lobby_players = packet.lobby_players;
+        let count = (packet.num_players as usize).min(lobby_players.len());
+        let mut previous: Vec<Option<LobbyPlayer>> = self.players.drain(..).map(Some).collect();
+        let mut events = Vec::new();
+
+        for info in &lobby_players[..count] {
+            let player = LobbyPlayer::from(info);
+            let before = previous
+                .iter_mut()
+                .find(|p| {
+                    p.as_ref()
+                        .is_some_and(|p| p.name == player.name && p.platform == player.platform)
+                })
+                .and_then(Option::take);
+
+            match before {
+                None => events.push(LobbyEvent::Joined(player.clone())),
+                Some(before) => {
+                    if before.team != player.team {
+                        events.push(LobbyEvent::TeamChanged {
+                            player: player.clone(),
+                            from: before.team,
+                        });
+                    }
+                    if before.ready_status != player.ready_status {
+                        events.push(LobbyEvent::ReadyStatusChanged {
+                            player: player.clone(),
+                            from: before.ready_status,
+                        });
+                    }
+                }
+            }
+            self.players.push(player);
+        }
+
+        events.extend(previous.into_iter().flatten().map(LobbyEvent::Left));
+        events
+    }
+
+    /// Players in slot order
+    pub fn players(&self) -> &[LobbyPlayer] {
+        &self.players
+    }
+
+    pub fn player(&self, name: &str) -> Option<&LobbyPlayer> {
+        self.players.iter().find(|p| p.name == name)
+    }
+
+    fn humans(&self) -> impl Iterator<Item = &LobbyPlayer> {
+        self.players.iter().filter(|p| !p.ai_controlled)
+    }
+
+    /// Every human player who isn't spectating is ready
+    pub fn all_ready(&self) -> bool {
+        self.humans().all(|p| p.is_ready() || p.is_spectating())
+    }
+
+    /// Human players with their UDP telemetry setting on restricted
+    pub fn restricted_telemetry(&self) -> Vec<&LobbyPlayer> {
+        self.humans().filter(|p| !p.public_telemetry).collect()
+    }
+
+    /// Lowest and highest F1 World tech level among the human players
+    pub fn tech_level_range(&self) -> Option<(u16, u16)> {
+        let levels = || self.humans().map(|p| p.tech_level);
+        Some((levels().min()?, levels().max()?))
+    }
+
+    /// Platforms the human players are on, e.g. to check cross-play is needed
+    pub fn platforms(&self) -> Vec<Platform> {
+        let mut platforms: Vec<Platform> = self.humans().filter_map(|p| p.platform).collect();
+        platforms.sort_by_key(|p| p.id());
+        platforms.dedup();
+        platforms
+    }
+
+    pub fn checklist(&self) -> LobbyChecklist {
+        let racing = || self.humans().filter(|p| !p.is_spectating());
+        LobbyChecklist {
+            players: self.humans().count(),
+            not_ready: names(racing().filter(|p| !p.is_ready())),
+            spectating: names(self.humans().filter(|p| p.is_spectating())),
+            without_team: names(racing().filter(|p| p.team.is_none())),
+            restricted_telemetry: names(self.restricted_telemetry().into_iter()),
+        }
+    }
+}
+
+impl PacketConsumer for LobbyTracker {
+    type Event = LobbyEvent;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<LobbyEvent> {
+        match packet {
+            AnyRawPacket::LobbyInfo(p) => self.push_lobby_info(p),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str, platform: u8, team_id: u8, ready_status: u8) -> LobbyInfoData {
+        let mut info: LobbyInfoData = bytemuck::Zeroable::zeroed();
+        info.name[..name.len()].copy_from_slice(name.as_bytes());
+        info.platform = platform;
+        info.team_id = team_id;
+        info.ready_status = ready_status;
+        info.your_telemetry = 1;
+        info
+    }
+
+    fn packet(players: &[LobbyInfoData]) -> PacketLobbyInfoData {
+        let mut packet: PacketLobbyInfoData = bytemuck::Zeroable::zeroed();
+        packet.num_players = players.len() as u8;
+        packet.lobby_players[..players.len()].copy_from_slice(players);
+        packet
+    }
+
+    #[test]
+    fn players_are_followed_by_name_and_platform() {
+        let mut lobby = LobbyTracker::new();
+        let events = lobby.push_lobby_info(&packet(&[
+            player("Ann", 1, NO_TEAM, 0),
+            player("Bo", 3, 0, 0),
+        ]));
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| matches!(e, LobbyEvent::Joined(_))));
+        assert_eq!(lobby.player("Ann").unwrap().team, None);
+
+        let events = lobby.push_lobby_info(&packet(&[player("Ann", 1, 0, 1)]));
+        assert!(matches!(
+            &events[..],
+            [
+                LobbyEvent::TeamChanged { from: None, .. },
+                LobbyEvent::ReadyStatusChanged {
+                    from: Some(ReadyStatus::NotReady),
+                    ..
+                },
+                LobbyEvent::Left(left),
+            ] if left.name == "Bo"
+        ));
+    }
+
+    #[test]
+    fn same_name_on_another_platform_is_another_player() {
+        let mut lobby = LobbyTracker::new();
+        lobby.push_lobby_info(&packet(&[player("Player", 1, 0, 0)]));
+        let events = lobby.push_lobby_info(&packet(&[player("Player", 4, 0, 0)]));
+
+        assert!(matches!(
+            &events[..],
+            [LobbyEvent::Joined(_), LobbyEvent::Left(_)]
+        ));
+        assert_eq!(lobby.platforms(), [Platform::Xbox]);
+    }
+
+    #[test]
+    fn checklist_ignores_ai_and_spectators() {
+        let mut lobby = LobbyTracker::new();
+        let mut ai = player("AI", 1, NO_TEAM, 0);
+        ai.ai_controlled = 1;
+        let mut restricted = player("Cy", 3, 0, 1);
+        restricted.your_telemetry = 0;
+        restricted.tech_level = 900;
+        lobby.push_lobby_info(&packet(&[
+            player("Ann", 1, 0, 1),
+            player("Bo", 1, NO_TEAM, 2),
+            restricted,
+            ai,
+        ]));
+
+        let checklist = lobby.checklist();
+        assert_eq!(checklist.players, 3);
+        assert!(checklist.all_ready());
+        assert!(checklist.without_team.is_empty());
+        assert_eq!(checklist.spectating, ["Bo"]);
+        assert_eq!(checklist.restricted_telemetry, ["Cy"]);
+        assert!(!checklist.is_complete());
+        assert!(lobby.all_ready());
+        assert_eq!(lobby.tech_level_range(), Some((0, 900)));
+        assert_eq!(lobby.platforms(), [Platform::Steam, Platform::PlayStation]);
+    }
+}
@@ -0,0 +1,320 @@
+use crate::{
+    constants::FiaFlag,
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{PacketLapData, PacketSessionData, constants::MAX_NUM_CARS},
+};
+
+/// Marshal zone as a lap distance range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zone {
+    pub index: usize,
+    /// Lap distance the zone starts at (m)
+    pub start: f32,
+    /// Lap distance the next zone starts at (m). The last zone wraps past the line and
+    /// ends where the first one starts, so its `end` is not after its `start`.
+    pub end: f32,
+    /// `None` when the game reports the flag as invalid or unknown
+    pub flag: Option<FiaFlag>,
+}
+
+impl Zone {
+    /// Whether the zone covers a lap distance. The wrap-around zone also covers
+    /// negative distances before the line and any past the track length.
+    pub fn contains(&self, lap_distance: f32) -> bool {
+        if self.wraps() {
+            lap_distance >= self.start || lap_distance < self.end
+        } else {
+            (self.start..self.end).contains(&lap_distance)
+        }
+    }
+
+    /// Whether this is the last zone, running across the line into the first
+    pub fn wraps(&self) -> bool {
+        self.end <= self.start
+    }
+}
+
+/// A zone's flag changing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlagChange {
+    pub session_time: f32,
+    pub zone: usize,
+    pub from: Option<FiaFlag>,
+    pub to: Option<FiaFlag>,
+}
+
+/// Flag state of the marshal zones and the cars inside them.
+///
+/// The session packet gives each zone's start as a fraction of the lap, which is mapped
+/// onto lap distance with `track_length`. Each zone runs until the start of the next,
+/// and the last one wraps across the line to the start of the first. A packet without a
+/// track length, or whose zone starts aren't increasing, leaves the map without zones.
+///
+/// A flashback drops the flag changes after the rewound time and the zones, so the next
+/// session packet sets the flags again without reporting changes.
+#[derive(Debug, Clone, Default)]
+pub struct MarshalZoneMap {
+    session_uid: Option<u64>,
+    session_time: f32,
+    track_length: f32,
+    zones: Vec<Zone>,
+    changes: Vec<FlagChange>,
+    lap_distances: [Option<f32>; MAX_NUM_CARS],
+}
+
+impl MarshalZoneMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::new();
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    fn rewind_on_flashback(&mut self, session_time: f32) {
+        if session_time < self.session_time {
+            self.changes
+                .retain(|change| change.session_time <= session_time);
+            self.zones.clear();
+            self.lap_distances = [None; MAX_NUM_CARS];
+        }
+        self.session_time = session_time;
+    }
+
+    /// Updates the zones, returning the flags that changed since the previous packet
+    pub fn push_session(&mut self, packet: &PacketSessionData) -> Vec<FlagChange> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+        self.track_length = packet.track_length as f32;
+
+        let marshal_zones = packet.marshal_zones;
+        let count = (packet.num_marshal_zones as usize).min(marshal_zones.len());
+        let starts: Vec<f32> = marshal_zones[..count]
+            .iter()
+            .map(|zone| zone.zone_start.clamp(0.0, 1.0) * self.track_length)
+            .collect();
+
+        // Otherwise zones would overlap, and any zone could wrap
+        let valid = self.track_length > 0.0
+            && starts.iter().all(|start| start.is_finite())
+            && starts.windows(2).all(|pair| pair[0] < pair[1]);
+        if !valid {
+            self.zones.clear();
+            return Vec::new();
+        }
+
+        let zones: Vec<Zone> = marshal_zones[..count]
+            .iter()
+            .enumerate()
+            .map(|(index, zone)| Zone {
+                index,
+                start: starts[index],
+                end: starts.get(index + 1).copied().unwrap_or(starts[0]),
+                flag: zone.zone_flag(),
+            })
+            .collect();
+
+        let mut changes = Vec::new();
+        if self.zones.len() == zones.len() {
+            for (before, after) in self.zones.iter().zip(&zones) {
+                if before.flag != after.flag {
+                    changes.push(FlagChange {
+                        session_time: header.session_time,
+                        zone: after.index,
+                        from: before.flag,
+                        to: after.flag,
+                    });
+                }
+            }
+        }
+
+        self.zones = zones;
+        self.changes.extend_from_slice(&changes);
+        changes
+    }
+
+    pub fn push_lap_data(&mut self, packet: &PacketLapData) {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let lap_data = packet.lap_data;
+        for (distance, lap) in self.lap_distances.iter_mut().zip(lap_data.iter()) {
+            *distance = lap
+                .result_status()
+                .is_some_and(|status| status.is_participating())
+                .then_some(lap.lap_distance);
+        }
+    }
+
+    pub fn zones(&self) -> &[Zone] {
+        &self.zones
+    }
+
+    /// Every flag change of the session, oldest first
+    pub fn changes(&self) -> &[FlagChange] {
+        &self.changes
+    }
+
+    /// Zone covering a lap distance
+    pub fn zone_at(&self, lap_distance: f32) -> Option<&Zone> {
+        self.zones.iter().find(|zone| zone.contains(lap_distance))
+    }
+
+    pub fn yellow_zones(&self) -> impl Iterator<Item = &Zone> {
+        self.zones
+            .iter()
+            .filter(|zone| zone.flag == Some(FiaFlag::Yellow))
+    }
+
+    /// Zone the car is in, `None` for inactive cars
+    pub fn car_zone(&self, car_idx: u8) -> Option<&Zone> {
+        let distance = self
+            .lap_distances
+            .get(car_idx as usize)
+            .copied()
+            .flatten()?;
+        self.zone_at(distance)
+    }
+
+    pub fn in_yellow(&self, car_idx: u8) -> bool {
+        self.car_zone(car_idx)
+            .is_some_and(|zone| zone.flag == Some(FiaFlag::Yellow))
+    }
+
+    pub fn cars_in_yellow(&self) -> Vec<u8> {
+        (0..MAX_NUM_CARS as u8)
+            .filter(|&car_idx| self.in_yellow(car_idx))
+            .collect()
+    }
+}
+
+impl PacketConsumer for MarshalZoneMap {
+    type Event = FlagChange;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<FlagChange> {
+        match packet {
+            AnyRawPacket::Session(p) => self.push_session(p),
+            AnyRawPacket::Lap(p) => {
+                self.push_lap_data(p);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Track of 1000 m with zones starting at 100, 400 and 700 m
+    fn session(session_time: f32, flags: [i8; 3]) -> PacketSessionData {
+        let mut packet: PacketSessionData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.header.session_time = session_time;
+        packet.track_length = 1000;
+        packet.num_marshal_zones = 3;
+        for (zone, (start, flag)) in packet
+            .marshal_zones
+            .iter_mut()
+            .zip([0.1, 0.4, 0.7].into_iter().zip(flags))
+        {
+            zone.zone_start = start;
+            zone.zone_flag = flag;
+        }
+        packet
+    }
+
+    fn lap_data(session_time: f32, distances: &[f32]) -> PacketLapData {
+        let mut packet: PacketLapData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.header.session_time = session_time;
+        for (lap, &distance) in packet.lap_data.iter_mut().zip(distances) {
+            lap.result_status = 2;
+            lap.lap_distance = distance;
+        }
+        packet
+    }
+
+    #[test]
+    fn last_zone_wraps_across_the_line() {
+        let mut map = MarshalZoneMap::new();
+        map.push_session(&session(1.0, [1, 1, 1]));
+
+        let last = map.zones()[2];
+        assert!(last.wraps());
+        assert!(last.contains(900.0));
+        assert!(last.contains(50.0));
+        assert!(last.contains(-5.0));
+        assert!(!last.contains(100.0));
+        assert!(!map.zones()[0].wraps());
+
+        assert_eq!(map.zone_at(50.0).map(|z| z.index), Some(2));
+        assert_eq!(map.zone_at(100.0).map(|z| z.index), Some(0));
+        assert_eq!(map.zone_at(650.0).map(|z| z.index), Some(1));
+        assert_eq!(map.zone_at(1200.0).map(|z| z.index), Some(2));
+    }
+
+    #[test]
+    fn skips_zones_without_a_track_length_or_increasing_starts() {
+        let mut map = MarshalZoneMap::new();
+        let mut packet = session(1.0, [1, 1, 1]);
+        packet.track_length = 0;
+        map.push_session(&packet);
+        assert!(map.zones().is_empty());
+
+        let mut packet = session(2.0, [1, 1, 1]);
+        packet.marshal_zones[1].zone_start = 0.8;
+        map.push_session(&packet);
+        assert!(map.zones().is_empty());
+        assert!(map.zone_at(500.0).is_none());
+
+        map.push_session(&session(3.0, [1, 1, 1]));
+        assert_eq!(map.zones().iter().filter(|zone| zone.wraps()).count(), 1);
+    }
+
+    #[test]
+    fn flag_changes_and_cars_in_yellow() {
+        let mut map = MarshalZoneMap::new();
+        assert!(map.push_session(&session(1.0, [1, 1, 1])).is_empty());
+
+        let changes = map.push_session(&session(2.0, [1, 3, 1]));
+        assert_eq!(
+            changes,
+            [FlagChange {
+                session_time: 2.0,
+                zone: 1,
+                from: Some(FiaFlag::Green),
+                to: Some(FiaFlag::Yellow),
+            }]
+        );
+
+        map.push_lap_data(&lap_data(2.1, &[500.0, 50.0, 450.0]));
+        assert_eq!(map.cars_in_yellow(), [0, 2]);
+        assert!(!map.in_yellow(1));
+        assert!(!map.in_yellow(3));
+        assert_eq!(map.yellow_zones().count(), 1);
+    }
+
+    #[test]
+    fn flashback_drops_later_changes_without_reporting_new_ones() {
+        let mut map = MarshalZoneMap::new();
+        map.push_session(&session(1.0, [1, 1, 1]));
+        map.push_session(&session(5.0, [1, 3, 1]));
+        map.push_lap_data(&lap_data(5.1, &[500.0]));
+        assert!(map.in_yellow(0));
+
+        assert!(map.push_session(&session(3.0, [1, 1, 1])).is_empty());
+        assert!(map.changes().is_empty());
+        assert!(!map.in_yellow(0));
+
+        let changes = map.push_session(&session(4.0, [3, 1, 1]));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].zone, 0);
+    }
+}
@@ -1,2 +1,12 @@
+pub mod championship;
 pub mod constants;
+pub mod lobby;
+pub mod marshal_zones;
+pub mod results;
+pub mod roster;
+pub mod setup;
+pub mod shift_lights;
+pub mod timing;
 pub mod utils;
+pub mod weather;
+pub mod weekend;
@@ -0,0 +1,541 @@
+use core::fmt;
+
+use crate::{
+    constants::{
+        ActualTyreCompound, Formula, ResultReason, ResultStatus, TeamId, VisualTyreCompound,
+    },
+    raw::{FinalClassificationData, PacketFinalClassificationData, PacketParticipantsData},
+    timing::LapTime,
+};
+
+/// Points awarded by finishing position, with an optional fastest lap bonus
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointsSystem {
+    /// Points of P1, P2, ...; positions past the end score nothing
+    pub positions: Vec<u32>,
+    pub fastest_lap: Option<FastestLapBonus>,
+}
+
+/// Extra points for the fastest lap, if its driver is classified within `max_position`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FastestLapBonus {
+    pub points: u32,
+    pub max_position: u8,
+}
+
+impl PointsSystem {
+    pub fn new(positions: Vec<u32>) -> Self {
+        Self {
+            positions,
+            fastest_lap: None,
+        }
+    }
+
+    /// Grand prix points, 25 for the win down to 1 for P10
+    pub fn grand_prix() -> Self {
+        Self::new(vec![25, 18, 15, 12, 10, 8, 6, 4, 2, 1])
+    }
+
+    /// Sprint points, 8 for the win down to 1 for P8
+    pub fn sprint() -> Self {
+        Self::new(vec![8, 7, 6, 5, 4, 3, 2, 1])
+    }
+
+    pub fn with_fastest_lap(mut self, points: u32, max_position: u8) -> Self {
+        self.fastest_lap = Some(FastestLapBonus {
+            points,
+            max_position,
+        });
+        self
+    }
+
+    /// Every position scaled, e.g. by 0.5 for half points, rounded down
+    pub fn scaled(mut self, factor: f32) -> Self {
+        for points in &mut self.positions {
+            *points = (*points as f32 * factor) as u32;
+        }
+        self
+    }
+
+    fn points(&self, position: usize, fastest_lap: bool) -> u32 {
+        let points = position
+            .checked_sub(1)
+            .and_then(|i| self.positions.get(i))
+            .copied()
+            .unwrap_or(0);
+        let bonus = self
+            .fastest_lap
+            .filter(|bonus| fastest_lap && position <= bonus.max_position as usize)
+            .map_or(0, |bonus| bonus.points);
+        points + bonus
+    }
+}
+
+impl Default for PointsSystem {
+    fn default() -> Self {
+        Self::grand_prix()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Stint {
+    pub actual: Option<ActualTyreCompound>,
+    pub visual: Option<VisualTyreCompound>,
+    pub end_lap: u8,
+}
+
+/// Gap of a classified driver to the winner
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Gap {
+    Winner,
+    /// Seconds, penalties included
+    Time(f64),
+    Laps(u8),
+}
+
+impl fmt::Display for Gap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Gap::Winner => write!(f, "Winner"),
+            Gap::Time(seconds) => write!(f, "+{:.3}", seconds),
+            Gap::Laps(1) => write!(f, "+1 lap"),
+            Gap::Laps(laps) => write!(f, "+{} laps", laps),
+        }
+    }
+}
+
+/// One line of the results table
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ResultRow {
+    /// Position in the table, which differs from the game's for unclassified drivers
+    pub position: u8,
+    pub car_idx: u8,
+    pub name: String,
+    pub team: Option<TeamId>,
+    pub race_number: u8,
+    pub grid_position: u8,
+    pub num_laps: u8,
+    pub status: Option<ResultStatus>,
+    pub reason: Option<ResultReason>,
+    /// Seconds without penalties
+    pub race_time: f64,
+    /// Seconds of time penalties
+    pub penalties_time: u8,
+    pub num_penalties: u8,
+    pub num_pit_stops: u8,
+    pub best_lap_time: LapTime,
+    pub fastest_lap: bool,
+    pub stints: Vec<Stint>,
+    /// `None` for drivers who weren't classified
+    pub gap: Option<Gap>,
+    /// Points of the points system used
+    pub points: u32,
+    /// Points given by the game
+    pub game_points: u8,
+}
+
+impl ResultRow {
+    /// Seconds including time penalties
+    pub fn total_time(&self) -> f64 {
+        self.race_time + self.penalties_time as f64
+    }
+
+    /// Short compound letters of each stint, e.g. `M-H`
+    pub fn strategy(&self) -> String {
+        self.stints
+            .iter()
+            .map(|stint| {
+                stint
+                    .visual
+                    .map(|c| c.letter())
+                    .or(stint.actual.map(|c| c.letter()))
+                    .unwrap_or("?")
+            })
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// The game's status or reason for drivers who weren't classified
+    fn outcome(&self) -> String {
+        match (self.gap, self.status) {
+            (Some(gap), _) => gap.to_string(),
+            (None, Some(ResultStatus::Disqualified)) => "DSQ".to_string(),
+            (None, _) => self
+                .reason
+                .map_or("DNF", |reason| reason.name())
+                .to_string(),
+        }
+    }
+}
+
+/// Group a driver is ordered in, classified drivers first and disqualified drivers last
+fn group(status: Option<ResultStatus>) -> u8 {
+    match status {
+        Some(ResultStatus::Finished | ResultStatus::Active) => 0,
+        Some(ResultStatus::Disqualified) => 2,
+        _ => 1,
+    }
+}
+
+/// Results table of a session from the final classification.
+///
+/// Classified drivers are ordered by the game's position. Drivers who did not finish,
+/// retired or weren't classified follow by laps completed, then disqualified drivers.
+/// Cars the game reports as invalid or inactive are left out.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RaceResult {
+    pub session_uid: u64,
+    pub rows: Vec<ResultRow>,
+}
+
+impl RaceResult {
+    /// Drivers are named from the participants packet, and the stints decoded with the
+    /// session's `formula`
+    pub fn new(
+        classification: &PacketFinalClassificationData,
+        participants: &PacketParticipantsData,
+        formula: Formula,
+        points_system: &PointsSystem,
+    ) -> Self {
+        let classification_data = classification.classification_data;
+        let participants_data = participants.participants;
+        let count = (classification.num_cars as usize).min(classification_data.len());
+
+        let mut cars: Vec<(u8, &FinalClassificationData)> = classification_data[..count]
+            .iter()
+            .enumerate()
+            .filter(|(_, data)| data.result_status().is_some_and(|s| s.is_participating()))
+            .map(|(idx, data)| (idx as u8, data))
+            .collect();
+        cars.sort_by_key(|(_, data)| {
+            let status = data.result_status();
+            let laps = match group(status) {
+                1 => u8::MAX - data.num_laps,
+                _ => 0,
+            };
+            (group(status), laps, data.position)
+        });
+
+        let fastest = cars
+            .iter()
+            .filter(|(_, data)| data.best_lap_time_in_ms > 0 && group(data.result_status()) < 2)
+            .min_by_key(|(_, data)| data.best_lap_time_in_ms)
+            .map(|&(idx, _)| idx);
+
+        let winner = cars.first().map(|&(_, data)| data);
+        let rows = cars
+            .iter()
+            .enumerate()
+            .map(|(i, &(car_idx, data))| {
+                let participant = &participants_data[car_idx as usize];
+                let status = data.result_status();
+                let classified = group(status) == 0;
+                let fastest_lap = fastest == Some(car_idx);
+
+                let gap = winner.filter(|_| classified).map(|winner| {
+                    let total =
+                        |d: &FinalClassificationData| d.total_race_time + d.penalties_time as f64;
+                    match (i, winner.num_laps - data.num_laps.min(winner.num_laps)) {
+                        (0, _) => Gap::Winner,
+                        (_, 0) => Gap::Time(total(data) - total(winner)),
+                        (_, laps) => Gap::Laps(laps),
+                    }
+                });
+
+                let stints = data
+                    .tyre_stints_actual(formula)
+                    .into_iter()
+                    .zip(data.tyre_stints_visual(formula))
+                    .zip(data.tyre_stints_end_laps)
+                    .map(|((actual, visual), end_lap)| Stint {
+                        actual,
+                        visual,
+                        end_lap,
+                    })
+                    .collect();
+
+                ResultRow {
+                    position: i as u8 + 1,
+                    car_idx,
+                    name: participant.name_str().to_string(),
+                    team: TeamId::try_from_id(participant.team_id),
+                    race_number: participant.race_number,
+                    grid_position: data.grid_position,
+                    num_laps: data.num_laps,
+                    status,
+                    reason: data.result_reason(),
+                    race_time: data.total_race_time,
+                    penalties_time: data.penalties_time,
+                    num_penalties: data.num_penalties,
+                    num_pit_stops: data.num_pit_stops,
+                    best_lap_time: LapTime::from_millis(data.best_lap_time_in_ms),
+                    fastest_lap,
+                    stints,
+                    gap,
+                    points: match classified {
+                        true => points_system.points(i + 1, fastest_lap),
+                        false => 0,
+                    },
+                    game_points: data.points,
+                }
+            })
+            .collect();
+
+        Self {
+            session_uid: classification.header.session_uid,
+            rows,
+        }
+    }
+
+    pub fn winner(&self) -> Option<&ResultRow> {
+        self.rows.first().filter(|row| row.gap == Some(Gap::Winner))
+    }
+
+    pub fn row(&self, car_idx: u8) -> Option<&ResultRow> {
+        self.rows.iter().find(|row| row.car_idx == car_idx)
+    }
+
+    pub fn fastest_lap(&self) -> Option<&ResultRow> {
+        self.rows.iter().find(|row| row.fastest_lap)
+    }
+
+    /// One line per driver, with a header line
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "position,car_idx,name,team,race_number,grid_position,laps,status,reason,\
+             race_time,penalties_time,num_penalties,num_pit_stops,best_lap_time,fastest_lap,\
+             strategy,gap,points\n",
+        );
+        for row in &self.rows {
+            let fields = [
+                row.position.to_string(),
+                row.car_idx.to_string(),
+                csv_field(&row.name),
+                csv_field(row.team.map_or("", |team| team.name())),
+                row.race_number.to_string(),
+                row.grid_position.to_string(),
+                row.num_laps.to_string(),
+                row.status.map_or("", |status| status.name()).to_string(),
+                row.reason.map_or("", |reason| reason.name()).to_string(),
+                format!("{:.3}", row.race_time),
+                row.penalties_time.to_string(),
+                row.num_penalties.to_string(),
+                row.num_pit_stops.to_string(),
+                row.best_lap_time.to_string(),
+                row.fastest_lap.to_string(),
+                row.strategy(),
+                csv_field(&row.outcome()),
+                row.points.to_string(),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// `h:mm:ss.mmm`
+fn race_time(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// Quotes a field containing a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    match value.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+impl fmt::Display for RaceResult {
+    /// Aligned table, the fastest lap marked with `*`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>3}  {:>3}  {:<20} {:<16} {:>4}  {:<24} {:>9}  {:<12} {:>4}",
+            "Pos", "No", "Driver", "Team", "Laps", "Time / Gap", "Best", "Tyres", "Pts"
+        )?;
+        for row in &self.rows {
+            let time = match row.gap {
+                Some(Gap::Winner) => race_time(row.total_time()),
+                _ => row.outcome(),
+            };
+            let penalties = match row.penalties_time {
+                0 => String::new(),
+                seconds => format!(" ({}s pen)", seconds),
+            };
+            writeln!(
+                f,
+                "{:>3}  {:>3}  {:<20} {:<16} {:>4}  {:<24} {:>9}{} {:<12} {:>4}",
+                row.position,
+                row.race_number,
+                row.name,
+                row.team.map_or("", |team| team.name()),
+                row.num_laps,
+                time + &penalties,
+                row.best_lap_time,
+                if row.fastest_lap { "*" } else { " " },
+                row.strategy(),
+                row.points
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Car {
+        position: u8,
+        laps: u8,
+        status: u8,
+        time: f64,
+        penalties: u8,
+        best: u32,
+    }
+
+    fn classify(cars: &[Car], points_system: &PointsSystem) -> RaceResult {
+        let mut classification: PacketFinalClassificationData = bytemuck::Zeroable::zeroed();
+        let mut participants: PacketParticipantsData = bytemuck::Zeroable::zeroed();
+        classification.num_cars = cars.len() as u8;
+        for (idx, car) in cars.iter().enumerate() {
+            let data = &mut classification.classification_data[idx];
+            data.position = car.position;
+            data.num_laps = car.laps;
+            data.result_status = car.status;
+            data.total_race_time = car.time;
+            data.penalties_time = car.penalties;
+            data.best_lap_time_in_ms = car.best;
+
+            let name = format!("Driver {}", idx);
+            participants.participants[idx].name[..name.len()].copy_from_slice(name.as_bytes());
+            participants.participants[idx].race_number = idx as u8 + 1;
+        }
+        classification.classification_data[0].num_tyre_stints = 2;
+        classification.classification_data[0].tyre_stints_visual[..2].copy_from_slice(&[17, 18]);
+        classification.classification_data[3].result_reason = 8;
+
+        RaceResult::new(
+            &classification,
+            &participants,
+            Formula::F1Modern,
+            points_system,
+        )
+    }
+
+    fn race(points_system: &PointsSystem) -> RaceResult {
+        let car = |position, laps, status, time, penalties, best| Car {
+            position,
+            laps,
+            status,
+            time,
+            penalties,
+            best,
+        };
+        classify(
+            &[
+                car(1, 50, 3, 3600.0, 0, 90_000),
+                car(2, 50, 3, 3605.5, 5, 89_500),
+                car(3, 49, 3, 3610.0, 0, 91_000),
+                car(4, 30, 7, 2200.0, 0, 88_000),
+                car(5, 50, 5, 3590.0, 0, 85_000),
+                car(0, 0, 1, 0.0, 0, 0),
+            ],
+            points_system,
+        )
+    }
+
+    #[test]
+    fn rows_are_ordered_with_gaps_and_points() {
+        let result = race(&PointsSystem::grand_prix().with_fastest_lap(1, 10));
+
+        let cars: Vec<u8> = result.rows.iter().map(|row| row.car_idx).collect();
+        assert_eq!(cars, [0, 1, 2, 3, 4]);
+        assert_eq!(result.winner().map(|row| row.car_idx), Some(0));
+
+        let gaps: Vec<Option<Gap>> = result.rows.iter().map(|row| row.gap).collect();
+        assert_eq!(
+            gaps,
+            [
+                Some(Gap::Winner),
+                Some(Gap::Time(10.5)),
+                Some(Gap::Laps(1)),
+                None,
+                None
+            ]
+        );
+
+        // Retired drivers can take the fastest lap, disqualified ones can't
+        assert_eq!(result.fastest_lap().map(|row| row.car_idx), Some(3));
+        let points: Vec<u32> = result.rows.iter().map(|row| row.points).collect();
+        assert_eq!(points, [25, 18, 15, 0, 0]);
+        assert_eq!(result.row(0).unwrap().strategy(), "M-H");
+    }
+
+    #[test]
+    fn fastest_lap_bonus_needs_a_classified_position() {
+        let car = |position, best| Car {
+            position,
+            laps: 10,
+            status: 3,
+            time: position as f64,
+            penalties: 0,
+            best,
+        };
+        let system = PointsSystem::sprint().with_fastest_lap(1, 1);
+        let result = classify(&[car(1, 90_000), car(2, 89_000)], &system);
+        assert_eq!(result.rows[1].points, 7);
+
+        let system = PointsSystem::grand_prix()
+            .scaled(0.5)
+            .with_fastest_lap(1, 2);
+        let result = classify(&[car(1, 90_000), car(2, 89_000)], &system);
+        assert_eq!(result.rows[0].points, 12);
+        assert_eq!(result.rows[1].points, 10);
+    }
+
+    #[test]
+    fn outcomes_in_csv_and_table() {
+        let result = race(&PointsSystem::default());
+
+        let csv = result.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with("position,car_idx,name,"));
+        assert!(lines[2].ends_with(",+10.500,18"));
+        assert!(lines[3].ends_with(",+1 lap,15"));
+        assert!(lines[4].ends_with(",Mechanical Failure,0"));
+        assert!(lines[5].ends_with(",DSQ,0"));
+
+        let table = result.to_string();
+        assert!(table.contains("1:00:00.000"));
+        assert!(table.contains("+10.500 (5s pen)"));
+    }
+
+    #[test]
+    fn csv_fields_are_quoted() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,337 @@
+use crate::{
+    constants::{DriverId, NationalityId, Platform, TeamId},
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{
+        PacketParticipantsData, ParticipantData,
+        constants::{MAX_NUM_CARS, NETWORK_HUMAN_DRIVER_ID},
+    },
+};
+
+/// A participant, followed across car index changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriverIdentity {
+    /// Assigned in order of joining, never reused within a session
+    pub id: usize,
+    /// Car index used by the other packets
+    pub car_idx: u8,
+    /// Raw game driver id, 255 for network humans
+    pub driver_id: u8,
+    pub network_id: u8,
+    pub race_number: u8,
+    pub name: String,
+    pub team: Option<TeamId>,
+    pub nationality: Option<NationalityId>,
+    pub platform: Option<Platform>,
+    pub ai_controlled: bool,
+}
+
+impl DriverIdentity {
+    fn new(id: usize, car_idx: u8, participant: &ParticipantData) -> Self {
+        Self {
+            id,
+            car_idx,
+            driver_id: participant.driver_id,
+            network_id: participant.network_id,
+            race_number: participant.race_number,
+            name: participant.name_str().to_string(),
+            team: TeamId::try_from_id(participant.team_id),
+            nationality: NationalityId::try_from_id(participant.nationality),
+            platform: participant.platform(),
+            ai_controlled: participant.ai_controlled == 1,
+        }
+    }
+
+    pub fn is_network_human(&self) -> bool {
+        self.driver_id == NETWORK_HUMAN_DRIVER_ID
+    }
+
+    /// Game driver, `None` for network humans and ids missing from the appendix
+    pub fn driver(&self) -> Option<DriverId> {
+        if self.is_network_human() {
+            return None;
+        }
+        DriverId::try_from_id(self.driver_id)
+    }
+
+    /// Whether a participant is the same driver, ignoring the name, which can change
+    fn matches(&self, participant: &ParticipantData) -> bool {
+        match participant.driver_id {
+            NETWORK_HUMAN_DRIVER_ID => {
+                self.is_network_human()
+                    && self.network_id == participant.network_id
+                    && self.race_number == participant.race_number
+            }
+            id => self.driver_id == id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RosterEventKind {
+    /// New to the session, or back after leaving
+    Joined,
+    Left,
+    Renamed {
+        from: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RosterEvent {
+    pub session_time: f32,
+    pub kind: RosterEventKind,
+    /// Identity after the change, or the last one known for `Left`
+    pub identity: DriverIdentity,
+}
+
+/// Participants of a session with identities that stay stable when car indices change.
+///
+/// Each participants packet is matched against the known drivers: AI drivers by
+/// `driver_id`, network humans by `network_id` and `race_number`, and failing that by
+/// name. Drivers who left are matched too, so a rejoining driver gets their identity
+/// back. Only the first `num_active_cars` entries are considered occupied.
+#[derive(Debug, Clone, Default)]
+pub struct Roster {
+    session_uid: Option<u64>,
+    /// Everyone seen this session, by identity id
+    identities: Vec<DriverIdentity>,
+    /// Identity id of each car index
+    cars: [Option<usize>; MAX_NUM_CARS],
+}
+
+impl Roster {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::new();
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    pub fn push_participants(&mut self, packet: &PacketParticipantsData) -> Vec<RosterEvent> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+
+        let participants = packet.participants;
+        let count = (packet.num_active_cars as usize).min(participants.len());
+        let previous: Vec<usize> = self.cars.iter().flatten().copied().collect();
+        let mut cars = [None; MAX_NUM_CARS];
+        let mut events = Vec::new();
+        let mut event = |kind, identity: &DriverIdentity| {
+            events.push(RosterEvent {
+                session_time: header.session_time,
+                kind,
+                identity: identity.clone(),
+            })
+        };
+
+        let occupied = &participants[..count];
+        // Matching by id first, so a rename can't take over another driver's identity
+        for (car_idx, participant) in occupied.iter().enumerate() {
+            cars[car_idx] = (0..self.identities.len())
+                .filter(|id| !cars.contains(&Some(*id)))
+                .find(|&id| self.identities[id].matches(participant));
+        }
+        for (car_idx, participant) in occupied.iter().enumerate() {
+            let name = participant.name_str();
+            if cars[car_idx].is_none() && !name.is_empty() {
+                cars[car_idx] = (0..self.identities.len())
+                    .filter(|id| !cars.contains(&Some(*id)))
+                    .find(|&id| self.identities[id].name == name);
+            }
+        }
+
+        for (car_idx, participant) in occupied.iter().enumerate() {
+            let Some(id) = cars[car_idx] else {
+                let id = self.identities.len();
+                let identity = DriverIdentity::new(id, car_idx as u8, participant);
+                event(RosterEventKind::Joined, &identity);
+                self.identities.push(identity);
+                cars[car_idx] = Some(id);
+                continue;
+            };
+
+            let updated = DriverIdentity::new(id, car_idx as u8, participant);
+            let identity = &mut self.identities[id];
+            let from = std::mem::replace(identity, updated).name;
+            if !previous.contains(&id) {
+                event(RosterEventKind::Joined, identity);
+            } else if identity.name != from {
+                event(RosterEventKind::Renamed { from }, identity);
+            }
+        }
+
+        for id in previous {
+            if !cars.contains(&Some(id)) {
+                event(RosterEventKind::Left, &self.identities[id]);
+            }
+        }
+
+        self.cars = cars;
+        events
+    }
+
+    /// Identity of the driver in a car
+    pub fn identity(&self, car_idx: u8) -> Option<&DriverIdentity> {
+        let id = self.cars.get(car_idx as usize).copied().flatten()?;
+        self.identities.get(id)
+    }
+
+    /// Drivers currently in the session, by car index
+    pub fn current(&self) -> impl Iterator<Item = &DriverIdentity> {
+        self.cars.iter().flatten().map(|&id| &self.identities[id])
+    }
+
+    /// Everyone seen this session including those who left, by identity id
+    pub fn identities(&self) -> &[DriverIdentity] {
+        &self.identities
+    }
+
+    /// Car index of an identity, `None` once it left
+    pub fn car_idx(&self, id: usize) -> Option<u8> {
+        self.cars
+            .iter()
+            .position(|&car| car == Some(id))
+            .map(|idx| idx as u8)
+    }
+}
+
+impl PacketConsumer for Roster {
+    type Event = RosterEvent;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<RosterEvent> {
+        match packet {
+            AnyRawPacket::Participants(p) => self.push_participants(p),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn participant(driver_id: u8, network_id: u8, name: &str) -> ParticipantData {
+        let mut participant: ParticipantData = bytemuck::Zeroable::zeroed();
+        participant.driver_id = driver_id;
+        participant.network_id = network_id;
+        participant.race_number = network_id + 1;
+        participant.ai_controlled = (driver_id != NETWORK_HUMAN_DRIVER_ID) as u8;
+        participant.name[..name.len()].copy_from_slice(name.as_bytes());
+        participant
+    }
+
+    fn packet(session_time: f32, participants: &[ParticipantData]) -> PacketParticipantsData {
+        let mut packet: PacketParticipantsData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.header.session_time = session_time;
+        packet.num_active_cars = participants.len() as u8;
+        packet.participants[..participants.len()].copy_from_slice(participants);
+        packet
+    }
+
+    fn kinds(events: &[RosterEvent]) -> Vec<(RosterEventKind, usize)> {
+        events
+            .iter()
+            .map(|e| (e.kind.clone(), e.identity.id))
+            .collect()
+    }
+
+    #[test]
+    fn identities_follow_car_index_changes() {
+        let mut roster = Roster::new();
+        let sainz = participant(0, 0, "SAINZ");
+        let human = participant(NETWORK_HUMAN_DRIVER_ID, 1, "Player");
+        let events = roster.push_participants(&packet(1.0, &[sainz, human]));
+        assert_eq!(
+            kinds(&events),
+            [(RosterEventKind::Joined, 0), (RosterEventKind::Joined, 1)]
+        );
+
+        assert!(
+            roster
+                .push_participants(&packet(2.0, &[human, sainz]))
+                .is_empty()
+        );
+        assert_eq!(roster.car_idx(0), Some(1));
+        assert_eq!(roster.car_idx(1), Some(0));
+        assert_eq!(
+            roster.identity(1).unwrap().driver(),
+            Some(DriverId::CarlosSainz)
+        );
+        assert!(roster.identity(0).unwrap().is_network_human());
+    }
+
+    #[test]
+    fn unknown_ai_driver_id_is_not_a_network_human() {
+        let mut roster = Roster::new();
+        let unknown = participant(1, 0, "");
+        let human = participant(NETWORK_HUMAN_DRIVER_ID, 0, "");
+        roster.push_participants(&packet(1.0, &[unknown, human]));
+
+        let identity = roster.identity(0).unwrap();
+        assert_eq!(identity.driver_id, 1);
+        assert_eq!(identity.driver(), None);
+        assert!(!identity.is_network_human());
+
+        // Same network id and race number, but the AI must keep its own identity
+        assert!(
+            roster
+                .push_participants(&packet(2.0, &[human, unknown]))
+                .is_empty()
+        );
+        assert_eq!(roster.car_idx(0), Some(1));
+        assert_eq!(roster.car_idx(1), Some(0));
+    }
+
+    #[test]
+    fn rejoining_driver_gets_their_identity_back() {
+        let mut roster = Roster::new();
+        let sainz = participant(0, 0, "SAINZ");
+        let alonso = participant(3, 1, "ALONSO");
+        let human = participant(NETWORK_HUMAN_DRIVER_ID, 2, "Player");
+        roster.push_participants(&packet(1.0, &[sainz, alonso, human]));
+
+        let events = roster.push_participants(&packet(2.0, &[sainz]));
+        assert_eq!(
+            kinds(&events),
+            [(RosterEventKind::Left, 1), (RosterEventKind::Left, 2)]
+        );
+
+        let events = roster.push_participants(&packet(3.0, &[sainz, human, alonso]));
+        assert_eq!(
+            kinds(&events),
+            [(RosterEventKind::Joined, 2), (RosterEventKind::Joined, 1)]
+        );
+        assert_eq!(roster.identities().len(), 3);
+        assert_eq!(roster.car_idx(1), Some(2));
+        assert_eq!(roster.car_idx(2), Some(1));
+    }
+
+    #[test]
+    fn renamed_human_keeps_identity() {
+        let mut roster = Roster::new();
+        roster.push_participants(&packet(
+            1.0,
+            &[participant(NETWORK_HUMAN_DRIVER_ID, 4, "Player")],
+        ));
+        let events = roster.push_participants(&packet(
+            2.0,
+            &[participant(NETWORK_HUMAN_DRIVER_ID, 4, "Racer")],
+        ));
+
+        assert_eq!(
+            kinds(&events),
+            [(
+                RosterEventKind::Renamed {
+                    from: "Player".to_string()
+                },
+                0
+            )]
+        );
+        assert_eq!(roster.current().count(), 1);
+    }
+}
@@ -0,0 +1,206 @@
+use core::fmt;
+
+use crate::raw::CarSetupData;
+
+/// Differences below this are float noise, not a setup change
+const EPSILON: f32 = 1e-4;
+
+/// One adjustable value of a car setup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SetupParameter {
+    FrontWing,
+    RearWing,
+    OnThrottle,
+    OffThrottle,
+    EngineBraking,
+    FrontCamber,
+    RearCamber,
+    FrontToe,
+    RearToe,
+    FrontSuspension,
+    RearSuspension,
+    FrontAntiRollBar,
+    RearAntiRollBar,
+    FrontRideHeight,
+    RearRideHeight,
+    BrakePressure,
+    BrakeBias,
+    FrontLeftTyrePressure,
+    FrontRightTyrePressure,
+    RearLeftTyrePressure,
+    RearRightTyrePressure,
+    Ballast,
+    FuelLoad,
+}
+
+impl SetupParameter {
+    /// In the order of the game's setup screen
+    pub const ALL: [SetupParameter; 23] = [
+        SetupParameter::FrontWing,
+        SetupParameter::RearWing,
+        SetupParameter::OnThrottle,
+        SetupParameter::OffThrottle,
+        SetupParameter::EngineBraking,
+        SetupParameter::FrontCamber,
+        SetupParameter::RearCamber,
+        SetupParameter::FrontToe,
+        SetupParameter::RearToe,
+        SetupParameter::FrontSuspension,
+        SetupParameter::RearSuspension,
+        SetupParameter::FrontAntiRollBar,
+        SetupParameter::RearAntiRollBar,
+        SetupParameter::FrontRideHeight,
+        SetupParameter::RearRideHeight,
+        SetupParameter::BrakePressure,
+        SetupParameter::BrakeBias,
+        SetupParameter::FrontLeftTyrePressure,
+        SetupParameter::FrontRightTyrePressure,
+        SetupParameter::RearLeftTyrePressure,
+        SetupParameter::RearRightTyrePressure,
+        SetupParameter::Ballast,
+        SetupParameter::FuelLoad,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SetupParameter::FrontWing => "Front Wing Aero",
+            SetupParameter::RearWing => "Rear Wing Aero",
+            SetupParameter::OnThrottle => "Differential On Throttle",
+            SetupParameter::OffThrottle => "Differential Off Throttle",
+            SetupParameter::EngineBraking => "Engine Braking",
+            SetupParameter::FrontCamber => "Front Camber",
+            SetupParameter::RearCamber => "Rear Camber",
+            SetupParameter::FrontToe => "Front Toe-Out",
+            SetupParameter::RearToe => "Rear Toe-In",
+            SetupParameter::FrontSuspension => "Front Suspension",
+            SetupParameter::RearSuspension => "Rear Suspension",
+            SetupParameter::FrontAntiRollBar => "Front Anti-Roll Bar",
+            SetupParameter::RearAntiRollBar => "Rear Anti-Roll Bar",
+            SetupParameter::FrontRideHeight => "Front Ride Height",
+            SetupParameter::RearRideHeight => "Rear Ride Height",
+            SetupParameter::BrakePressure => "Brake Pressure",
+            SetupParameter::BrakeBias => "Front Brake Bias",
+            SetupParameter::FrontLeftTyrePressure => "Front Left Tyre Pressure",
+            SetupParameter::FrontRightTyrePressure => "Front Right Tyre Pressure",
+            SetupParameter::RearLeftTyrePressure => "Rear Left Tyre Pressure",
+            SetupParameter::RearRightTyrePressure => "Rear Right Tyre Pressure",
+            SetupParameter::Ballast => "Ballast",
+            SetupParameter::FuelLoad => "Fuel Load",
+        }
+    }
+
+    /// Empty for values in the game's setup steps
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SetupParameter::OnThrottle
+            | SetupParameter::OffThrottle
+            | SetupParameter::EngineBraking
+            | SetupParameter::BrakePressure
+            | SetupParameter::BrakeBias => "%",
+            SetupParameter::FrontCamber
+            | SetupParameter::RearCamber
+            | SetupParameter::FrontToe
+            | SetupParameter::RearToe => "°",
+            SetupParameter::FrontLeftTyrePressure
+            | SetupParameter::FrontRightTyrePressure
+            | SetupParameter::RearLeftTyrePressure
+            | SetupParameter::RearRightTyrePressure => "psi",
+            SetupParameter::FuelLoad => "kg",
+            _ => "",
+        }
+    }
+
+    /// Decimals the game shows the value with
+    pub fn precision(&self) -> usize {
+        match self {
+            SetupParameter::FrontCamber | SetupParameter::RearCamber => 2,
+            SetupParameter::FrontToe | SetupParameter::RearToe => 2,
+            SetupParameter::FrontLeftTyrePressure
+            | SetupParameter::FrontRightTyrePressure
+            | SetupParameter::RearLeftTyrePressure
+            | SetupParameter::RearRightTyrePressure => 1,
+            SetupParameter::FuelLoad => 1,
+            _ => 0,
+        }
+    }
+
+    pub fn value(&self, setup: &CarSetupData) -> f32 {
+        let setup = *setup;
+        match self {
+            SetupParameter::FrontWing => setup.front_wing as f32,
+            SetupParameter::RearWing => setup.rear_wing as f32,
+            SetupParameter::OnThrottle => setup.on_throttle as f32,
+            SetupParameter::OffThrottle => setup.off_throttle as f32,
+            SetupParameter::EngineBraking => setup.engine_braking as f32,
+            SetupParameter::FrontCamber => setup.front_camber,
+            SetupParameter::RearCamber => setup.rear_camber,
+            SetupParameter::FrontToe => setup.front_toe,
+            SetupParameter::RearToe => setup.rear_toe,
+            SetupParameter::FrontSuspension => setup.front_suspension as f32,
+            SetupParameter::RearSuspension => setup.rear_suspension as f32,
+            SetupParameter::FrontAntiRollBar => setup.front_anti_roll_bar as f32,
+            SetupParameter::RearAntiRollBar => setup.rear_anti_roll_bar as f32,
+            SetupParameter::FrontRideHeight => setup.front_suspension_height as f32,
+            SetupParameter::RearRideHeight => setup.rear_suspension_height as f32,
+            SetupParameter::BrakePressure => setup.brake_pressure as f32,
+            SetupParameter::BrakeBias => setup.brake_bias as f32,
+            SetupParameter::FrontLeftTyrePressure => setup.front_left_tyre_pressure,
+            SetupParameter::FrontRightTyrePressure => setup.front_right_tyre_pressure,
+            SetupParameter::RearLeftTyrePressure => setup.rear_left_tyre_pressure,
+            SetupParameter::RearRightTyrePressure => setup.rear_right_tyre_pressure,
+            SetupParameter::Ballast => setup.ballast as f32,
+            SetupParameter::FuelLoad => setup.fuel_load,
+        }
+    }
+
+    /// Value with its unit, e.g. `-3.50°`
+    pub fn format(&self, value: f32) -> String {
+        format!("{:.*}{}", self.precision(), value, self.unit())
+    }
+}
+
+/// A parameter that differs between two setups
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetupChange {
+    pub parameter: SetupParameter,
+    pub from: f32,
+    pub to: f32,
+}
+
+impl SetupChange {
+    pub fn delta(&self) -> f32 {
+        self.to - self.from
+    }
+}
+
+impl fmt::Display for SetupChange {
+    /// e.g. `Front Wing Aero: 28 -> 31 (+3)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let delta = self.parameter.format(self.delta());
+        let sign = if self.delta() > 0.0 { "+" } else { "" };
+        write!(
+            f,
+            "{}: {} -> {} ({}{})",
+            self.parameter.name(),
+            self.parameter.format(self.from),
+            self.parameter.format(self.to),
+            sign,
+            delta
+        )
+    }
+}
+
+/// Parameters changed from `from` to `to`, in the order of the setup screen
+pub fn diff_setups(from: &CarSetupData, to: &CarSetupData) -> Vec<SetupChange> {
+    SetupParameter::ALL
+        .iter()
+        .map(|&parameter| SetupChange {
+            parameter,
+            from: parameter.value(from),
+            to: parameter.value(to),
+        })
+        .filter(|change| change.delta().abs() > EPSILON)
+        .collect()
+}
@@ -0,0 +1,51 @@
+use core::fmt;
+
+#[derive(Debug)]
+pub enum SetupError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    TomlDe(toml::de::Error),
+    TomlSer(toml::ser::Error),
+    /// Path without a `.toml` or `.json` extension
+    UnknownFormat(String),
+}
+
+impl std::error::Error for SetupError {}
+
+impl fmt::Display for SetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetupError::Io(e) => write!(f, "I/O error: {}", e),
+            SetupError::Json(e) => write!(f, "JSON error: {}", e),
+            SetupError::TomlDe(e) => write!(f, "TOML parse error: {}", e),
+            SetupError::TomlSer(e) => write!(f, "TOML write error: {}", e),
+            SetupError::UnknownFormat(path) => {
+                write!(f, "Unknown setup sheet format: {}", path)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for SetupError {
+    fn from(e: std::io::Error) -> Self {
+        SetupError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SetupError {
+    fn from(e: serde_json::Error) -> Self {
+        SetupError::Json(e)
+    }
+}
+
+impl From<toml::de::Error> for SetupError {
+    fn from(e: toml::de::Error) -> Self {
+        SetupError::TomlDe(e)
+    }
+}
+
+impl From<toml::ser::Error> for SetupError {
+    fn from(e: toml::ser::Error) -> Self {
+        SetupError::TomlSer(e)
+    }
+}
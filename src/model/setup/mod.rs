@@ -0,0 +1,11 @@
+pub mod diff;
+#[cfg(feature = "serde")]
+pub mod error;
+pub mod sheet;
+pub mod tracker;
+
+pub use diff::*;
+#[cfg(feature = "serde")]
+pub use error::*;
+pub use sheet::*;
+pub use tracker::*;
@@ -0,0 +1,335 @@
+#[cfg(feature = "serde")]
+use std::{fs, path::Path};
+
+#[cfg(feature = "serde")]
+use super::SetupError;
+use super::{SetupChange, diff_setups};
+use crate::{
+    constants::{TeamId, TrackId},
+    raw::CarSetupData,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AeroSetup {
+    pub front_wing: u8,
+    pub rear_wing: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransmissionSetup {
+    pub differential_on_throttle_pct: u8,
+    pub differential_off_throttle_pct: u8,
+    pub engine_braking_pct: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SuspensionGeometrySetup {
+    pub front_camber_deg: f32,
+    pub rear_camber_deg: f32,
+    pub front_toe_deg: f32,
+    pub rear_toe_deg: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SuspensionSetup {
+    pub front_suspension: u8,
+    pub rear_suspension: u8,
+    pub front_anti_roll_bar: u8,
+    pub rear_anti_roll_bar: u8,
+    pub front_ride_height: u8,
+    pub rear_ride_height: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BrakesSetup {
+    pub brake_pressure_pct: u8,
+    pub front_brake_bias_pct: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TyresSetup {
+    pub front_left_pressure_psi: f32,
+    pub front_right_pressure_psi: f32,
+    pub rear_left_pressure_psi: f32,
+    pub rear_right_pressure_psi: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeightSetup {
+    pub ballast: u8,
+    pub fuel_load_kg: f32,
+}
+
+/// A car setup laid out like the game's setup screen, with the units in the field names.
+///
+/// With the `serde` feature it's saved as TOML or JSON, e.g. to share setups:
+///
+/// ```toml
+/// name = "Monza low downforce"
+/// team = "Williams"
+/// track = "Monza"
+///
+/// [aero]
+/// front_wing = 6
+/// rear_wing = 3
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetupSheet {
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub team: Option<TeamId>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub track: Option<TrackId>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub notes: Option<String>,
+    pub aero: AeroSetup,
+    pub transmission: TransmissionSetup,
+    pub suspension_geometry: SuspensionGeometrySetup,
+    pub suspension: SuspensionSetup,
+    pub brakes: BrakesSetup,
+    pub tyres: TyresSetup,
+    pub weight: WeightSetup,
+}
+
+impl SetupSheet {
+    pub fn new(name: impl Into<String>, setup: &CarSetupData) -> Self {
+        let setup = *setup;
+        Self {
+            name: name.into(),
+            team: None,
+            track: None,
+            notes: None,
+            aero: AeroSetup {
+                front_wing: setup.front_wing,
+                rear_wing: setup.rear_wing,
+            },
+            transmission: TransmissionSetup {
+                differential_on_throttle_pct: setup.on_throttle,
+                differential_off_throttle_pct: setup.off_throttle,
+                engine_braking_pct: setup.engine_braking,
+            },
+            suspension_geometry: SuspensionGeometrySetup {
+                front_camber_deg: setup.front_camber,
+                rear_camber_deg: setup.rear_camber,
+                front_toe_deg: setup.front_toe,
+                rear_toe_deg: setup.rear_toe,
+            },
+            suspension: SuspensionSetup {
+                front_suspension: setup.front_suspension,
+                rear_suspension: setup.rear_suspension,
+                front_anti_roll_bar: setup.front_anti_roll_bar,
+                rear_anti_roll_bar: setup.rear_anti_roll_bar,
+                front_ride_height: setup.front_suspension_height,
+                rear_ride_height: setup.rear_suspension_height,
+            },
+            brakes: BrakesSetup {
+                brake_pressure_pct: setup.brake_pressure,
+                front_brake_bias_pct: setup.brake_bias,
+            },
+            tyres: TyresSetup {
+                front_left_pressure_psi: setup.front_left_tyre_pressure,
+                front_right_pressure_psi: setup.front_right_tyre_pressure,
+                rear_left_pressure_psi: setup.rear_left_tyre_pressure,
+                rear_right_pressure_psi: setup.rear_right_tyre_pressure,
+            },
+            weight: WeightSetup {
+                ballast: setup.ballast,
+                fuel_load_kg: setup.fuel_load,
+            },
+        }
+    }
+
+    pub fn with_team(mut self, team: TeamId) -> Self {
+        self.team = Some(team);
+        self
+    }
+
+    pub fn with_track(mut self, track: TrackId) -> Self {
+        self.track = Some(track);
+        self
+    }
+
+    pub fn with_notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = Some(notes.into());
+        self
+    }
+
+    pub fn setup(&self) -> CarSetupData {
+        CarSetupData {
+            front_wing: self.aero.front_wing,
+            rear_wing: self.aero.rear_wing,
+            on_throttle: self.transmission.differential_on_throttle_pct,
+            off_throttle: self.transmission.differential_off_throttle_pct,
+            front_camber: self.suspension_geometry.front_camber_deg,
+            rear_camber: self.suspension_geometry.rear_camber_deg,
+            front_toe: self.suspension_geometry.front_toe_deg,
+            rear_toe: self.suspension_geometry.rear_toe_deg,
+            front_suspension: self.suspension.front_suspension,
+            rear_suspension: self.suspension.rear_suspension,
+            front_anti_roll_bar: self.suspension.front_anti_roll_bar,
+            rear_anti_roll_bar: self.suspension.rear_anti_roll_bar,
+            front_suspension_height: self.suspension.front_ride_height,
+            rear_suspension_height: self.suspension.rear_ride_height,
+            brake_pressure: self.brakes.brake_pressure_pct,
+            brake_bias: self.brakes.front_brake_bias_pct,
+            engine_braking: self.transmission.engine_braking_pct,
+            rear_left_tyre_pressure: self.tyres.rear_left_pressure_psi,
+            rear_right_tyre_pressure: self.tyres.rear_right_pressure_psi,
+            front_left_tyre_pressure: self.tyres.front_left_pressure_psi,
+            front_right_tyre_pressure: self.tyres.front_right_pressure_psi,
+            ballast: self.weight.ballast,
+            fuel_load: self.weight.fuel_load_kg,
+        }
+    }
+
+    /// Parameters changed going from this sheet to `other`
+    pub fn diff(&self, other: &SetupSheet) -> Vec<SetupChange> {
+        diff_setups(&self.setup(), &other.setup())
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_toml(&self) -> Result<String, SetupError> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_toml(toml: &str) -> Result<Self, SetupError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, SetupError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, SetupError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Reads a `.toml` or `.json` sheet
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SetupError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+        match SheetFormat::from_path(path)? {
+            SheetFormat::Toml => Self::from_toml(&contents),
+            SheetFormat::Json => Self::from_json(&contents),
+        }
+    }
+
+    /// Writes a `.toml` or `.json` sheet
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SetupError> {
+        let path = path.as_ref();
+        let contents = match SheetFormat::from_path(path)? {
+            SheetFormat::Toml => self.to_toml()?,
+            SheetFormat::Json => self.to_json()?,
+        };
+        fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+enum SheetFormat {
+    Toml,
+    Json,
+}
+
+#[cfg(feature = "serde")]
+impl SheetFormat {
+    fn from_path(path: &Path) -> Result<Self, SetupError> {
+        let extension = path.extension().and_then(|e| e.to_str());
+        match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("toml") => Ok(SheetFormat::Toml),
+            Some("json") => Ok(SheetFormat::Json),
+            _ => Err(SetupError::UnknownFormat(path.display().to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::SetupParameter;
+
+    fn car_setup() -> CarSetupData {
+        let mut setup: CarSetupData = bytemuck::Zeroable::zeroed();
+        setup.front_wing = 6;
+        setup.rear_wing = 3;
+        setup.front_camber = -3.5;
+        setup.brake_bias = 56;
+        setup.front_left_tyre_pressure = 22.5;
+        setup.fuel_load = 12.4;
+        setup
+    }
+
+    fn sheet() -> SetupSheet {
+        SetupSheet::new("Monza low downforce", &car_setup())
+            .with_team(TeamId::Williams)
+            .with_track(TrackId::Monza)
+    }
+
+    #[test]
+    fn sheet_gives_back_the_setup() {
+        let sheet = sheet();
+        assert_eq!(sheet.aero.front_wing, 6);
+        assert_eq!(sheet.brakes.front_brake_bias_pct, 56);
+        assert!(bytemuck::bytes_of(&sheet.setup()) == bytemuck::bytes_of(&car_setup()));
+    }
+
+    #[test]
+    fn diff_lists_changed_parameters() {
+        let mut other = sheet();
+        other.aero.front_wing = 8;
+        other.suspension_geometry.front_camber_deg = -3.0;
+
+        let changes = sheet().diff(&other);
+        let parameters: Vec<SetupParameter> = changes.iter().map(|c| c.parameter).collect();
+        assert_eq!(
+            parameters,
+            [SetupParameter::FrontWing, SetupParameter::FrontCamber]
+        );
+        assert_eq!(changes[0].to_string(), "Front Wing Aero: 6 -> 8 (+2)");
+        assert!(sheet().diff(&sheet()).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn toml_and_json_round_trip() {
+        let sheet = sheet().with_notes("Tow on the straights");
+
+        let toml = sheet.to_toml().unwrap();
+        assert!(toml.contains("front_wing = 6"));
+        assert_eq!(SetupSheet::from_toml(&toml).unwrap(), sheet);
+        assert_eq!(
+            SetupSheet::from_json(&sheet.to_json().unwrap()).unwrap(),
+            sheet
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn sheet_format_comes_from_the_extension() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("setup-{}.TOML", std::process::id()));
+        sheet().save(&path).unwrap();
+        assert_eq!(SetupSheet::load(&path).unwrap(), sheet());
+        fs::remove_file(&path).unwrap();
+
+        let path = dir.join("setup.txt");
+        assert!(matches!(
+            sheet().save(&path),
+            Err(SetupError::UnknownFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,206 @@
+use super::{SetupChange, SetupParameter, diff_setups};
+use crate::{
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{CarSetupData, PacketCarSetupData, constants::MAX_NUM_CARS},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetupEvent {
+    /// Setup of a car differs from the previous packet, e.g. after a pit stop or a garage
+    /// visit in practice
+    Changed {
+        session_time: f32,
+        car_idx: u8,
+        changes: Vec<SetupChange>,
+    },
+    /// The player's front wing will be adjusted at the next pit stop
+    FrontWingScheduled {
+        session_time: f32,
+        from: f32,
+        to: f32,
+    },
+    /// The front wing adjustment of the next pit stop was taken back
+    FrontWingCancelled { session_time: f32 },
+}
+
+/// Follows the car setups packet to tell when a setup changes during a session.
+///
+/// Cars with restricted telemetry send an all-zero setup, they're left out.
+#[derive(Debug, Clone)]
+pub struct SetupTracker {
+    session_uid: Option<u64>,
+    setups: [Option<CarSetupData>; MAX_NUM_CARS],
+    /// Front wing after the next pit stop, when it differs from the current one
+    pending_front_wing: Option<f32>,
+}
+
+impl Default for SetupTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SetupTracker {
+    pub fn new() -> Self {
+        Self {
+            session_uid: None,
+            setups: [None; MAX_NUM_CARS],
+            pending_front_wing: None,
+        }
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::new();
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    pub fn push_car_setups(&mut self, packet: &PacketCarSetupData) -> Vec<SetupEvent> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+
+        let mut events = Vec::new();
+        let car_setups = packet.car_setup_data;
+        for (car_idx, setup) in car_setups.iter().enumerate() {
+            if bytemuck::bytes_of(setup).iter().all(|&b| b == 0) {
+                continue;
+            }
+            if let Some(previous) = self.setups[car_idx].replace(*setup) {
+                let changes = diff_setups(&previous, setup);
+                if !changes.is_empty() {
+                    events.push(SetupEvent::Changed {
+                        session_time: header.session_time,
+                        car_idx: car_idx as u8,
+                        changes,
+                    });
+                }
+            }
+        }
+
+        let Some(player) = self
+            .setups
+            .get(header.player_car_index as usize)
+            .copied()
+            .flatten()
+        else {
+            return events;
+        };
+        let front_wing = SetupParameter::FrontWing.value(&player);
+        let next = packet.next_front_wing_value;
+        let pending = (next.round() != front_wing).then_some(next.round());
+        // Applied at the stop, which is reported as a setup change
+        let applied = self.pending_front_wing == Some(front_wing);
+        match pending {
+            Some(to) if pending != self.pending_front_wing => {
+                events.push(SetupEvent::FrontWingScheduled {
+                    session_time: header.session_time,
+                    from: front_wing,
+                    to,
+                });
+            }
+            None if self.pending_front_wing.is_some() && !applied => {
+                events.push(SetupEvent::FrontWingCancelled {
+                    session_time: header.session_time,
+                });
+            }
+            _ => {}
+        }
+        self.pending_front_wing = pending;
+        events
+    }
+
+    /// Latest setup of a car, `None` if restricted or not received yet
+    pub fn setup(&self, car_idx: u8) -> Option<&CarSetupData> {
+        self.setups.get(car_idx as usize)?.as_ref()
+    }
+
+    /// Front wing the player's car will have after the next pit stop, if adjusted
+    pub fn pending_front_wing(&self) -> Option<f32> {
+        self.pending_front_wing
+    }
+}
+
+impl PacketConsumer for SetupTracker {
+    type Event = SetupEvent;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<SetupEvent> {
+        match packet {
+            AnyRawPacket::CarSetups(p) => self.push_car_setups(p),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(session_time: f32, front_wing: u8, next_front_wing: f32) -> PacketCarSetupData {
+        let mut packet: PacketCarSetupData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.header.session_time = session_time;
+        packet.car_setup_data[0].front_wing = front_wing;
+        packet.car_setup_data[0].brake_bias = 56;
+        packet.next_front_wing_value = next_front_wing;
+        packet
+    }
+
+    #[test]
+    fn setup_changes_are_reported_and_restricted_cars_skipped() {
+        let mut tracker = SetupTracker::new();
+        assert!(tracker.push_car_setups(&packet(1.0, 6, 6.0)).is_empty());
+        assert!(tracker.setup(1).is_none());
+
+        let mut changed = packet(2.0, 6, 6.0);
+        changed.car_setup_data[0].brake_bias = 58;
+        let events = tracker.push_car_setups(&changed);
+        let [
+            SetupEvent::Changed {
+                car_idx: 0,
+                changes,
+                ..
+            },
+        ] = &events[..]
+        else {
+            panic!("unexpected events {:?}", events);
+        };
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].parameter, SetupParameter::BrakeBias);
+        assert_eq!(changes[0].delta(), 2.0);
+    }
+
+    #[test]
+    fn front_wing_scheduled_then_applied() {
+        let mut tracker = SetupTracker::new();
+        tracker.push_car_setups(&packet(1.0, 6, 6.0));
+
+        let events = tracker.push_car_setups(&packet(2.0, 6, 8.0));
+        assert_eq!(
+            events,
+            [SetupEvent::FrontWingScheduled {
+                session_time: 2.0,
+                from: 6.0,
+                to: 8.0,
+            }]
+        );
+        assert!(tracker.push_car_setups(&packet(3.0, 6, 8.0)).is_empty());
+        assert_eq!(tracker.pending_front_wing(), Some(8.0));
+
+        let events = tracker.push_car_setups(&packet(4.0, 8, 8.0));
+        assert!(matches!(&events[..], [SetupEvent::Changed { .. }]));
+        assert_eq!(tracker.pending_front_wing(), None);
+    }
+
+    #[test]
+    fn front_wing_adjustment_taken_back() {
+        let mut tracker = SetupTracker::new();
+        tracker.push_car_setups(&packet(1.0, 6, 8.0));
+        let events = tracker.push_car_setups(&packet(2.0, 6, 6.0));
+
+        assert_eq!(
+            events,
+            [SetupEvent::FrontWingCancelled { session_time: 2.0 }]
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use crate::{
+    constants::Colour,
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{PacketCarStatusData, PacketCarTelemetryData},
+};
+
+/// LEDs of the rev light strip, as in `rev_lights_bit_value`
+pub const NUM_REV_LEDS: usize = 15;
+
+/// Colour band of the strip, the game's wheel lights five LEDs of each
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LedZone {
+    Green,
+    Red,
+    Blue,
+}
+
+impl LedZone {
+    /// Zone of an LED in the game's layout, leftmost first
+    pub fn of(led: usize) -> Self {
+        match led * 3 / NUM_REV_LEDS {
+            0 => LedZone::Green,
+            1 => LedZone::Red,
+            _ => LedZone::Blue,
+        }
+    }
+
+    pub fn default_colour(&self) -> Colour {
+        match self {
+            LedZone::Green => Colour::new(0, 200, 40),
+            LedZone::Red => Colour::new(230, 0, 0),
+            LedZone::Blue => Colour::new(60, 60, 255),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Led {
+    pub zone: LedZone,
+    pub colour: Colour,
+    pub on: bool,
+}
+
+/// When each LED lights up, by engine RPM
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RpmPattern {
+    /// RPM each LED lights at, leftmost first
+    Absolute([u16; NUM_REV_LEDS]),
+    /// LEDs spread evenly from `start` to `end`, as fractions of the idle to max RPM range
+    Relative { start: f32, end: f32 },
+}
+
+impl RpmPattern {
+    /// RPM each LED lights at
+    pub fn thresholds(&self, idle_rpm: u16, max_rpm: u16) -> [u16; NUM_REV_LEDS] {
+        match *self {
+            RpmPattern::Absolute(thresholds) => thresholds,
+            RpmPattern::Relative { start, end } => {
+                let range = max_rpm.saturating_sub(idle_rpm) as f32;
+                let step = (end - start) / (NUM_REV_LEDS - 1) as f32;
+                std::array::from_fn(|i| {
+                    idle_rpm as f32 + range * (start + step * i as f32).clamp(0.0, 1.0)
+                })
+                .map(|rpm: f32| rpm.round() as u16)
+            }
+        }
+    }
+}
+
+/// What the strip shows, for a [`ShiftLightDriver`] to render
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShiftLightState {
+    pub leds: [Led; NUM_REV_LEDS],
+    pub engine_rpm: u16,
+    /// `rev_lights_percent` of the game
+    pub rev_percent: u8,
+    /// Gear selected, N = 0, R = -1
+    pub gear: i8,
+    /// Gear the game suggests, `None` if none
+    pub suggested_gear: Option<i8>,
+    /// Every LED is lit, i.e. change up now. Drivers usually flash the strip.
+    pub shift: bool,
+}
+
+impl ShiftLightState {
+    pub fn lit(&self) -> usize {
+        self.leds.iter().filter(|led| led.on).count()
+    }
+}
+
+/// Output for the shift lights, e.g. a simulated LED strip, a terminal widget or an adapter
+/// for a device
+pub trait ShiftLightDriver {
+    type Error;
+
+    fn render(&mut self, state: &ShiftLightState) -> Result<(), Self::Error>;
+}
+
+/// Decodes the player's rev lights into per LED states.
+///
+/// By default the LEDs follow the game's `rev_lights_bit_value`. With an [`RpmPattern`] for
+/// the current gear, or a default one, they light by engine RPM instead, using the
+/// `idle_rpm` and `max_rpm` of the car status packet for relative patterns.
+#[derive(Debug, Clone)]
+pub struct ShiftLights {
+    patterns: HashMap<i8, RpmPattern>,
+    default_pattern: Option<RpmPattern>,
+    idle_rpm: u16,
+    max_rpm: u16,
+    state: ShiftLightState,
+}
+
+impl Default for ShiftLights {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShiftLights {
+    pub fn new() -> Self {
+        let led = |i| Led {
+            zone: LedZone::of(i),
+            colour: LedZone::of(i).default_colour(),
+            on: false,
+        };
+        Self {
+            patterns: HashMap::new(),
+            default_pattern: None,
+            idle_rpm: 0,
+            max_rpm: 0,
+            state: ShiftLightState {
+                leds: std::array::from_fn(led),
+                engine_rpm: 0,
+                rev_percent: 0,
+                gear: 0,
+                suggested_gear: None,
+                shift: false,
+            },
+        }
+    }
+
+    pub fn with_colour(mut self, zone: LedZone, colour: Colour) -> Self {
+        for led in self.state.leds.iter_mut().filter(|led| led.zone == zone) {
+            led.colour = colour;
+        }
+        self
+    }
+
+    /// Lights by RPM in `gear`
+    pub fn with_pattern(mut self, gear: i8, pattern: RpmPattern) -> Self {
+        self.patterns.insert(gear, pattern);
+        self
+    }
+
+    /// Lights by RPM in gears without their own pattern
+    pub fn with_default_pattern(mut self, pattern: RpmPattern) -> Self {
+        self.default_pattern = Some(pattern);
+        self
+    }
+
+    pub fn push_car_status(&mut self, packet: &PacketCarStatusData) {
+        let statuses = packet.car_status_data;
+        if let Some(status) = statuses.get(packet.header.player_car_index as usize) {
+            self.idle_rpm = status.idle_rpm;
+            self.max_rpm = status.max_rpm;
+        }
+    }
+
+    pub fn push_car_telemetry(&mut self, packet: &PacketCarTelemetryData) {
+        let car_telemetry = packet.car_telemetry_data;
+        let Some(telemetry) = car_telemetry.get(packet.header.player_car_index as usize) else {
+            return;
+        };
+
+        let gear = telemetry.gear;
+        let engine_rpm = telemetry.engine_rpm;
+        let pattern = self
+            .patterns
+            .get(&gear)
+            .or(self.default_pattern.as_ref())
+            .filter(|p| matches!(p, RpmPattern::Absolute(_)) || self.max_rpm > 0);
+        let lit: [bool; NUM_REV_LEDS] = match pattern {
+            Some(pattern) => pattern
+                .thresholds(self.idle_rpm, self.max_rpm)
+                .map(|threshold| engine_rpm >= threshold),
+            None => {
+                let bits = telemetry.rev_lights_bit_value;
+                std::array::from_fn(|i| bits & (1 << i) != 0)
+            }
+        };
+
+        let state = &mut self.state;
+        for (led, on) in state.leds.iter_mut().zip(lit) {
+            led.on = on;
+        }
+        state.engine_rpm = engine_rpm;
+        state.rev_percent = telemetry.rev_lights_percent;
+        state.gear = gear;
+        state.suggested_gear = Some(packet.suggested_gear).filter(|&g| g > 0);
+        state.shift = lit.iter().all(|&on| on);
+    }
+
+    pub fn state(&self) -> &ShiftLightState {
+        &self.state
+    }
+
+    pub fn render<D: ShiftLightDriver>(&self, driver: &mut D) -> Result<(), D::Error> {
+        driver.render(&self.state)
+    }
+}
+
+impl PacketConsumer for ShiftLights {
+    type Event = ShiftLightState;
+
+    /// Yields the new state after each car telemetry packet
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<ShiftLightState> {
+        match packet {
+            AnyRawPacket::CarTelemetry(p) => {
+                self.push_car_telemetry(p);
+                vec![self.state]
+            }
+            AnyRawPacket::CarStatus(p) => {
+                self.push_car_status(p);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Renders the strip as a line of coloured dots on an ANSI terminal, with the gear
+pub struct AnsiStrip<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> AnsiStrip<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> ShiftLightDriver for AnsiStrip<W> {
+    type Error = io::Error;
+
+    /// Redraws the current line
+    fn render(&mut self, state: &ShiftLightState) -> io::Result<()> {
+        write!(self.writer, "\r")?;
+        for led in &state.leds {
+            match led.on {
+                true => {
+                    let Colour { red, green, blue } = led.colour;
+                    write!(self.writer, "\x1b[38;2;{};{};{}m●\x1b[0m", red, green, blue)?;
+                }
+                false => write!(self.writer, "\x1b[2m○\x1b[0m")?,
+            }
+        }
+        let gear = match state.gear {
+            -1 => "R".to_string(),
+            0 => "N".to_string(),
+            gear => gear.to_string(),
+        };
+        write!(self.writer, "  {:>2} {:>5} rpm", gear, state.engine_rpm)?;
+        if let Some(suggested) = state.suggested_gear {
+            write!(self.writer, "  -> {}", suggested)?;
+        }
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn telemetry(gear: i8, engine_rpm: u16, bits: u16) -> PacketCarTelemetryData {
+        let mut packet: PacketCarTelemetryData = bytemuck::Zeroable::zeroed();
+        let car = &mut packet.car_telemetry_data[0];
+        car.gear = gear;
+        car.engine_rpm = engine_rpm;
+        car.rev_lights_bit_value = bits;
+        packet
+    }
+
+    fn status(idle_rpm: u16, max_rpm: u16) -> PacketCarStatusData {
+        let mut packet: PacketCarStatusData = bytemuck::Zeroable::zeroed();
+        packet.car_status_data[0].idle_rpm = idle_rpm;
+        packet.car_status_data[0].max_rpm = max_rpm;
+        packet
+    }
+
+    fn lit(lights: &ShiftLights) -> Vec<bool> {
+        lights.state().leds.iter().map(|led| led.on).collect()
+    }
+
+    #[test]
+    fn consumes_telemetry_into_states() {
+        let mut lights = ShiftLights::new();
+        assert!(
+            lights
+                .consume(&AnyRawPacket::CarStatus(status(4000, 13000)))
+                .is_empty()
+        );
+
+        let states = lights.consume(&AnyRawPacket::CarTelemetry(telemetry(3, 11000, 0b111)));
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].lit(), 3);
+        assert_eq!(states[0].gear, 3);
+    }
+
+    #[test]
+    fn zones_are_five_leds_each() {
+        let zones: Vec<LedZone> = (0..NUM_REV_LEDS).map(LedZone::of).collect();
+        assert_eq!(zones[..5], [LedZone::Green; 5]);
+        assert_eq!(zones[5..10], [LedZone::Red; 5]);
+        assert_eq!(zones[10..], [LedZone::Blue; 5]);
+    }
+
+    #[test]
+    fn relative_pattern_spreads_over_the_rpm_range() {
+        let thresholds = RpmPattern::Relative {
+            start: 0.3,
+            end: 1.0,
+        }
+        .thresholds(4000, 14000);
+        assert_eq!(thresholds[0], 7000);
+        assert_eq!(thresholds[7], 10500);
+        assert_eq!(thresholds[14], 14000);
+    }
+
+    #[test]
+    fn leds_follow_the_game_bits_without_a_pattern() {
+        let mut lights = ShiftLights::new();
+        lights.push_car_telemetry(&telemetry(4, 11000, 0b111));
+
+        assert_eq!(lights.state().lit(), 3);
+        assert!(lit(&lights)[..3].iter().all(|&on| on));
+        assert!(!lights.state().shift);
+
+        lights.push_car_telemetry(&telemetry(4, 12000, 0x7fff));
+        assert!(lights.state().shift);
+    }
+
+    #[test]
+    fn relative_pattern_needs_the_car_status() {
+        let pattern = RpmPattern::Relative {
+            start: 0.0,
+            end: 1.0,
+        };
+        let mut lights = ShiftLights::new().with_default_pattern(pattern);
+        lights.push_car_telemetry(&telemetry(3, 9000, 0b1));
+        assert_eq!(lights.state().lit(), 1);
+
+        lights.push_car_status(&status(0, 14000));
+        lights.push_car_telemetry(&telemetry(3, 7000, 0b1));
+        assert_eq!(lights.state().lit(), 8);
+    }
+
+    #[test]
+    fn gear_pattern_takes_precedence() {
+        let mut absolute = [u16::MAX; NUM_REV_LEDS];
+        absolute[..2].copy_from_slice(&[5000, 6000]);
+        let mut lights = ShiftLights::new()
+            .with_default_pattern(RpmPattern::Absolute([0; NUM_REV_LEDS]))
+            .with_pattern(1, RpmPattern::Absolute(absolute));
+
+        lights.push_car_telemetry(&telemetry(1, 6500, 0));
+        assert_eq!(lights.state().lit(), 2);
+        lights.push_car_telemetry(&telemetry(2, 6500, 0));
+        assert!(lights.state().shift);
+    }
+
+    #[test]
+    fn ansi_strip_draws_leds_and_gear() {
+        let colour = Colour::new(1, 2, 3);
+        let mut lights = ShiftLights::new().with_colour(LedZone::Green, colour);
+        let mut packet = telemetry(-1, 3000, 0b1);
+        packet.suggested_gear = 1;
+        lights.push_car_telemetry(&packet);
+
+        let mut strip = AnsiStrip::new(Vec::new());
+        lights.render(&mut strip).unwrap();
+        let output = String::from_utf8(strip.into_inner()).unwrap();
+
+        assert!(output.starts_with("\r\x1b[38;2;1;2;3m●\x1b[0m\x1b[2m○"));
+        assert_eq!(output.matches('○').count(), NUM_REV_LEDS - 1);
+        assert!(output.ends_with("   R  3000 rpm  -> 1"));
+    }
+}
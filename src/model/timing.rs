@@ -0,0 +1,137 @@
+use core::fmt;
+use std::{str::FromStr, time::Duration};
+
+/// Lap or sector time with millisecond resolution, displayed as `mm:ss.mmm`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LapTime(u32);
+
+impl LapTime {
+    pub const ZERO: LapTime = LapTime(0);
+
+    pub const fn from_millis(ms: u32) -> Self {
+        Self(ms)
+    }
+
+    /// Combines the split `*_minutes_part`/`*_ms_part` fields used by the lap packets
+    pub const fn from_parts(minutes: u8, ms: u16) -> Self {
+        Self(minutes as u32 * 60_000 + ms as u32)
+    }
+
+    pub const fn as_millis(&self) -> u32 {
+        self.0
+    }
+
+    pub const fn as_duration(&self) -> Duration {
+        Duration::from_millis(self.0 as u64)
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn saturating_sub(self, other: LapTime) -> LapTime {
+        LapTime(self.0.saturating_sub(other.0))
+    }
+}
+
+impl From<LapTime> for Duration {
+    fn from(time: LapTime) -> Self {
+        time.as_duration()
+    }
+}
+
+impl From<Duration> for LapTime {
+    /// Truncates to whole milliseconds, saturating at `u32::MAX`
+    fn from(duration: Duration) -> Self {
+        LapTime(duration.as_millis().min(u32::MAX as u128) as u32)
+    }
+}
+
+impl fmt::Display for LapTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes = self.0 / 60_000;
+        let seconds = self.0 / 1000 % 60;
+        let millis = self.0 % 1000;
+        write!(f, "{:02}:{:02}.{:03}", minutes, seconds, millis)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LapTimeParseError(String);
+
+impl std::error::Error for LapTimeParseError {}
+
+impl fmt::Display for LapTimeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid lap time: {:?}", self.0)
+    }
+}
+
+impl FromStr for LapTime {
+    type Err = LapTimeParseError;
+
+    /// Parses `mm:ss.mmm`, as well as `m:ss.mmm`, `ss.mmm` and fractions shorter than 3 digits
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || LapTimeParseError(s.to_owned());
+        let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+
+        let (minutes, rest) = match s.trim().split_once(':') {
+            Some((m, rest)) if digits(m) => (m.parse::<u32>().map_err(|_| err())?, rest),
+            Some(_) => return Err(err()),
+            None => (0, s.trim()),
+        };
+
+        let (seconds, fraction) = rest.split_once('.').unwrap_or((rest, ""));
+        if !digits(seconds) || fraction.len() > 3 || (!fraction.is_empty() && !digits(fraction)) {
+            return Err(err());
+        }
+
+        let seconds: u32 = seconds.parse().map_err(|_| err())?;
+        if s.contains(':') && seconds >= 60 {
+            return Err(err());
+        }
+
+        let millis = format!("{:0<3}", fraction)
+            .parse::<u32>()
+            .map_err(|_| err())?;
+
+        minutes
+            .checked_mul(60_000)
+            .and_then(|ms| ms.checked_add(seconds.checked_mul(1000)?))
+            .and_then(|ms| ms.checked_add(millis))
+            .map(LapTime)
+            .ok_or_else(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn displays_zero_padded_minutes() {
+        assert_eq!(LapTime::from_millis(83_456).to_string(), "01:23.456");
+        assert_eq!(LapTime::from_millis(5_007).to_string(), "00:05.007");
+        assert_eq!(LapTime::from_parts(12, 3_000).to_string(), "12:03.000");
+    }
+
+    #[test]
+    fn parses_what_it_displays() {
+        for ms in [0, 5_007, 83_456, 754_000] {
+            let time = LapTime::from_millis(ms);
+            assert_eq!(time.to_string().parse::<LapTime>(), Ok(time));
+        }
+        assert_eq!("1:23.4".parse(), Ok(LapTime::from_millis(83_400)));
+        assert_eq!("59.9".parse(), Ok(LapTime::from_millis(59_900)));
+        assert!("1:60.000".parse::<LapTime>().is_err());
+        assert!("1:2x.000".parse::<LapTime>().is_err());
+    }
+
+    #[test]
+    fn converts_to_and_from_durations() {
+        let time = LapTime::from(Duration::from_micros(83_456_999));
+        assert_eq!(time.as_millis(), 83_456);
+        assert_eq!(Duration::from(time), Duration::from_millis(83_456));
+    }
+}
@@ -1,6 +1,7 @@
 macro_rules! define_appendix {
     ($name:ident { $($id:expr => $variant:ident $(=> $display:expr)?),* $(,)? }) => {
-        #[derive(Debug, Clone, Copy)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub enum $name { $($variant),* }
 
         impl $name {
@@ -9,14 +10,25 @@ macro_rules! define_appendix {
                     $(Self::$variant => define_appendix!(@display $variant $(, $display)?)),*
                 }
             }
+
+            pub fn id(&self) -> u8 {
+                match self {
+                    $(Self::$variant => $id),*
+                }
+            }
+
+            pub fn try_from_id(value: u8) -> Option<Self> {
+                match value {
+                    $($id => Some(Self::$variant)),*,
+                    _ => None,
+                }
+            }
         }
 
         impl From<u8> for $name {
             fn from(value: u8) -> Self {
-                match value {
-                    $($id => Self::$variant),*,
-                    _ => panic!("Unknown {} ID: {}", stringify!($name), value),
-                }
+                Self::try_from_id(value)
+                    .unwrap_or_else(|| panic!("Unknown {} ID: {}", stringify!($name), value))
             }
         }
     };
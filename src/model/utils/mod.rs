@@ -1,5 +1,7 @@
 pub mod macros;
+pub mod strings;
 pub mod wheel;
 
 pub(crate) use macros::*;
+pub use strings::*;
 pub use wheel::*;
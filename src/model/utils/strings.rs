@@ -0,0 +1,13 @@
+/// Decodes a null terminated UTF-8 buffer, as used for participant names.
+///
+/// Invalid trailing bytes (e.g. a multi-byte character cut off by the game's
+/// truncation) are dropped rather than failing the whole name.
+pub fn null_terminated_str(bytes: &[u8]) -> &str {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let bytes = &bytes[..end];
+
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(e) => std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default(),
+    }
+}
@@ -0,0 +1,331 @@
+use crate::{
+    constants::{ForecastAccuracy, SessionType, TemperatureTrend, Weather},
+    raw::{PacketSessionData, WeatherForecastSample},
+};
+
+/// Typed forecast for one session at one time offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Forecast {
+    pub session_type: SessionType,
+    /// Minutes ahead the forecast is for
+    pub time_offset: u8,
+    pub weather: Weather,
+    pub track_temperature: i8,
+    pub track_temperature_trend: TemperatureTrend,
+    pub air_temperature: i8,
+    pub air_temperature_trend: TemperatureTrend,
+    /// 0-100
+    pub rain_percentage: u8,
+}
+
+impl Forecast {
+    /// `None` if any of the appendix ids is unknown
+    pub fn from_sample(sample: &WeatherForecastSample) -> Option<Self> {
+        Some(Self {
+            session_type: SessionType::try_from_id(sample.session_type)?,
+            time_offset: sample.time_offset,
+            weather: Weather::try_from_id(sample.weather)?,
+            track_temperature: sample.track_temperature,
+            track_temperature_trend: TemperatureTrend::try_from_id(
+                sample.track_temperature_change as u8,
+            )?,
+            air_temperature: sample.air_temperature,
+            air_temperature_trend: TemperatureTrend::try_from_id(
+                sample.air_temperature_change as u8,
+            )?,
+            rain_percentage: sample.rain_percentage,
+        })
+    }
+}
+
+/// A forecast as it was at some point of the session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForecastRevision {
+    /// Session time the revision was first received
+    pub session_time: f32,
+    pub forecast: Forecast,
+}
+
+/// Change between the first and latest forecast for the same session and offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForecastDrift {
+    pub from: Forecast,
+    pub to: Forecast,
+    /// Number of times the forecast changed
+    pub revisions: usize,
+}
+
+impl ForecastDrift {
+    pub fn rain_percentage(&self) -> i16 {
+        self.to.rain_percentage as i16 - self.from.rain_percentage as i16
+    }
+
+    pub fn track_temperature(&self) -> i16 {
+        self.to.track_temperature as i16 - self.from.track_temperature as i16
+    }
+
+    pub fn air_temperature(&self) -> i16 {
+        self.to.air_temperature as i16 - self.from.air_temperature as i16
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ForecastSeries {
+    session_type: SessionType,
+    time_offset: u8,
+    revisions: Vec<ForecastRevision>,
+}
+
+/// Deduplicated weather forecast of a session, and the sessions after it.
+///
+/// Session packets repeat the same samples every time, so samples are keyed by
+/// `session_type` and `time_offset` and a new revision is only kept when a forecast
+/// actually changes, which gives the forecast drift over the session.
+#[derive(Debug, Clone, Default)]
+pub struct WeatherTimeline {
+    session_uid: Option<u64>,
+    session_type: Option<SessionType>,
+    approximate: bool,
+    /// Sorted by session type id, then time offset
+    series: Vec<ForecastSeries>,
+    /// Session types in the order the game first sent them, which is weekend order
+    session_types: Vec<SessionType>,
+}
+
+impl WeatherTimeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::new();
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    pub fn push_session(&mut self, packet: &PacketSessionData) {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.session_type = SessionType::try_from_id(packet.session_type);
+        self.approximate = packet.forecast_accuracy() == Some(ForecastAccuracy::Approximate);
+
+        let samples = packet.weather_forecast_samples;
+        let count = (packet.num_weather_forecast_samples as usize).min(samples.len());
+        for forecast in samples[..count].iter().filter_map(Forecast::from_sample) {
+            let key = (forecast.session_type.id(), forecast.time_offset);
+            let revision = ForecastRevision {
+                session_time: header.session_time,
+                forecast,
+            };
+
+            match self
+                .series
+                .binary_search_by_key(&key, |s| (s.session_type.id(), s.time_offset))
+            {
+                Ok(i) => {
+                    let revisions = &mut self.series[i].revisions;
+                    if revisions.last().is_none_or(|r| r.forecast != forecast) {
+                        revisions.push(revision);
+                    }
+                }
+                Err(i) => {
+                    if !self.session_types.contains(&forecast.session_type) {
+                        self.session_types.push(forecast.session_type);
+                    }
+                    self.series.insert(
+                        i,
+                        ForecastSeries {
+                            session_type: forecast.session_type,
+                            time_offset: forecast.time_offset,
+                            revisions: vec![revision],
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Type of the session the packets are from
+    pub fn session_type(&self) -> Option<SessionType> {
+        self.session_type
+    }
+
+    /// The game only gives an approximate forecast on this difficulty
+    pub fn is_approximate(&self) -> bool {
+        self.approximate
+    }
+
+    /// Session types a forecast is known for, in weekend order
+    pub fn session_types(&self) -> Vec<SessionType> {
+        self.session_types.clone()
+    }
+
+    /// Latest forecast of a session, by time offset
+    pub fn forecasts(&self, session_type: SessionType) -> Vec<Forecast> {
+        self.series
+            .iter()
+            .filter(|s| s.session_type == session_type)
+            .filter_map(|s| s.revisions.last().map(|r| r.forecast))
+            .collect()
+    }
+
+    pub fn forecast(&self, session_type: SessionType, time_offset: u8) -> Option<Forecast> {
+        self.revisions(session_type, time_offset)
+            .last()
+            .map(|r| r.forecast)
+    }
+
+    /// Every distinct forecast received for a session and offset, oldest first
+    pub fn revisions(&self, session_type: SessionType, time_offset: u8) -> &[ForecastRevision] {
+        self.series
+            .iter()
+            .find(|s| s.session_type == session_type && s.time_offset == time_offset)
+            .map_or(&[], |s| s.revisions.as_slice())
+    }
+
+    pub fn drift(&self, session_type: SessionType, time_offset: u8) -> Option<ForecastDrift> {
+        let revisions = self.revisions(session_type, time_offset);
+        Some(ForecastDrift {
+            from: revisions.first()?.forecast,
+            to: revisions.last()?.forecast,
+            revisions: revisions.len() - 1,
+        })
+    }
+
+    /// Rain percentage expected `minutes` from now in the current session, interpolated
+    /// between the forecast offsets. `None` past the last offset.
+    pub fn rain_percentage_in(&self, minutes: f32) -> Option<f32> {
+        let forecasts = self.forecasts(self.session_type?);
+        let after = forecasts
+            .iter()
+            .position(|f| f.time_offset as f32 >= minutes)?;
+
+        let next = &forecasts[after];
+        let Some(previous) = after.checked_sub(1).map(|i| &forecasts[i]) else {
+            return Some(next.rain_percentage as f32);
+        };
+
+        let span = (next.time_offset - previous.time_offset) as f32;
+        let fraction = (minutes - previous.time_offset as f32) / span;
+        let (from, to) = (previous.rain_percentage as f32, next.rain_percentage as f32);
+        Some(from + (to - from) * fraction)
+    }
+
+    /// Weather forecast for `minutes` from now in the current session, i.e. that of the
+    /// latest offset not after it
+    pub fn weather_in(&self, minutes: f32) -> Option<Weather> {
+        self.forecasts(self.session_type?)
+            .iter()
+            .take_while(|f| f.time_offset as f32 <= minutes)
+            .last()
+            .map(|f| f.weather)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(session_type: u8, time_offset: u8, weather: u8, rain: u8) -> WeatherForecastSample {
+        let mut sample: WeatherForecastSample = bytemuck::Zeroable::zeroed();
+        sample.session_type = session_type;
+        sample.time_offset = time_offset;
+        sample.weather = weather;
+        sample.track_temperature = 30;
+        sample.air_temperature = 22;
+        sample.track_temperature_change = 2;
+        sample.air_temperature_change = 2;
+        sample.rain_percentage = rain;
+        sample
+    }
+
+    fn session(
+        session_uid: u64,
+        session_time: f32,
+        samples: &[WeatherForecastSample],
+    ) -> PacketSessionData {
+        let mut packet: PacketSessionData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        packet.header.session_time = session_time;
+        packet.session_type = 1;
+        packet.num_weather_forecast_samples = samples.len() as u8;
+        packet.weather_forecast_samples[..samples.len()].copy_from_slice(samples);
+        packet
+    }
+
+    #[test]
+    fn repeated_samples_keep_one_revision() {
+        let mut timeline = WeatherTimeline::new();
+        let samples = [sample(1, 0, 0, 0), sample(1, 5, 1, 10), sample(2, 0, 2, 20)];
+        for time in [1.0, 2.0, 3.0] {
+            timeline.push_session(&session(1, time, &samples));
+        }
+
+        assert_eq!(timeline.session_type(), Some(SessionType::Practice1));
+        assert_eq!(
+            timeline.session_types(),
+            [SessionType::Practice1, SessionType::Practice2]
+        );
+        assert_eq!(timeline.forecasts(SessionType::Practice1).len(), 2);
+        let revisions = timeline.revisions(SessionType::Practice1, 5);
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].session_time, 1.0);
+        assert_eq!(
+            timeline.drift(SessionType::Practice1, 5).unwrap().revisions,
+            0
+        );
+    }
+
+    #[test]
+    fn session_types_keep_the_order_they_were_sent_in() {
+        let mut timeline = WeatherTimeline::new();
+        let samples = [sample(5, 0, 0, 0), sample(1, 0, 0, 0), sample(5, 5, 0, 0)];
+        timeline.push_session(&session(1, 1.0, &samples));
+
+        assert_eq!(
+            timeline.session_types(),
+            [SessionType::Qualifying1, SessionType::Practice1]
+        );
+    }
+
+    #[test]
+    fn changed_forecast_adds_revision_and_drift() {
+        let mut timeline = WeatherTimeline::new();
+        timeline.push_session(&session(1, 1.0, &[sample(1, 10, 1, 10)]));
+        timeline.push_session(&session(1, 2.0, &[sample(1, 10, 3, 60)]));
+        timeline.push_session(&session(1, 3.0, &[sample(1, 10, 3, 60)]));
+
+        let forecast = timeline.forecast(SessionType::Practice1, 10).unwrap();
+        assert_eq!(forecast.weather, Weather::LightRain);
+        let drift = timeline.drift(SessionType::Practice1, 10).unwrap();
+        assert_eq!(drift.revisions, 1);
+        assert_eq!(drift.rain_percentage(), 50);
+        assert_eq!(drift.track_temperature(), 0);
+        assert!(timeline.drift(SessionType::Practice1, 15).is_none());
+    }
+
+    #[test]
+    fn rain_and_weather_interpolate_between_offsets() {
+        let mut timeline = WeatherTimeline::new();
+        let samples = [sample(1, 0, 0, 0), sample(1, 10, 3, 40)];
+        timeline.push_session(&session(1, 1.0, &samples));
+
+        assert_eq!(timeline.rain_percentage_in(0.0), Some(0.0));
+        assert_eq!(timeline.rain_percentage_in(5.0), Some(20.0));
+        assert_eq!(timeline.rain_percentage_in(11.0), None);
+        assert_eq!(timeline.weather_in(9.0), Some(Weather::Clear));
+        assert_eq!(timeline.weather_in(10.0), Some(Weather::LightRain));
+    }
+
+    #[test]
+    fn unknown_ids_are_skipped_and_new_session_resets() {
+        let mut timeline = WeatherTimeline::new();
+        timeline.push_session(&session(1, 1.0, &[sample(1, 0, 0, 0), sample(1, 5, 99, 0)]));
+        assert_eq!(timeline.forecasts(SessionType::Practice1).len(), 1);
+
+        timeline.push_session(&session(2, 1.0, &[]));
+        assert!(timeline.session_types().is_empty());
+        assert!(timeline.forecast(SessionType::Practice1, 0).is_none());
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{constants::SessionType, raw::PacketSessionData};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WeekendFormat {
+    Standard,
+    /// Has a sprint shootout, or a sprint race ahead of the main race
+    Sprint,
+}
+
+/// One session of the weekend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlannedSession {
+    /// Place in the weekend, from 0
+    pub index: usize,
+    /// `Unknown` for ids from a newer game version
+    pub session_type: SessionType,
+    /// A race with another race after it
+    pub sprint: bool,
+}
+
+impl PlannedSession {
+    /// Short label, e.g. to name recordings
+    pub fn label(&self) -> &'static str {
+        use SessionType::*;
+
+        match self.session_type {
+            Race | Race2 | Race3 if self.sprint => "Sprint",
+            Unknown => "Unknown",
+            Practice1 => "FP1",
+            Practice2 => "FP2",
+            Practice3 => "FP3",
+            ShortPractice => "Practice",
+            Qualifying1 => "Q1",
+            Qualifying2 => "Q2",
+            Qualifying3 => "Q3",
+            ShortQualifying => "Qualifying",
+            OneShotQualifying => "One-Shot Qualifying",
+            SprintShootout1 => "SQ1",
+            SprintShootout2 => "SQ2",
+            SprintShootout3 => "SQ3",
+            ShortSprintShootout => "Sprint Shootout",
+            OneShotSprintShootout => "One-Shot Sprint Shootout",
+            Race => "Race",
+            Race2 => "Race 2",
+            Race3 => "Race 3",
+            TimeTrial => "Time Trial",
+        }
+    }
+}
+
+pub(crate) fn is_race(session_type: SessionType) -> bool {
+    matches!(
+        session_type,
+        SessionType::Race | SessionType::Race2 | SessionType::Race3
+    )
+}
+
+fn is_sprint_shootout(session_type: SessionType) -> bool {
+    use SessionType::*;
+
+    matches!(
+        session_type,
+        SprintShootout1
+            | SprintShootout2
+            | SprintShootout3
+            | ShortSprintShootout
+            | OneShotSprintShootout
+    )
+}
+
+/// Sequence of sessions of a weekend, decoded from `weekend_structure`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeekendPlan {
+    pub weekend_link_identifier: u32,
+    pub sessions: Vec<PlannedSession>,
+    /// Index of the session the packet is from, `None` if it isn't part of the plan
+    pub current: Option<usize>,
+}
+
+impl WeekendPlan {
+    pub fn from_session(packet: &PacketSessionData) -> Self {
+        let structure = packet.weekend_structure;
+        let count = (packet.num_sessions_in_weekend as usize).min(structure.len());
+        let types: Vec<SessionType> = structure[..count]
+            .iter()
+            .map(|&id| SessionType::try_from_id(id).unwrap_or(SessionType::Unknown))
+            .collect();
+
+        let sessions = types
+            .iter()
+            .enumerate()
+            .map(|(index, &session_type)| PlannedSession {
+                index,
+                session_type,
+                sprint: is_race(session_type) && types[index + 1..].iter().any(|&t| is_race(t)),
+            })
+            .collect();
+
+        Self {
+            weekend_link_identifier: packet.weekend_link_identifier,
+            sessions,
+            current: structure[..count]
+                .iter()
+                .position(|&id| id == packet.session_type),
+        }
+    }
+
+    pub fn format(&self) -> WeekendFormat {
+        let sprint = self
+            .sessions
+            .iter()
+            .any(|s| s.sprint || is_sprint_shootout(s.session_type));
+        match sprint {
+            true => WeekendFormat::Sprint,
+            false => WeekendFormat::Standard,
+        }
+    }
+
+    pub fn current(&self) -> Option<&PlannedSession> {
+        self.sessions.get(self.current?)
+    }
+
+    /// The current session is a sprint race
+    pub fn is_sprint_race(&self) -> bool {
+        self.current().is_some_and(|s| s.sprint)
+    }
+
+    pub fn session(&self, session_type: SessionType) -> Option<&PlannedSession> {
+        self.sessions
+            .iter()
+            .find(|s| s.session_type == session_type)
+    }
+
+    /// Planned sessions whose type isn't in `captured`
+    pub fn missing(&self, captured: &[SessionType]) -> Vec<&PlannedSession> {
+        self.sessions
+            .iter()
+            .filter(|s| !captured.contains(&s.session_type))
+            .collect()
+    }
+}
+
+/// A session of the plan seen in the packets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapturedSession {
+    pub session: PlannedSession,
+    pub session_uid: u64,
+}
+
+/// Follows the session packets of a weekend to tell which planned sessions were captured.
+///
+/// A new `weekend_link_identifier` starts a new weekend. A session restarted under a new
+/// `session_uid` replaces the earlier capture.
+#[derive(Debug, Clone, Default)]
+pub struct WeekendTracker {
+    plan: Option<WeekendPlan>,
+    captured: Vec<CapturedSession>,
+}
+
+impl WeekendTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_session(&mut self, packet: &PacketSessionData) {
+        let plan = WeekendPlan::from_session(packet);
+        if self
+            .plan
+            .as_ref()
+            .is_some_and(|p| p.weekend_link_identifier != plan.weekend_link_identifier)
+        {
+            self.captured.clear();
+        }
+
+        if let Some(&session) = plan.current() {
+            let capture = CapturedSession {
+                session,
+                session_uid: packet.header.session_uid,
+            };
+            match self
+                .captured
+                .iter_mut()
+                .find(|c| c.session.index == session.index)
+            {
+                Some(captured) => *captured = capture,
+                None => self.captured.push(capture),
+            }
+        }
+        self.plan = Some(plan);
+    }
+
+    pub fn plan(&self) -> Option<&WeekendPlan> {
+        self.plan.as_ref()
+    }
+
+    /// Captured sessions in the order they were first seen
+    pub fn captured(&self) -> &[CapturedSession] {
+        &self.captured
+    }
+
+    /// Planned sessions not seen so far
+    pub fn missing(&self) -> Vec<PlannedSession> {
+        let Some(plan) = &self.plan else {
+            return Vec::new();
+        };
+        plan.sessions
+            .iter()
+            .filter(|s| !self.captured.iter().any(|c| c.session.index == s.index))
+            .copied()
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.plan.is_some() && self.missing().is_empty()
+    }
+
+    /// Label of a recorded session, e.g. `FP1`
+    pub fn label(&self, session_uid: u64) -> Option<&'static str> {
+        self.captured
+            .iter()
+            .find(|c| c.session_uid == session_uid)
+            .map(|c| c.session.label())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(
+        session_uid: u64,
+        weekend: u32,
+        structure: &[u8],
+        session_type: u8,
+    ) -> PacketSessionData {
+        let mut packet: PacketSessionData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        packet.weekend_link_identifier = weekend;
+        packet.session_type = session_type;
+        packet.num_sessions_in_weekend = structure.len() as u8;
+        packet.weekend_structure[..structure.len()].copy_from_slice(structure);
+        packet
+    }
+
+    const STANDARD: [u8; 5] = [1, 2, 3, 9, 15];
+    const SPRINT: [u8; 5] = [1, 14, 15, 9, 16];
+
+    #[test]
+    fn plan_tells_sprint_races_apart() {
+        let standard = WeekendPlan::from_session(&session(1, 1, &STANDARD, 15));
+        assert_eq!(standard.format(), WeekendFormat::Standard);
+        assert!(!standard.is_sprint_race());
+        assert_eq!(standard.current().map(|s| s.label()), Some("Race"));
+
+        let sprint = WeekendPlan::from_session(&session(1, 1, &SPRINT, 15));
+        assert_eq!(sprint.format(), WeekendFormat::Sprint);
+        assert!(sprint.is_sprint_race());
+        assert_eq!(sprint.current().map(|s| s.label()), Some("Sprint"));
+        assert_eq!(
+            sprint.session(SessionType::Race2).map(|s| s.sprint),
+            Some(false)
+        );
+
+        let unknown = WeekendPlan::from_session(&session(1, 1, &[1, 99], 18));
+        assert_eq!(unknown.sessions[1].session_type, SessionType::Unknown);
+        assert_eq!(unknown.current, None);
+    }
+
+    #[test]
+    fn tracker_follows_captured_sessions() {
+        let mut tracker = WeekendTracker::new();
+        tracker.push_session(&session(1, 1, &STANDARD, 1));
+        tracker.push_session(&session(2, 1, &STANDARD, 2));
+        tracker.push_session(&session(3, 1, &STANDARD, 2));
+
+        assert_eq!(tracker.captured().len(), 2);
+        assert_eq!(tracker.label(3), Some("FP2"));
+        assert_eq!(tracker.label(2), None);
+        let missing: Vec<&str> = tracker.missing().iter().map(|s| s.label()).collect();
+        assert_eq!(missing, ["FP3", "One-Shot Qualifying", "Race"]);
+        assert!(!tracker.is_complete());
+
+        for (uid, session_type) in [(4, 3), (5, 9), (6, 15)] {
+            tracker.push_session(&session(uid, 1, &STANDARD, session_type));
+        }
+        assert!(tracker.is_complete());
+    }
+
+    #[test]
+    fn new_weekend_clears_captures() {
+        let mut tracker = WeekendTracker::new();
+        tracker.push_session(&session(1, 1, &STANDARD, 1));
+        tracker.push_session(&session(2, 2, &SPRINT, 14));
+
+        assert_eq!(tracker.captured().len(), 1);
+        assert_eq!(tracker.label(2), Some("One-Shot Sprint Shootout"));
+        assert_eq!(tracker.missing().len(), 4);
+    }
+}
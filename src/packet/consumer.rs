@@ -0,0 +1,9 @@
+use crate::packet::AnyRawPacket;
+
+/// Stateful consumer of the packet stream, turning packets into higher level events
+pub trait PacketConsumer {
+    type Event;
+
+    /// Feeds the next packet, returning any events it completed
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<Self::Event>;
+}
@@ -6,6 +6,7 @@ pub enum PacketError {
     InvalidData,
     InvalidHeader(String),
     BytemuckError(String),
+    Io(std::io::Error),
     Unknown(String),
 }
 
@@ -24,7 +25,14 @@ impl fmt::Display for PacketError {
             PacketError::InvalidData => write!(f, "Failed to interpret packet data"),
             PacketError::InvalidHeader(msg) => write!(f, "Invalid packet header: {}", msg),
             PacketError::BytemuckError(msg) => write!(f, "Bytemuck error: {}", msg),
+            PacketError::Io(e) => write!(f, "I/O error: {}", e),
             PacketError::Unknown(msg) => write!(f, "Packet error: {}", msg),
         }
     }
 }
+
+impl From<std::io::Error> for PacketError {
+    fn from(e: std::io::Error) -> Self {
+        PacketError::Io(e)
+    }
+}
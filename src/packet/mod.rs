@@ -1,9 +1,13 @@
+pub mod consumer;
 pub mod dispatcher;
 pub mod error;
 pub mod macros;
+pub mod recording;
 pub mod traits;
 
+pub use consumer::*;
 pub use dispatcher::*;
 pub use error::*;
 pub(crate) use macros::*;
+pub use recording::*;
 pub use traits::*;
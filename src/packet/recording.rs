@@ -0,0 +1,107 @@
+use std::io::{self, ErrorKind, Read, Write};
+
+use crate::{
+    packet::{AnyRawPacket, PacketError, RawPacket},
+    raw::{PacketHeader, constants::packet_sizes},
+};
+
+/// Writes a recording: raw UDP payloads stored back to back.
+///
+/// No framing is needed since the `packet_id` in each header determines the
+/// packet size, so a recording is simply every datagram as received.
+pub struct RecordingWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> RecordingWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Appends a datagram, which must be a single complete packet
+    pub fn write(&mut self, datagram: &[u8]) -> Result<(), PacketError> {
+        let header_len = packet_sizes::HEADER;
+        if datagram.len() < header_len {
+            return Err(PacketError::InvalidLength {
+                expected: header_len,
+                actual: datagram.len(),
+            });
+        }
+
+        let header = PacketHeader::from_bytes(&datagram[..header_len])?;
+        let expected =
+            packet_sizes::for_packet_id(header.packet_id).ok_or(PacketError::InvalidData)?;
+        if datagram.len() != expected {
+            return Err(PacketError::InvalidLength {
+                expected,
+                actual: datagram.len(),
+            });
+        }
+
+        self.writer.write_all(datagram)?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads back a recording written by [`RecordingWriter`], yielding one packet at a time.
+///
+/// Iteration stops at the end of the input or after the first error, since an unknown
+/// packet id leaves no way to find the start of the next packet.
+pub struct RecordingReader<R: Read> {
+    reader: R,
+    failed: bool,
+}
+
+impl<R: Read> RecordingReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            failed: false,
+        }
+    }
+
+    fn read_packet(&mut self) -> Result<Option<AnyRawPacket>, PacketError> {
+        let mut bytes = vec![0u8; packet_sizes::HEADER];
+        match self.reader.read_exact(&mut bytes) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+
+        let header = PacketHeader::from_bytes(&bytes)?;
+        let size = packet_sizes::for_packet_id(header.packet_id).ok_or_else(|| {
+            PacketError::InvalidHeader(format!("unknown packet id {}", header.packet_id))
+        })?;
+
+        bytes.resize(size, 0);
+        self.reader.read_exact(&mut bytes[packet_sizes::HEADER..])?;
+
+        AnyRawPacket::from_bytes(&bytes).map(Some)
+    }
+}
+
+impl<R: Read> Iterator for RecordingReader<R> {
+    type Item = Result<AnyRawPacket, PacketError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        match self.read_packet() {
+            Ok(packet) => packet.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
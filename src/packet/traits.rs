@@ -8,6 +8,7 @@ pub trait HasHeader {
 
 pub trait RawPacket: Sized {
     fn from_bytes(bytes: &[u8]) -> Result<Self, PacketError>;
+    #[allow(clippy::wrong_self_convention)]
     fn into_bytes(&self) -> &[u8];
 }
 
@@ -21,6 +22,7 @@ pub trait Packet: Sized {
         let raw = Self::Raw::from_bytes(bytes)?;
         Self::from_raw(raw)
     }
+    #[allow(clippy::wrong_self_convention)]
     fn into_bytes(&self) -> &[u8];
 }
 
@@ -38,7 +40,7 @@ where
         }
 
         bytemuck::try_from_bytes::<Self>(bytes)
-            .map(|p| *p)
+            .copied()
             .map_err(|e| PacketError::BytemuckError(e.to_string()))
     }
     fn into_bytes(&self) -> &[u8] {
@@ -2,6 +2,10 @@ use bytemuck::{Pod, Zeroable};
 
 use crate::{
     assert_packet_size,
+    constants::{
+        ActualTyreCompound, ErsDeployMode, FiaFlag, Formula, FuelMix, TractionControl,
+        VisualTyreCompound,
+    },
     packet::impl_has_header,
     raw::{
         PacketHeader,
@@ -64,6 +68,38 @@ pub struct CarStatusData {
     pub network_paused: u8,
 }
 
+impl CarStatusData {
+    pub fn traction_control(&self) -> Option<TractionControl> {
+        TractionControl::try_from_id(self.traction_control)
+    }
+
+    pub fn anti_lock_brakes(&self) -> bool {
+        self.anti_lock_brakes == 1
+    }
+
+    pub fn fuel_mix(&self) -> Option<FuelMix> {
+        FuelMix::try_from_id(self.fuel_mix)
+    }
+
+    /// `None` when the flag is invalid or unknown (-1)
+    pub fn vehicle_fia_flags(&self) -> Option<FiaFlag> {
+        FiaFlag::try_from_id(self.vehicle_fia_flags as u8)
+    }
+
+    pub fn ers_deploy_mode(&self) -> Option<ErsDeployMode> {
+        ErsDeployMode::try_from_id(self.ers_deploy_mode)
+    }
+
+    /// The compound codes depend on the session's `formula`
+    pub fn actual_tyre_compound(&self, formula: Formula) -> Option<ActualTyreCompound> {
+        ActualTyreCompound::try_from_id(formula, self.actual_tyre_compound)
+    }
+
+    pub fn visual_tyre_compound(&self, formula: Formula) -> Option<VisualTyreCompound> {
+        VisualTyreCompound::try_from_id(formula, self.visual_tyre_compound)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct PacketCarStatusData {
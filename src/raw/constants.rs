@@ -1,5 +1,7 @@
 pub const MAX_NUM_CARS: usize = 22;
 pub const MAX_PARTICIPANT_NAME_LEN: usize = 32;
+/// `driver_id` of human players in online sessions
+pub const NETWORK_HUMAN_DRIVER_ID: u8 = 255;
 pub const MAX_TYRE_STINTS: usize = 8;
 /// 13 slick and 7 wet weather
 pub const MAX_TYRE_SETS: usize = 13 + 7;
@@ -31,6 +33,30 @@ pub mod packet_sizes {
     pub const MOTION_EX: usize = 273;
     pub const TIME_TRIAL: usize = 101;
     pub const LAP_POSITIONS: usize = 1131;
+
+    /// Size of the packet with the given header `packet_id`, `None` for unknown ids
+    pub fn for_packet_id(packet_id: u8) -> Option<usize> {
+        let size = match packet_id {
+            0 => MOTION,
+            1 => SESSION,
+            2 => LAP,
+            3 => EVENT,
+            4 => PARTICIPANTS,
+            5 => CAR_SETUPS,
+            6 => CAR_TELEMETRY,
+            7 => CAR_STATUS,
+            8 => FINAL_CLASSIFICATION,
+            9 => LOBBY_INFO,
+            10 => CAR_DAMAGE,
+            11 => SESSION_HISTORY,
+            12 => TYRE_SETS,
+            13 => MOTION_EX,
+            14 => TIME_TRIAL,
+            15 => LAP_POSITIONS,
+            _ => return None,
+        };
+        Some(size)
+    }
 }
 
 pub mod event {
@@ -4,10 +4,11 @@ use bytemuck::{Pod, Zeroable};
 
 use crate::{
     assert_packet_size,
+    constants::{ButtonFlags, DrsDisabledReason, ResultReason, SafetyCarEventType, SafetyCarType},
     packet::{PacketError, RawPacket, impl_has_header},
     raw::{
         PacketHeader,
-        constants::{event::EVENT_STRING_CODE_LEN, packet_sizes},
+        constants::{event::*, packet_sizes},
     },
 };
 
@@ -49,6 +50,12 @@ pub struct Retirement {
     pub reason: u8,
 }
 
+impl Retirement {
+    pub fn reason(&self) -> Option<ResultReason> {
+        ResultReason::try_from_id(self.reason)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct DRSDisabled {
@@ -56,6 +63,12 @@ pub struct DRSDisabled {
     pub reason: u8,
 }
 
+impl DRSDisabled {
+    pub fn reason(&self) -> Option<DrsDisabledReason> {
+        DrsDisabledReason::try_from_id(self.reason)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct TeamMateInPits {
@@ -145,6 +158,12 @@ pub struct Buttons {
     pub button_status: u32,
 }
 
+impl Buttons {
+    pub fn button_status(&self) -> ButtonFlags {
+        ButtonFlags::from_bits_retain(self.button_status)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Overtake {
@@ -163,6 +182,16 @@ pub struct SafetyCar {
     pub event_type: u8,
 }
 
+impl SafetyCar {
+    pub fn safety_car_type(&self) -> Option<SafetyCarType> {
+        SafetyCarType::try_from_id(self.safety_car_type)
+    }
+
+    pub fn event_type(&self) -> Option<SafetyCarEventType> {
+        SafetyCarEventType::try_from_id(self.event_type)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Collision {
@@ -182,6 +211,102 @@ pub struct PacketEventData {
 
 impl_has_header!(PacketEventData);
 
+/// Typed view of an event packet, pairing the string code with its union member
+#[derive(Clone, Copy, Debug)]
+pub enum Event {
+    SessionStarted,
+    SessionEnded,
+    FastestLap(FastestLap),
+    Retirement(Retirement),
+    DrsEnabled,
+    DrsDisabled(DRSDisabled),
+    TeamMateInPits(TeamMateInPits),
+    ChequeredFlag,
+    RaceWinner(RaceWinner),
+    Penalty(Penalty),
+    SpeedTrap(SpeedTrap),
+    StartLights(StartLights),
+    LightsOut,
+    DriveThroughServed(DriveThroughPenaltyServed),
+    StopGoServed(StopGoPenaltyServed),
+    Flashback(Flashback),
+    Buttons(Buttons),
+    RedFlag,
+    Overtake(Overtake),
+    SafetyCar(SafetyCar),
+    Collision(Collision),
+}
+
+impl PacketEventData {
+    /// Event string code, e.g. "PENA"
+    pub fn code(&self) -> &str {
+        std::str::from_utf8(&self.event_string_code).unwrap_or_default()
+    }
+
+    /// Decodes the event details according to the string code, `None` if the code is unknown
+    pub fn event(&self) -> Option<Event> {
+        let details = self.event_details;
+
+        // SAFETY: every union member is plain old data, and the string code tells us
+        // which member the game populated.
+        let event = unsafe {
+            match &self.event_string_code {
+                SESSION_STARTED_EVENT_CODE => Event::SessionStarted,
+                SESSION_ENDED_EVENT_CODE => Event::SessionEnded,
+                FASTEST_LAP_EVENT_CODE => Event::FastestLap(details.fastest_lap),
+                RETIREMENT_EVENT_CODE => Event::Retirement(details.retirement),
+                DRS_ENABLED_EVENT_CODE => Event::DrsEnabled,
+                DRS_DISABLED_EVENT_CODE => Event::DrsDisabled(details.drs_disabled),
+                TEAM_MATE_IN_PITS_EVENT_CODE => Event::TeamMateInPits(details.team_mate_in_pits),
+                CHEQUERED_FLAG_EVENT_CODE => Event::ChequeredFlag,
+                RACE_WINNER_EVENT_CODE => Event::RaceWinner(details.race_winner),
+                PENALTY_EVENT_CODE => Event::Penalty(details.penalty),
+                SPEED_TRAP_EVENT_CODE => Event::SpeedTrap(details.speed_trap),
+                START_LIGHTS_EVENT_CODE => Event::StartLights(details.start_lights),
+                LIGHTS_OUT_EVENT_CODE => Event::LightsOut,
+                DRIVE_THROUGH_SERVED_EVENT_CODE => {
+                    Event::DriveThroughServed(details.drive_through_penalty_served)
+                }
+                STOP_GO_SERVED_EVENT_CODE => Event::StopGoServed(details.stop_go_penalty_served),
+                FLASHBACK_EVENT_CODE => Event::Flashback(details.flashback),
+                BUTTON_STATUS_EVENT_CODE => Event::Buttons(details.buttons),
+                RED_FLAG_EVENT_CODE => Event::RedFlag,
+                OVERTAKE_EVENT_CODE => Event::Overtake(details.overtake),
+                SAFETY_CAR_EVENT_CODE => Event::SafetyCar(details.safety_car),
+                COLLISION_EVENT_CODE => Event::Collision(details.collision),
+                _ => return None,
+            }
+        };
+
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+impl PacketEventData {
+    /// Event packet with `details` as the leading bytes of the event details
+    pub(crate) fn for_test(
+        session_uid: u64,
+        session_time: f32,
+        code: &[u8; EVENT_STRING_CODE_LEN],
+        details: &[u8],
+    ) -> Self {
+        let header = PacketHeader {
+            session_uid,
+            session_time,
+            ..Zeroable::zeroed()
+        };
+        let header_size = std::mem::size_of::<PacketHeader>();
+        let details_start = header_size + EVENT_STRING_CODE_LEN;
+
+        let mut bytes = vec![0; std::mem::size_of::<Self>()];
+        bytes[..header_size].copy_from_slice(bytemuck::bytes_of(&header));
+        bytes[header_size..details_start].copy_from_slice(code);
+        bytes[details_start..details_start + details.len()].copy_from_slice(details);
+        Self::from_bytes(&bytes).unwrap()
+    }
+}
+
 impl RawPacket for PacketEventData {
     fn from_bytes(bytes: &[u8]) -> Result<Self, PacketError> {
         let expected_len = std::mem::size_of::<PacketEventData>();
@@ -2,6 +2,7 @@ use bytemuck::{Pod, Zeroable};
 
 use crate::{
     assert_packet_size,
+    constants::{ActualTyreCompound, Formula, ResultReason, ResultStatus, VisualTyreCompound},
     packet::impl_has_header,
     raw::{
         PacketHeader,
@@ -45,6 +46,36 @@ pub struct FinalClassificationData {
     pub tyre_stints_end_laps: [u8; MAX_TYRE_STINTS],
 }
 
+impl FinalClassificationData {
+    pub fn result_status(&self) -> Option<ResultStatus> {
+        ResultStatus::try_from_id(self.result_status)
+    }
+
+    pub fn result_reason(&self) -> Option<ResultReason> {
+        ResultReason::try_from_id(self.result_reason)
+    }
+
+    /// Actual compound of each stint, the codes depend on the session's `formula`
+    pub fn tyre_stints_actual(&self, formula: Formula) -> Vec<Option<ActualTyreCompound>> {
+        let stints = self.tyre_stints_actual;
+        let count = (self.num_tyre_stints as usize).min(stints.len());
+        stints[..count]
+            .iter()
+            .map(|&id| ActualTyreCompound::try_from_id(formula, id))
+            .collect()
+    }
+
+    /// Visual compound of each stint, the codes depend on the session's `formula`
+    pub fn tyre_stints_visual(&self, formula: Formula) -> Vec<Option<VisualTyreCompound>> {
+        let stints = self.tyre_stints_visual;
+        let count = (self.num_tyre_stints as usize).min(stints.len());
+        stints[..count]
+            .iter()
+            .map(|&id| VisualTyreCompound::try_from_id(formula, id))
+            .collect()
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct PacketFinalClassificationData {
@@ -33,7 +33,7 @@ impl RawPacket for PacketHeader {
             });
         }
         bytemuck::try_from_bytes(bytes)
-            .map(|p| *p)
+            .copied()
             .map_err(|_| PacketError::InvalidData)
     }
 
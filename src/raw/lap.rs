@@ -1,12 +1,16 @@
+use std::time::Duration;
+
 use bytemuck::{Pod, Zeroable};
 
 use crate::{
     assert_packet_size,
+    constants::{DriverStatus, PitStatus, ResultStatus},
     packet::impl_has_header,
     raw::{
         PacketHeader,
         constants::{MAX_NUM_CARS, packet_sizes},
     },
+    timing::LapTime,
 };
 
 #[repr(C, packed)]
@@ -81,6 +85,54 @@ pub struct LapData {
     pub speed_trap_fastest_lap: u8,
 }
 
+impl LapData {
+    pub fn last_lap_time(&self) -> Duration {
+        LapTime::from_millis(self.last_lap_time_in_ms).as_duration()
+    }
+
+    pub fn current_lap_time(&self) -> Duration {
+        LapTime::from_millis(self.current_lap_time_in_ms).as_duration()
+    }
+
+    /// Zero until sector 1 of the current lap is complete
+    pub fn sector1_time(&self) -> Duration {
+        LapTime::from_parts(self.sector1_time_minutes_part, self.sector1_time_ms_part).as_duration()
+    }
+
+    /// Zero until sector 2 of the current lap is complete
+    pub fn sector2_time(&self) -> Duration {
+        LapTime::from_parts(self.sector2_time_minutes_part, self.sector2_time_ms_part).as_duration()
+    }
+
+    pub fn delta_to_car_in_front(&self) -> Duration {
+        LapTime::from_parts(
+            self.delta_to_car_in_front_minutes_part,
+            self.delta_to_car_in_front_ms_part,
+        )
+        .as_duration()
+    }
+
+    pub fn delta_to_race_leader(&self) -> Duration {
+        LapTime::from_parts(
+            self.delta_to_race_leader_minutes_part,
+            self.delta_to_race_leader_ms_part,
+        )
+        .as_duration()
+    }
+
+    pub fn pit_status(&self) -> Option<PitStatus> {
+        PitStatus::try_from_id(self.pit_status)
+    }
+
+    pub fn driver_status(&self) -> Option<DriverStatus> {
+        DriverStatus::try_from_id(self.driver_status)
+    }
+
+    pub fn result_status(&self) -> Option<ResultStatus> {
+        ResultStatus::try_from_id(self.result_status)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct PacketLapData {
@@ -2,11 +2,13 @@ use bytemuck::{Pod, Zeroable};
 
 use crate::{
     assert_packet_size,
+    constants::{Platform, ReadyStatus},
     packet::impl_has_header,
     raw::{
         PacketHeader,
         constants::{MAX_NUM_CARS, MAX_PARTICIPANT_NAME_LEN, packet_sizes},
     },
+    utils::null_terminated_str,
 };
 
 #[repr(C, packed)]
@@ -35,6 +37,21 @@ pub struct LobbyInfoData {
     pub ready_status: u8,
 }
 
+impl LobbyInfoData {
+    /// Player name decoded from the null terminated UTF-8 buffer
+    pub fn name_str(&self) -> &str {
+        null_terminated_str(&self.name)
+    }
+
+    pub fn platform(&self) -> Option<Platform> {
+        Platform::try_from_id(self.platform)
+    }
+
+    pub fn ready_status(&self) -> Option<ReadyStatus> {
+        ReadyStatus::try_from_id(self.ready_status)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct PacketLobbyInfoData {
@@ -35,8 +35,8 @@ pub struct CarMotionData {
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct PacketMotionData {
-    header: PacketHeader, // Header
-    car_motion_data: [CarMotionData; MAX_NUM_CARS],
+    pub header: PacketHeader, // Header
+    pub car_motion_data: [CarMotionData; MAX_NUM_CARS],
 }
 
 impl_has_header!(PacketMotionData);
@@ -1,10 +1,12 @@
 use crate::{
     assert_packet_size,
+    constants::Platform,
     packet::impl_has_header,
     raw::{
         PacketHeader,
         constants::{MAX_NUM_CARS, MAX_PARTICIPANT_NAME_LEN, packet_sizes},
     },
+    utils::null_terminated_str,
 };
 use bytemuck::{Pod, Zeroable};
 
@@ -51,6 +53,17 @@ pub struct ParticipantData {
     pub livery_colours: [LiveryColour; 4],
 }
 
+impl ParticipantData {
+    /// Participant name decoded from the null terminated UTF-8 buffer
+    pub fn name_str(&self) -> &str {
+        null_terminated_str(&self.name)
+    }
+
+    pub fn platform(&self) -> Option<Platform> {
+        Platform::try_from_id(self.platform)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct PacketParticipantsData {
@@ -2,6 +2,13 @@ use bytemuck::{Pod, Zeroable};
 
 use crate::{
     assert_packet_size,
+    constants::{
+        BrakingAssist, CarDamage, CarDamageRate, Collisions, CornerCuttingStringency,
+        DynamicRacingLine, DynamicRacingLineType, EventFrequency, Experience, FiaFlag,
+        FlashbackLimit, ForecastAccuracy, Formula, GearboxAssist, LowFuelMode, PitStopExperience,
+        RaceStarts, RecoveryMode, SafetyCarType, SessionLength, SpeedUnit, SurfaceSimulation,
+        TemperatureUnit, TyreTemperatureSimulation,
+    },
     packet::impl_has_header,
     raw::{
         PacketHeader,
@@ -21,6 +28,13 @@ pub struct MarshalZone {
     pub zone_flag: i8,
 }
 
+impl MarshalZone {
+    /// `None` when the flag is invalid or unknown (-1)
+    pub fn zone_flag(&self) -> Option<FiaFlag> {
+        FiaFlag::try_from_id(self.zone_flag as u8)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct WeatherForecastSample {
@@ -204,6 +218,208 @@ pub struct PacketSessionData {
     pub sector3_lap_distance_start: f32,
 }
 
+impl PacketSessionData {
+    pub fn formula(&self) -> Option<Formula> {
+        Formula::try_from_id(self.formula)
+    }
+
+    pub fn safety_car_status(&self) -> Option<SafetyCarType> {
+        SafetyCarType::try_from_id(self.safety_car_status)
+    }
+
+    pub fn forecast_accuracy(&self) -> Option<ForecastAccuracy> {
+        ForecastAccuracy::try_from_id(self.forecast_accuracy)
+    }
+
+    pub fn steering_assist(&self) -> bool {
+        self.steering_assist == 1
+    }
+
+    pub fn braking_assist(&self) -> Option<BrakingAssist> {
+        BrakingAssist::try_from_id(self.braking_assist)
+    }
+
+    pub fn gearbox_assist(&self) -> Option<GearboxAssist> {
+        GearboxAssist::try_from_id(self.gearbox_assist)
+    }
+
+    pub fn pit_assist(&self) -> bool {
+        self.pit_assist == 1
+    }
+
+    pub fn pit_release_assist(&self) -> bool {
+        self.pit_release_assist == 1
+    }
+
+    pub fn ers_assist(&self) -> bool {
+        self.ers_assist == 1
+    }
+
+    pub fn drs_assist(&self) -> bool {
+        self.drs_assist == 1
+    }
+
+    pub fn dynamic_racing_line(&self) -> Option<DynamicRacingLine> {
+        DynamicRacingLine::try_from_id(self.dynamic_racing_line)
+    }
+
+    pub fn dynamic_racing_line_type(&self) -> Option<DynamicRacingLineType> {
+        DynamicRacingLineType::try_from_id(self.dynamic_racing_line_type)
+    }
+
+    pub fn session_length(&self) -> Option<SessionLength> {
+        SessionLength::try_from_id(self.session_length)
+    }
+
+    pub fn speed_units_lead_player(&self) -> Option<SpeedUnit> {
+        SpeedUnit::try_from_id(self.speed_units_lead_player)
+    }
+
+    pub fn temperature_units_lead_player(&self) -> Option<TemperatureUnit> {
+        TemperatureUnit::try_from_id(self.temperature_units_lead_player)
+    }
+
+    pub fn speed_units_secondary_player(&self) -> Option<SpeedUnit> {
+        SpeedUnit::try_from_id(self.speed_units_secondary_player)
+    }
+
+    pub fn temperature_units_secondary_player(&self) -> Option<TemperatureUnit> {
+        TemperatureUnit::try_from_id(self.temperature_units_secondary_player)
+    }
+
+    pub fn equal_car_performance(&self) -> bool {
+        self.equal_car_performance == 1
+    }
+
+    pub fn recovery_mode(&self) -> Option<RecoveryMode> {
+        RecoveryMode::try_from_id(self.recovery_mode)
+    }
+
+    pub fn flashback_limit(&self) -> Option<FlashbackLimit> {
+        FlashbackLimit::try_from_id(self.flashback_limit)
+    }
+
+    pub fn surface_type(&self) -> Option<SurfaceSimulation> {
+        SurfaceSimulation::try_from_id(self.surface_type)
+    }
+
+    pub fn low_fuel_mode(&self) -> Option<LowFuelMode> {
+        LowFuelMode::try_from_id(self.low_fuel_mode)
+    }
+
+    pub fn race_starts(&self) -> Option<RaceStarts> {
+        RaceStarts::try_from_id(self.race_starts)
+    }
+
+    pub fn tyre_temperature(&self) -> Option<TyreTemperatureSimulation> {
+        TyreTemperatureSimulation::try_from_id(self.tyre_temperature)
+    }
+
+    /// Encoded inverted, 0 = on
+    pub fn pit_lane_tyre_sim(&self) -> bool {
+        self.pit_lane_tyre_sim == 0
+    }
+
+    pub fn car_damage(&self) -> Option<CarDamage> {
+        CarDamage::try_from_id(self.car_damage)
+    }
+
+    pub fn car_damage_rate(&self) -> Option<CarDamageRate> {
+        CarDamageRate::try_from_id(self.car_damage_rate)
+    }
+
+    pub fn collisions(&self) -> Option<Collisions> {
+        Collisions::try_from_id(self.collisions)
+    }
+
+    pub fn collisions_off_for_first_lap_only(&self) -> bool {
+        self.collisions_off_for_first_lap_only == 1
+    }
+
+    /// Encoded inverted, 0 = on
+    pub fn mp_unsafe_pit_release(&self) -> bool {
+        self.mp_unsafe_pit_release == 0
+    }
+
+    pub fn mp_off_for_griefing(&self) -> bool {
+        self.mp_off_for_griefing == 1
+    }
+
+    pub fn corner_cutting_stringency(&self) -> Option<CornerCuttingStringency> {
+        CornerCuttingStringency::try_from_id(self.corner_cutting_stringency)
+    }
+
+    pub fn parc_ferme_rules(&self) -> bool {
+        self.parc_ferme_rules == 1
+    }
+
+    pub fn pit_stop_experience(&self) -> Option<PitStopExperience> {
+        PitStopExperience::try_from_id(self.pit_stop_experience)
+    }
+
+    pub fn safety_car(&self) -> Option<EventFrequency> {
+        EventFrequency::try_from_id(self.safety_car)
+    }
+
+    pub fn safety_car_experience(&self) -> Option<Experience> {
+        Experience::try_from_id(self.safety_car_experience)
+    }
+
+    pub fn formation_lap(&self) -> bool {
+        self.formation_lap == 1
+    }
+
+    pub fn formation_lap_experience(&self) -> Option<Experience> {
+        Experience::try_from_id(self.formation_lap_experience)
+    }
+
+    pub fn red_flags(&self) -> Option<EventFrequency> {
+        EventFrequency::try_from_id(self.red_flags)
+    }
+
+    pub fn affects_licence_level_solo(&self) -> bool {
+        self.affects_licence_level_solo == 1
+    }
+
+    pub fn affects_licence_level_mp(&self) -> bool {
+        self.affects_licence_level_mp == 1
+    }
+}
+
 impl_has_header!(PacketSessionData);
 
 assert_packet_size!(PacketSessionData, packet_sizes::SESSION);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accessors_map_ids_and_reject_unknown_ones() {
+        let mut packet: PacketSessionData = Zeroable::zeroed();
+        packet.safety_car_status = 2;
+        packet.corner_cutting_stringency = 7;
+        packet.marshal_zones[0].zone_flag = -1;
+        packet.marshal_zones[1].zone_flag = 3;
+
+        assert_eq!(packet.safety_car_status(), Some(SafetyCarType::Virtual));
+        assert_eq!(packet.corner_cutting_stringency(), None);
+        assert_eq!(packet.marshal_zones[0].zone_flag(), None);
+        assert_eq!(packet.marshal_zones[1].zone_flag(), Some(FiaFlag::Yellow));
+    }
+
+    #[test]
+    fn inverted_flags_read_zero_as_on() {
+        let mut packet: PacketSessionData = Zeroable::zeroed();
+        assert!(packet.pit_lane_tyre_sim());
+        assert!(packet.mp_unsafe_pit_release());
+        assert!(!packet.steering_assist());
+
+        packet.pit_lane_tyre_sim = 1;
+        packet.mp_unsafe_pit_release = 1;
+        packet.steering_assist = 1;
+        assert!(!packet.pit_lane_tyre_sim());
+        assert!(!packet.mp_unsafe_pit_release());
+        assert!(packet.steering_assist());
+    }
+}
@@ -1,12 +1,16 @@
+use std::time::Duration;
+
 use bytemuck::{Pod, Zeroable};
 
 use crate::{
     assert_packet_size,
+    constants::{ActualTyreCompound, Formula, LapValidFlags, VisualTyreCompound},
     packet::impl_has_header,
     raw::{
         PacketHeader,
         constants::{MAX_NUM_LAPS_IN_SESSION_HISTORY, MAX_TYRE_STINTS, packet_sizes},
     },
+    timing::LapTime,
 };
 
 #[repr(C, packed)]
@@ -31,6 +35,36 @@ pub struct LapHistoryData {
     pub lap_valid_bit_flags: u8,
 }
 
+impl LapHistoryData {
+    pub fn lap_time(&self) -> Duration {
+        LapTime::from_millis(self.lap_time_in_ms).as_duration()
+    }
+
+    pub fn sector1_time(&self) -> Duration {
+        LapTime::from_parts(self.sector1_time_minutes_part, self.sector1_time_ms_part).as_duration()
+    }
+
+    pub fn sector2_time(&self) -> Duration {
+        LapTime::from_parts(self.sector2_time_minutes_part, self.sector2_time_ms_part).as_duration()
+    }
+
+    /// Remainder of the lap time after sectors 1 and 2, falling back to the
+    /// sector 3 fields while the lap has no time yet
+    pub fn sector3_time(&self) -> Duration {
+        if self.lap_time_in_ms == 0 {
+            return LapTime::from_parts(self.sector3_time_minutes_part, self.sector3_time_ms_part)
+                .as_duration();
+        }
+        self.lap_time()
+            .saturating_sub(self.sector1_time())
+            .saturating_sub(self.sector2_time())
+    }
+
+    pub fn validity(&self) -> LapValidFlags {
+        LapValidFlags::from_bits_truncate(self.lap_valid_bit_flags)
+    }
+}
+
 //-----------------------------------------------------------------------------
 // Tyre stint history data
 //-----------------------------------------------------------------------------
@@ -45,6 +79,17 @@ pub struct TyreStintHistoryData {
     pub tyre_visual_compound: u8,
 }
 
+impl TyreStintHistoryData {
+    /// The compound codes depend on the session's `formula`
+    pub fn tyre_actual_compound(&self, formula: Formula) -> Option<ActualTyreCompound> {
+        ActualTyreCompound::try_from_id(formula, self.tyre_actual_compound)
+    }
+
+    pub fn tyre_visual_compound(&self, formula: Formula) -> Option<VisualTyreCompound> {
+        VisualTyreCompound::try_from_id(formula, self.tyre_visual_compound)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct PacketSessionHistoryData {
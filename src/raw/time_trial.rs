@@ -35,6 +35,32 @@ pub struct TimeTrialDataSet {
     pub valid: u8,
 }
 
+impl TimeTrialDataSet {
+    pub fn traction_control(&self) -> bool {
+        self.traction_control == 1
+    }
+
+    pub fn gearbox_assist(&self) -> bool {
+        self.gearbox_assist == 1
+    }
+
+    pub fn anti_lock_brakes(&self) -> bool {
+        self.anti_lock_brakes == 1
+    }
+
+    pub fn equal_car_performance(&self) -> bool {
+        self.equal_car_performance == 1
+    }
+
+    pub fn custom_setup(&self) -> bool {
+        self.custom_setup == 1
+    }
+
+    pub fn valid(&self) -> bool {
+        self.valid == 1
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct PacketTimeTrialData {
@@ -2,6 +2,7 @@ use bytemuck::{Pod, Zeroable};
 
 use crate::{
     assert_packet_size,
+    constants::{ActualTyreCompound, Formula, VisualTyreCompound},
     packet::impl_has_header,
     raw::{
         PacketHeader,
@@ -32,6 +33,17 @@ pub struct TyreSetData {
     pub fitted: u8,
 }
 
+impl TyreSetData {
+    /// The compound codes depend on the session's `formula`
+    pub fn actual_tyre_compound(&self, formula: Formula) -> Option<ActualTyreCompound> {
+        ActualTyreCompound::try_from_id(formula, self.actual_tyre_compound)
+    }
+
+    pub fn visual_tyre_compound(&self, formula: Formula) -> Option<VisualTyreCompound> {
+        VisualTyreCompound::try_from_id(formula, self.visual_tyre_compound)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct PacketTyreSetsData {
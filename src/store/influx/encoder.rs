@@ -0,0 +1,488 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    constants::TrackId,
+    packet::AnyRawPacket,
+    raw::{constants::MAX_NUM_CARS, *},
+    store::influx::Line,
+};
+
+/// Adds one field per wheel, suffixed in `WheelArray` order (RL, RR, FL, FR)
+macro_rules! wheel_fields {
+    ($line:expr, $name:literal, $values:expr) => {{
+        let values = $values;
+        $line
+            .field(concat!($name, "_rl"), values[0])
+            .field(concat!($name, "_rr"), values[1])
+            .field(concat!($name, "_fl"), values[2])
+            .field(concat!($name, "_fr"), values[3])
+    }};
+}
+
+/// Turns decoded packets into InfluxDB line protocol points.
+///
+/// Every packet type gets its own measurement (`lap`, `car_telemetry`, ...), with one
+/// point per active car for the per-car packets. Points are tagged with `session_uid`,
+/// `track`, `car_idx` and `driver`; the last two are only known once a session and
+/// participants packet have been seen, and are omitted until then.
+///
+/// Timestamps are `session_time` offset from the wall clock time at which the session
+/// started, anchored when the first packet of a session is encoded. After a flashback
+/// the session time goes backwards, so the rewound points overwrite the earlier ones.
+///
+/// Participants, car setups, final classification, lobby info, session history, tyre
+/// sets, time trial and lap positions packets only update the tags or are skipped.
+#[derive(Debug, Clone)]
+pub struct LineEncoder {
+    session_uid: Option<u64>,
+    /// Wall clock at `session_time == 0`, in nanoseconds since the unix epoch
+    anchor_ns: u64,
+    track: Option<TrackId>,
+    names: [String; MAX_NUM_CARS],
+    num_active_cars: usize,
+}
+
+impl Default for LineEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineEncoder {
+    pub fn new() -> Self {
+        Self {
+            session_uid: None,
+            anchor_ns: 0,
+            track: None,
+            names: Default::default(),
+            num_active_cars: MAX_NUM_CARS,
+        }
+    }
+
+    pub fn encode(&mut self, packet: &AnyRawPacket) -> Vec<Line> {
+        self.encode_at(packet, SystemTime::now())
+    }
+
+    /// Same as [`LineEncoder::encode`], with `now` used to anchor new sessions
+    pub fn encode_at(&mut self, packet: &AnyRawPacket, now: SystemTime) -> Vec<Line> {
+        let header = *packet.header();
+        if !self.anchor(&header, now) {
+            return Vec::new();
+        }
+
+        let Some(timestamp) = Duration::try_from_secs_f32(header.session_time.max(0.0))
+            .ok()
+            .and_then(|offset| u64::try_from(offset.as_nanos()).ok())
+            .and_then(|offset| self.anchor_ns.checked_add(offset))
+        else {
+            return Vec::new();
+        };
+
+        match packet {
+            AnyRawPacket::Motion(p) => self.encode_motion(p, timestamp),
+            AnyRawPacket::Session(p) => {
+                self.track = u8::try_from(p.track_id).ok().and_then(TrackId::try_from_id);
+                vec![self.encode_session(p, timestamp)]
+            }
+            AnyRawPacket::Lap(p) => self.encode_lap(p, timestamp),
+            AnyRawPacket::Event(p) => self.encode_event(p, timestamp).into_iter().collect(),
+            AnyRawPacket::Participants(p) => {
+                self.num_active_cars = (p.num_active_cars as usize).min(MAX_NUM_CARS);
+                for (name, participant) in self.names.iter_mut().zip(p.participants.iter()) {
+                    *name = participant.name_str().to_owned();
+                }
+                Vec::new()
+            }
+            AnyRawPacket::CarTelemetry(p) => self.encode_car_telemetry(p, timestamp),
+            AnyRawPacket::CarStatus(p) => self.encode_car_status(p, timestamp),
+            AnyRawPacket::CarDamage(p) => self.encode_car_damage(p, timestamp),
+            AnyRawPacket::MotionEx(p) => vec![self.encode_motion_ex(p, timestamp)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns whether the packet's session is anchored to the wall clock.
+    fn anchor(&mut self, header: &PacketHeader, now: SystemTime) -> bool {
+        let session_uid = header.session_uid;
+        if self.session_uid == Some(session_uid) {
+            return true;
+        }
+
+        self.session_uid = None;
+        self.track = None;
+        self.names = Default::default();
+        self.num_active_cars = MAX_NUM_CARS;
+
+        // Anchored by the next packet instead
+        let Ok(session_time) = Duration::try_from_secs_f32(header.session_time.max(0.0)) else {
+            return false;
+        };
+        let start = now.checked_sub(session_time).unwrap_or(now);
+
+        self.session_uid = Some(session_uid);
+        self.anchor_ns = start
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|d| u64::try_from(d.as_nanos()).ok())
+            .unwrap_or_default();
+        true
+    }
+
+    fn session_line(&self, measurement: &'static str, timestamp: u64) -> Line {
+        Line::new(measurement, timestamp)
+            .tag("session_uid", self.session_uid.unwrap_or_default())
+            .tag("track", self.track.map(|t| t.name()).unwrap_or_default())
+    }
+
+    fn car_line(&self, measurement: &'static str, timestamp: u64, car_idx: usize) -> Line {
+        let line = self
+            .session_line(measurement, timestamp)
+            .tag("car_idx", car_idx);
+        match self.names.get(car_idx) {
+            Some(name) => line.tag("driver", name),
+            None => line,
+        }
+    }
+
+    fn encode_motion(&self, packet: &PacketMotionData, timestamp: u64) -> Vec<Line> {
+        let cars = packet.car_motion_data;
+        cars.iter()
+            .take(self.num_active_cars)
+            .enumerate()
+            .map(|(idx, car)| {
+                self.car_line("motion", timestamp, idx)
+                    .field("world_position_x", car.world_position_x)
+                    .field("world_position_y", car.world_position_y)
+                    .field("world_position_z", car.world_position_z)
+                    .field("world_velocity_x", car.world_velocity_x)
+                    .field("world_velocity_y", car.world_velocity_y)
+                    .field("world_velocity_z", car.world_velocity_z)
+                    .field("g_force_lateral", car.g_force_lateral)
+                    .field("g_force_longitudinal", car.g_force_longitudinal)
+                    .field("g_force_vertical", car.g_force_vertical)
+                    .field("yaw", car.yaw)
+                    .field("pitch", car.pitch)
+                    .field("roll", car.roll)
+            })
+            .collect()
+    }
+
+    fn encode_session(&self, packet: &PacketSessionData, timestamp: u64) -> Line {
+        self.session_line("session", timestamp)
+            .field("weather", packet.weather)
+            .field("track_temperature", packet.track_temperature)
+            .field("air_temperature", packet.air_temperature)
+            .field("total_laps", packet.total_laps)
+            .field("session_type", packet.session_type)
+            .field("session_time_left", packet.session_time_left)
+            .field("safety_car_status", packet.safety_car_status)
+            .field("pit_speed_limit", packet.pit_speed_limit)
+    }
+
+    fn encode_lap(&self, packet: &PacketLapData, timestamp: u64) -> Vec<Line> {
+        let cars = packet.lap_data;
+        cars.iter()
+            .take(self.num_active_cars)
+            .enumerate()
+            .map(|(idx, car)| {
+                self.car_line("lap", timestamp, idx)
+                    .field("last_lap_time_in_ms", car.last_lap_time_in_ms)
+                    .field("current_lap_time_in_ms", car.current_lap_time_in_ms)
+                    .field("lap_distance", car.lap_distance)
+                    .field("total_distance", car.total_distance)
+                    .field("car_position", car.car_position)
+                    .field("current_lap_num", car.current_lap_num)
+                    .field("sector", car.sector)
+                    .field("current_lap_invalid", car.current_lap_invalid)
+                    .field("pit_status", car.pit_status)
+                    .field("num_pit_stops", car.num_pit_stops)
+                    .field("penalties", car.penalties)
+                    .field("total_warnings", car.total_warnings)
+                    .field("driver_status", car.driver_status)
+                    .field("result_status", car.result_status)
+            })
+            .collect()
+    }
+
+    fn encode_event(&self, packet: &PacketEventData, timestamp: u64) -> Option<Line> {
+        let line = self
+            .session_line("event", timestamp)
+            .tag("code", packet.code());
+
+        let line = match packet.event()? {
+            Event::FastestLap(e) => self
+                .with_car(line, e.vehicle_idx)
+                .field("lap_time", e.lap_time),
+            Event::Retirement(e) => self.with_car(line, e.vehicle_idx).field("reason", e.reason),
+            Event::DrsDisabled(e) => line.field("reason", e.reason),
+            Event::TeamMateInPits(e) => self.with_car(line, e.vehicle_idx),
+            Event::RaceWinner(e) => self.with_car(line, e.vehicle_idx),
+            Event::Penalty(e) => self
+                .with_car(line, e.vehicle_idx)
+                .field("penalty_type", e.penalty_type)
+                .field("infringement_type", e.infringement_type)
+                .field("other_vehicle_idx", e.other_vehicle_idx)
+                .field("time", e.time)
+                .field("lap_num", e.lap_num)
+                .field("places_gained", e.places_gained),
+            Event::SpeedTrap(e) => self
+                .with_car(line, e.vehicle_idx)
+                .field("speed", e.speed)
+                .field(
+                    "is_overall_fastest_in_session",
+                    e.is_overall_fastest_in_session,
+                )
+                .field(
+                    "is_driver_fastest_in_session",
+                    e.is_driver_fastest_in_session,
+                ),
+            Event::StartLights(e) => line.field("num_lights", e.num_lights),
+            Event::DriveThroughServed(e) => self.with_car(line, e.vehicle_idx),
+            Event::StopGoServed(e) => self
+                .with_car(line, e.vehicle_idx)
+                .field("stop_time", e.stop_time),
+            Event::Flashback(e) => line
+                .field("flashback_frame_identifier", e.flashback_frame_identifier)
+                .field("flashback_session_time", e.flashback_session_time),
+            Event::Buttons(e) => line.field("button_status", e.button_status),
+            Event::Overtake(e) => self
+                .with_car(line, e.overtaking_vehicle_idx)
+                .field("being_overtaken_vehicle_idx", e.being_overtaken_vehicle_idx),
+            Event::SafetyCar(e) => line
+                .field("safety_car_type", e.safety_car_type)
+                .field("event_type", e.event_type),
+            Event::Collision(e) => self
+                .with_car(line, e.vehicle1_idx)
+                .field("vehicle2_idx", e.vehicle2_idx),
+            Event::SessionStarted
+            | Event::SessionEnded
+            | Event::DrsEnabled
+            | Event::ChequeredFlag
+            | Event::LightsOut
+            | Event::RedFlag => line,
+        };
+
+        // A point needs at least one field
+        Some(if line.fields.is_empty() {
+            line.field("count", 1u8)
+        } else {
+            line
+        })
+    }
+
+    fn with_car(&self, line: Line, vehicle_idx: u8) -> Line {
+        let idx = vehicle_idx as usize;
+        let line = line.tag("car_idx", idx);
+        match self.names.get(idx) {
+            Some(name) => line.tag("driver", name),
+            None => line,
+        }
+    }
+
+    fn encode_car_telemetry(&self, packet: &PacketCarTelemetryData, timestamp: u64) -> Vec<Line> {
+        let cars = packet.car_telemetry_data;
+        cars.iter()
+            .take(self.num_active_cars)
+            .enumerate()
+            .map(|(idx, car)| {
+                let line = self
+                    .car_line("car_telemetry", timestamp, idx)
+                    .field("speed", car.speed)
+                    .field("throttle", car.throttle)
+                    .field("steer", car.steer)
+                    .field("brake", car.brake)
+                    .field("clutch", car.clutch)
+                    .field("gear", car.gear)
+                    .field("engine_rpm", car.engine_rpm)
+                    .field("drs", car.drs)
+                    .field("rev_lights_percent", car.rev_lights_percent)
+                    .field("engine_temperature", car.engine_temperature);
+                let line = wheel_fields!(line, "brakes_temperature", car.brakes_temperature);
+                let line = wheel_fields!(
+                    line,
+                    "tyres_surface_temperature",
+                    car.tyres_surface_temperature
+                );
+                let line =
+                    wheel_fields!(line, "tyres_inner_temperature", car.tyres_inner_temperature);
+                wheel_fields!(line, "tyres_pressure", car.tyres_pressure)
+            })
+            .collect()
+    }
+
+    fn encode_car_status(&self, packet: &PacketCarStatusData, timestamp: u64) -> Vec<Line> {
+        let cars = packet.car_status_data;
+        cars.iter()
+            .take(self.num_active_cars)
+            .enumerate()
+            .map(|(idx, car)| {
+                self.car_line("car_status", timestamp, idx)
+                    .field("fuel_mix", car.fuel_mix)
+                    .field("fuel_in_tank", car.fuel_in_tank)
+                    .field("fuel_remaining_laps", car.fuel_remaining_laps)
+                    .field("drs_allowed", car.drs_allowed)
+                    .field("actual_tyre_compound", car.actual_tyre_compound)
+                    .field("visual_tyre_compound", car.visual_tyre_compound)
+                    .field("tyres_age_laps", car.tyres_age_laps)
+                    .field("vehicle_fia_flags", car.vehicle_fia_flags)
+                    .field("engine_power_ice", car.engine_power_ice)
+                    .field("engine_power_mguk", car.engine_power_mguk)
+                    .field("ers_store_energy", car.ers_store_energy)
+                    .field("ers_deploy_mode", car.ers_deploy_mode)
+                    .field(
+                        "ers_harvested_this_lap_mguk",
+                        car.ers_harvested_this_lap_mguk,
+                    )
+                    .field(
+                        "ers_harvested_this_lap_mguh",
+                        car.ers_harvested_this_lap_mguh,
+                    )
+                    .field("ers_deployed_this_lap", car.ers_deployed_this_lap)
+            })
+            .collect()
+    }
+
+    fn encode_car_damage(&self, packet: &PacketCarDamageData, timestamp: u64) -> Vec<Line> {
+        let cars = packet.car_damage_data;
+        cars.iter()
+            .take(self.num_active_cars)
+            .enumerate()
+            .map(|(idx, car)| {
+                let line = self.car_line("car_damage", timestamp, idx);
+                let line = wheel_fields!(line, "tyres_wear", car.tyres_wear);
+                let line = wheel_fields!(line, "tyres_damage", car.tyres_damage);
+                let line = wheel_fields!(line, "brakes_damage", car.brakes_damage);
+                line.field("front_left_wing_damage", car.front_left_wing_damage)
+                    .field("front_right_wing_damage", car.front_right_wing_damage)
+                    .field("rear_wing_damage", car.rear_wing_damage)
+                    .field("floor_damage", car.floor_damage)
+                    .field("diffuser_damage", car.diffuser_damage)
+                    .field("sidepod_damage", car.sidepod_damage)
+                    .field("gear_box_damage", car.gear_box_damage)
+                    .field("engine_damage", car.engine_damage)
+                    .field("engine_mguh_wear", car.engine_mguh_wear)
+                    .field("engine_es_wear", car.engine_es_wear)
+                    .field("engine_ce_wear", car.engine_ce_wear)
+                    .field("engine_ice_wear", car.engine_ice_wear)
+                    .field("engine_mguk_wear", car.engine_mguk_wear)
+                    .field("engine_tc_wear", car.engine_tc_wear)
+            })
+            .collect()
+    }
+
+    fn encode_motion_ex(&self, packet: &PacketMotionExData, timestamp: u64) -> Line {
+        let idx = packet.header.player_car_index as usize;
+        let line = self
+            .car_line("motion_ex", timestamp, idx)
+            .field(
+                "height_of_cog_above_ground",
+                packet.height_of_cog_above_ground,
+            )
+            .field("front_aero_height", packet.front_aero_height)
+            .field("rear_aero_height", packet.rear_aero_height)
+            .field("front_wheels_angle", packet.front_wheels_angle);
+        let line = wheel_fields!(line, "suspension_position", packet.suspension_position);
+        let line = wheel_fields!(line, "wheel_speed", packet.wheel_speed);
+        let line = wheel_fields!(line, "wheel_slip_ratio", packet.wheel_slip_ratio);
+        wheel_fields!(line, "wheel_slip_angle", packet.wheel_slip_angle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lap_packet(session_uid: u64, session_time: f32) -> AnyRawPacket {
+        let mut packet: PacketLapData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        packet.header.session_time = session_time;
+        AnyRawPacket::Lap(packet)
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn anchors_session_time_to_the_wall_clock_once_per_session() {
+        let mut encoder = LineEncoder::new();
+
+        let lines = encoder.encode_at(&lap_packet(1, 10.0), at(1000));
+        assert_eq!(lines.len(), MAX_NUM_CARS);
+        assert_eq!(lines[0].timestamp, 1000 * 1_000_000_000);
+
+        // Later wall clock, same session: still relative to the first anchor
+        let lines = encoder.encode_at(&lap_packet(1, 12.5), at(5000));
+        assert_eq!(lines[0].timestamp, 1_002_500_000_000);
+
+        let lines = encoder.encode_at(&lap_packet(2, 1.0), at(5000));
+        assert_eq!(lines[0].timestamp, 5000 * 1_000_000_000);
+    }
+
+    #[test]
+    fn skips_anchoring_on_an_unrepresentable_session_time() {
+        let mut encoder = LineEncoder::new();
+
+        assert!(
+            encoder
+                .encode_at(&lap_packet(1, f32::INFINITY), at(1000))
+                .is_empty()
+        );
+        assert!(
+            encoder
+                .encode_at(&lap_packet(1, f32::MAX), at(1000))
+                .is_empty()
+        );
+
+        let lines = encoder.encode_at(&lap_packet(1, 5.0), at(2000));
+        assert_eq!(lines[0].timestamp, 2000 * 1_000_000_000);
+    }
+
+    #[test]
+    fn drops_packets_until_a_new_session_is_anchored() {
+        let mut encoder = LineEncoder::new();
+        encoder.encode_at(&lap_packet(1, 10.0), at(1000));
+
+        // A new session must not reuse the previous session's anchor or tags
+        assert!(
+            encoder
+                .encode_at(&lap_packet(2, f32::INFINITY), at(2000))
+                .is_empty()
+        );
+
+        let lines = encoder.encode_at(&lap_packet(2, 1.0), at(3000));
+        assert_eq!(lines[0].timestamp, 3000 * 1_000_000_000);
+        assert!(lines[0].tags.contains(&("session_uid", "2".to_owned())));
+    }
+
+    #[test]
+    fn drops_packets_whose_session_time_overflows_the_timestamp() {
+        let mut encoder = LineEncoder::new();
+        encoder.encode_at(&lap_packet(1, 0.0), at(1000));
+
+        assert!(
+            encoder
+                .encode_at(&lap_packet(1, f32::INFINITY), at(1000))
+                .is_empty()
+        );
+        assert!(
+            encoder
+                .encode_at(&lap_packet(1, f32::MAX), at(1000))
+                .is_empty()
+        );
+        assert_eq!(
+            encoder.encode_at(&lap_packet(1, 1.0), at(1000)).len(),
+            MAX_NUM_CARS
+        );
+    }
+
+    #[test]
+    fn tags_points_with_session_and_car() {
+        let mut encoder = LineEncoder::new();
+        let lines = encoder.encode_at(&lap_packet(7, 0.0), at(0));
+
+        assert_eq!(lines[3].measurement, "lap");
+        assert!(lines[3].tags.contains(&("session_uid", "7".to_owned())));
+        assert!(lines[3].tags.contains(&("car_idx", "3".to_owned())));
+    }
+}
@@ -0,0 +1,28 @@
+use core::fmt;
+
+#[derive(Debug)]
+pub enum InfluxError {
+    Io(std::io::Error),
+    InvalidEndpoint(String),
+    Http { status: u16, body: String },
+}
+
+impl std::error::Error for InfluxError {}
+
+impl fmt::Display for InfluxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InfluxError::Io(e) => write!(f, "I/O error: {}", e),
+            InfluxError::InvalidEndpoint(msg) => write!(f, "Invalid endpoint: {}", msg),
+            InfluxError::Http { status, body } => {
+                write!(f, "Write rejected with HTTP {}: {}", status, body)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for InfluxError {
+    fn from(e: std::io::Error) -> Self {
+        InfluxError::Io(e)
+    }
+}
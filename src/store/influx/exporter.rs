@@ -0,0 +1,39 @@
+use crate::{
+    packet::AnyRawPacket,
+    store::influx::{InfluxError, LineEncoder, LineSink},
+};
+
+/// Encodes packets and forwards the resulting points to a sink
+pub struct InfluxExporter<S: LineSink> {
+    encoder: LineEncoder,
+    sink: S,
+}
+
+impl<S: LineSink> InfluxExporter<S> {
+    pub fn new(sink: S) -> Self {
+        Self {
+            encoder: LineEncoder::new(),
+            sink,
+        }
+    }
+
+    pub fn push(&mut self, packet: &AnyRawPacket) -> Result<(), InfluxError> {
+        let lines = self.encoder.encode(packet);
+        if lines.is_empty() {
+            return Ok(());
+        }
+        self.sink.write(&lines)
+    }
+
+    pub fn flush(&mut self) -> Result<(), InfluxError> {
+        self.sink.flush()
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+}
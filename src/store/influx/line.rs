@@ -0,0 +1,158 @@
+use core::fmt;
+
+/// Value of a single line protocol field
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Float(f64),
+    Integer(i64),
+    UInteger(u64),
+    Boolean(bool),
+    String(String),
+}
+
+macro_rules! impl_field_from {
+    ($variant:ident: $target:ty => $($ty:ty),*) => {
+        $(impl From<$ty> for FieldValue {
+            fn from(value: $ty) -> Self {
+                FieldValue::$variant(value as $target)
+            }
+        })*
+    };
+}
+
+impl_field_from!(Float: f64 => f32, f64);
+// Unsigned integers are opt-in on InfluxDB 1.x, so only use them when i64 can't hold the value
+impl_field_from!(Integer: i64 => i8, i16, i32, i64, u8, u16, u32);
+impl_field_from!(UInteger: u64 => u64);
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Boolean(value)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        FieldValue::String(value.to_owned())
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Float(v) => write!(f, "{}", v),
+            FieldValue::Integer(v) => write!(f, "{}i", v),
+            FieldValue::UInteger(v) => write!(f, "{}u", v),
+            FieldValue::Boolean(v) => write!(f, "{}", v),
+            FieldValue::String(v) => {
+                write!(f, "\"")?;
+                for c in v.chars() {
+                    if c == '"' || c == '\\' {
+                        write!(f, "\\")?;
+                    }
+                    write!(f, "{}", c)?;
+                }
+                write!(f, "\"")
+            }
+        }
+    }
+}
+
+/// A single point in InfluxDB line protocol
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub measurement: &'static str,
+    pub tags: Vec<(&'static str, String)>,
+    pub fields: Vec<(&'static str, FieldValue)>,
+    /// Nanoseconds since the unix epoch
+    pub timestamp: u64,
+}
+
+impl Line {
+    pub fn new(measurement: &'static str, timestamp: u64) -> Self {
+        Self {
+            measurement,
+            tags: Vec::new(),
+            fields: Vec::new(),
+            timestamp,
+        }
+    }
+
+    /// Adds a tag, empty values are skipped as line protocol does not allow them
+    pub fn tag(mut self, key: &'static str, value: impl ToString) -> Self {
+        let value = value.to_string();
+        if !value.is_empty() {
+            self.tags.push((key, value));
+        }
+        self
+    }
+
+    /// Adds a field, non-finite floats are skipped as line protocol cannot represent them
+    pub fn field(mut self, key: &'static str, value: impl Into<FieldValue>) -> Self {
+        let value = value.into();
+        if !matches!(value, FieldValue::Float(v) if !v.is_finite()) {
+            self.fields.push((key, value));
+        }
+        self
+    }
+}
+
+fn write_escaped(f: &mut fmt::Formatter<'_>, s: &str, special: &[char]) -> fmt::Result {
+    for c in s.chars() {
+        if c == '\\' || special.contains(&c) {
+            write!(f, "\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_escaped(f, self.measurement, &[',', ' '])?;
+
+        for (key, value) in &self.tags {
+            write!(f, ",")?;
+            write_escaped(f, key, &[',', '=', ' '])?;
+            write!(f, "=")?;
+            write_escaped(f, value, &[',', '=', ' '])?;
+        }
+
+        for (i, (key, value)) in self.fields.iter().enumerate() {
+            write!(f, "{}", if i == 0 { " " } else { "," })?;
+            write_escaped(f, key, &[',', '=', ' '])?;
+            write!(f, "={}", value)?;
+        }
+
+        write!(f, " {}", self.timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_measurement_tags_and_fields() {
+        let line = Line::new("lap data", 42)
+            .tag("driver", "Max, V=1")
+            .tag("empty", "")
+            .field("note", r#"say "hi" \o/"#)
+            .field("speed", 300u16);
+
+        assert_eq!(
+            line.to_string(),
+            r#"lap\ data,driver=Max\,\ V\=1 note="say \"hi\" \\o/",speed=300i 42"#
+        );
+    }
+
+    #[test]
+    fn skips_non_finite_floats() {
+        let line = Line::new("motion", 1)
+            .field("yaw", f32::NAN)
+            .field("pitch", f64::INFINITY)
+            .field("roll", 0.5f32);
+
+        assert_eq!(line.fields, vec![("roll", FieldValue::Float(0.5))]);
+    }
+}
@@ -0,0 +1,11 @@
+pub mod encoder;
+pub mod error;
+pub mod exporter;
+pub mod line;
+pub mod sink;
+
+pub use encoder::*;
+pub use error::*;
+pub use exporter::*;
+pub use line::*;
+pub use sink::*;
@@ -0,0 +1,410 @@
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::Path,
+    time::Duration,
+};
+
+use crate::store::influx::{InfluxError, Line};
+
+/// Destination for encoded points
+pub trait LineSink {
+    fn write(&mut self, lines: &[Line]) -> Result<(), InfluxError>;
+    fn flush(&mut self) -> Result<(), InfluxError>;
+}
+
+/// Appends points to a line protocol file, one point per line
+pub struct FileSink {
+    writer: BufWriter<File>,
+}
+
+impl FileSink {
+    /// Opens `path` for appending, creating it if it doesn't exist
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, InfluxError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+}
+
+impl LineSink for FileSink {
+    fn write(&mut self, lines: &[Line]) -> Result<(), InfluxError> {
+        for line in lines {
+            writeln!(self.writer, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), InfluxError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Location of an InfluxDB write endpoint, e.g.
+/// `http://localhost:8086/api/v2/write?org=league&bucket=telemetry&precision=ns`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpEndpoint {
+    pub host: String,
+    pub port: u16,
+    /// Path including the query string
+    pub path: String,
+    /// Sent as `Authorization: Token <token>` when set
+    pub token: Option<String>,
+}
+
+impl HttpEndpoint {
+    /// Parses a plain `http://host[:port]/path[?query]` url, TLS is not supported
+    pub fn parse(url: &str) -> Result<Self, InfluxError> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| InfluxError::InvalidEndpoint(format!("not an http url: {}", url)))?;
+
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(i) if rest[i..].starts_with('?') => (&rest[..i], format!("/{}", &rest[i..])),
+            Some(i) => (&rest[..i], rest[i..].to_owned()),
+            None => (rest, "/".to_owned()),
+        };
+
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port
+                    .parse()
+                    .map_err(|_| InfluxError::InvalidEndpoint(format!("bad port: {}", port)))?;
+                (host, port)
+            }
+            None => (authority, 80),
+        };
+
+        if host.is_empty() {
+            return Err(InfluxError::InvalidEndpoint(format!(
+                "missing host: {}",
+                url
+            )));
+        }
+
+        Ok(Self {
+            host: host.to_owned(),
+            port,
+            path,
+            token: None,
+        })
+    }
+
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+}
+
+/// POSTs points to an InfluxDB write endpoint in batches.
+///
+/// Points are buffered until `batch_size` is reached or [`LineSink::flush`] is called,
+/// and sent in requests of at most `batch_size` points. A batch stays buffered until it
+/// is written, so points are retried with the next write after a connection error,
+/// server failure or an auth/size rejection (HTTP 401, 403, 413). Only a batch the server
+/// rejects as invalid (any other HTTP 4xx) is dropped, as sending it again can't succeed.
+/// Once `max_pending` points are buffered the oldest are dropped to make room.
+pub struct HttpSink {
+    endpoint: HttpEndpoint,
+    batch_size: usize,
+    max_pending: usize,
+    timeout: Duration,
+    buffer: VecDeque<String>,
+    dropped: usize,
+}
+
+impl HttpSink {
+    pub const DEFAULT_BATCH_SIZE: usize = 5000;
+    pub const DEFAULT_MAX_PENDING: usize = 100 * Self::DEFAULT_BATCH_SIZE;
+
+    pub fn new(endpoint: HttpEndpoint) -> Self {
+        Self {
+            endpoint,
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+            max_pending: Self::DEFAULT_MAX_PENDING,
+            timeout: Duration::from_secs(5),
+            buffer: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending.max(1);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Number of points waiting for the next batch
+    pub fn pending(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Number of points dropped, either rejected by the server or evicted from a full buffer
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Sends buffered points one batch at a time while at least `min_points` are pending,
+    /// stopping at the first error
+    fn post(&mut self, min_points: usize) -> Result<(), InfluxError> {
+        while !self.buffer.is_empty() && self.buffer.len() >= min_points {
+            let len = self.batch_size.min(self.buffer.len());
+            let result = self.send(len);
+            match result {
+                Ok(()) => {}
+                Err(InfluxError::Http {
+                    status: 401 | 403 | 413,
+                    ..
+                }) => return result,
+                Err(InfluxError::Http {
+                    status: 400..500, ..
+                }) => {
+                    self.buffer.drain(..len);
+                    self.dropped += len;
+                    return result;
+                }
+                Err(_) => return result,
+            }
+            self.buffer.drain(..len);
+        }
+        Ok(())
+    }
+
+    fn connect(&self) -> Result<TcpStream, InfluxError> {
+        let endpoint = &self.endpoint;
+        let mut error = None;
+        for addr in (endpoint.host.as_str(), endpoint.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(error
+            .unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address for host"))
+            .into())
+    }
+
+    /// Sends the first `len` buffered points
+    fn send(&self, len: usize) -> Result<(), InfluxError> {
+        let body: String = self.buffer.iter().take(len).map(String::as_str).collect();
+        let endpoint = &self.endpoint;
+        let mut stream = self.connect()?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        let mut request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+            endpoint.path,
+            endpoint.host,
+            endpoint.port,
+            body.len()
+        );
+        if let Some(token) = &endpoint.token {
+            request.push_str(&format!("Authorization: Token {}\r\n", token));
+        }
+        request.push_str("\r\n");
+
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body.as_bytes())?;
+        stream.flush()?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+        let response = String::from_utf8_lossy(&response);
+
+        let status = response
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse::<u16>().ok())
+            .ok_or_else(|| InfluxError::Http {
+                status: 0,
+                body: "malformed response".to_owned(),
+            })?;
+
+        if (200..300).contains(&status) {
+            Ok(())
+        } else {
+            let body = response
+                .split_once("\r\n\r\n")
+                .map(|(_, body)| body.trim().to_owned())
+                .unwrap_or_default();
+            Err(InfluxError::Http { status, body })
+        }
+    }
+}
+
+impl LineSink for HttpSink {
+    /// Buffers all of `lines` even when a batch fails, returning the first error
+    fn write(&mut self, lines: &[Line]) -> Result<(), InfluxError> {
+        let mut result = Ok(());
+        for line in lines {
+            if self.buffer.len() >= self.max_pending {
+                self.buffer.pop_front();
+                self.dropped += 1;
+            }
+            self.buffer.push_back(format!("{}\n", line));
+
+            if result.is_ok() && self.buffer.len() >= self.batch_size {
+                result = self.post(self.batch_size);
+            }
+        }
+        result
+    }
+
+    fn flush(&mut self) -> Result<(), InfluxError> {
+        self.post(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+        thread::{self, JoinHandle},
+    };
+
+    use super::*;
+
+    #[test]
+    fn parses_endpoints() {
+        let endpoint = HttpEndpoint::parse("http://influx:8086/api/v2/write?bucket=f1").unwrap();
+        assert_eq!(endpoint.host, "influx");
+        assert_eq!(endpoint.port, 8086);
+        assert_eq!(endpoint.path, "/api/v2/write?bucket=f1");
+
+        let endpoint = HttpEndpoint::parse("http://localhost?db=f1").unwrap();
+        assert_eq!(endpoint.port, 80);
+        assert_eq!(endpoint.path, "/?db=f1");
+
+        assert_eq!(HttpEndpoint::parse("http://host").unwrap().path, "/");
+        assert!(HttpEndpoint::parse("https://host/write").is_err());
+        assert!(HttpEndpoint::parse("http://:8086/write").is_err());
+        assert!(HttpEndpoint::parse("http://host:port/write").is_err());
+    }
+
+    /// Answers one request per status with it, returning the request bodies
+    fn serve(statuses: &[u16]) -> (HttpEndpoint, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let statuses = statuses.to_vec();
+
+        let server = thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header == "\r\n" {
+                        break;
+                    }
+                    if let Some(length) = header.strip_prefix("Content-Length: ") {
+                        content_length = length.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                bodies.push(String::from_utf8(body).unwrap());
+
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\nContent-Length: 4\r\n\r\nnope",
+                    status
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+            bodies
+        });
+
+        let endpoint = HttpEndpoint::parse(&format!("http://127.0.0.1:{}/write", port)).unwrap();
+        (endpoint, server)
+    }
+
+    fn lines(range: std::ops::Range<u64>) -> Vec<Line> {
+        range.map(|i| Line::new("lap", i).field("n", i)).collect()
+    }
+
+    #[test]
+    fn posts_full_batches_and_flushes_the_rest() {
+        let (endpoint, server) = serve(&[204, 204]);
+        let mut sink = HttpSink::new(endpoint).with_batch_size(2);
+
+        sink.write(&lines(0..3)).unwrap();
+        assert_eq!(sink.pending(), 1);
+        sink.flush().unwrap();
+        assert_eq!(sink.pending(), 0);
+
+        let bodies = server.join().unwrap();
+        assert_eq!(bodies, ["lap n=0u 0\nlap n=1u 1\n", "lap n=2u 2\n"]);
+    }
+
+    #[test]
+    fn keeps_a_failed_batch_and_the_rest_of_the_write() {
+        let (endpoint, server) = serve(&[503, 204, 204]);
+        let mut sink = HttpSink::new(endpoint).with_batch_size(2);
+
+        let error = sink.write(&lines(0..3)).unwrap_err();
+        assert!(matches!(error, InfluxError::Http { status: 503, .. }));
+        assert_eq!(sink.pending(), 3);
+
+        sink.flush().unwrap();
+        assert_eq!(sink.pending(), 0);
+
+        let bodies = server.join().unwrap();
+        assert_eq!(bodies[1..], ["lap n=0u 0\nlap n=1u 1\n", "lap n=2u 2\n"]);
+    }
+
+    #[test]
+    fn keeps_a_batch_rejected_for_auth_or_size() {
+        let (endpoint, server) = serve(&[401, 413]);
+        let mut sink = HttpSink::new(endpoint);
+
+        sink.write(&lines(0..2)).unwrap();
+        assert!(sink.flush().is_err());
+        assert!(sink.flush().is_err());
+        assert_eq!(sink.pending(), 2);
+        assert_eq!(sink.dropped(), 0);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn drops_only_the_batch_the_server_rejects() {
+        let (endpoint, server) = serve(&[400, 204]);
+        let mut sink = HttpSink::new(endpoint).with_batch_size(2);
+
+        sink.write(&lines(0..1)).unwrap();
+        sink.write(&lines(1..3)).unwrap_err();
+        assert_eq!(sink.pending(), 1);
+        assert_eq!(sink.dropped(), 2);
+
+        sink.flush().unwrap();
+        let bodies = server.join().unwrap();
+        assert_eq!(bodies[1], "lap n=2u 2\n");
+    }
+
+    #[test]
+    fn evicts_the_oldest_points_from_a_full_buffer() {
+        let endpoint = HttpEndpoint::parse("http://127.0.0.1:9/write").unwrap();
+        let mut sink = HttpSink::new(endpoint)
+            .with_batch_size(10)
+            .with_max_pending(3);
+
+        sink.write(&lines(0..5)).unwrap();
+        assert_eq!(sink.pending(), 3);
+        assert_eq!(sink.dropped(), 2);
+    }
+}
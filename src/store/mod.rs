@@ -0,0 +1,3 @@
+pub mod influx;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
@@ -0,0 +1,32 @@
+use core::fmt;
+
+use crate::packet::PacketError;
+
+#[derive(Debug)]
+pub enum StoreError {
+    Sqlite(rusqlite::Error),
+    Packet(PacketError),
+}
+
+impl std::error::Error for StoreError {}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Sqlite(e) => write!(f, "SQLite error: {}", e),
+            StoreError::Packet(e) => write!(f, "Packet error: {}", e),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        StoreError::Sqlite(e)
+    }
+}
+
+impl From<PacketError> for StoreError {
+    fn from(e: PacketError) -> Self {
+        StoreError::Packet(e)
+    }
+}
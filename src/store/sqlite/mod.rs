@@ -0,0 +1,9 @@
+pub mod error;
+pub mod query;
+pub mod schema;
+pub mod store;
+
+pub use error::*;
+pub use query::*;
+pub use schema::*;
+pub use store::*;
@@ -0,0 +1,160 @@
+use rusqlite::{Row, params};
+
+use crate::{
+    constants::{SessionType, TeamId, TrackId},
+    store::sqlite::{SessionStore, StoreError},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionRecord {
+    pub session_uid: u64,
+    pub season_link_identifier: Option<u32>,
+    pub weekend_link_identifier: Option<u32>,
+    pub session_link_identifier: Option<u32>,
+    /// `None` until a session packet has been stored, or for unknown tracks
+    pub track: Option<TrackId>,
+    pub session_type: Option<SessionType>,
+    pub total_laps: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LapRecord {
+    pub car_idx: u8,
+    pub lap_num: u8,
+    pub lap_time_in_ms: u32,
+    pub sector_times_in_ms: [u32; 3],
+    pub valid: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BestLap {
+    pub session_uid: u64,
+    pub driver: String,
+    pub team: Option<TeamId>,
+    pub lap: LapRecord,
+}
+
+const SESSION_COLUMNS: &str = "session_uid, season_link_identifier, weekend_link_identifier,
+    session_link_identifier, track_id, session_type, total_laps";
+
+fn session_from_row(row: &Row) -> rusqlite::Result<SessionRecord> {
+    let track_id: Option<i64> = row.get(4)?;
+    let session_type: Option<u8> = row.get(5)?;
+    Ok(SessionRecord {
+        session_uid: row.get::<_, i64>(0)? as u64,
+        season_link_identifier: row.get(1)?,
+        weekend_link_identifier: row.get(2)?,
+        session_link_identifier: row.get(3)?,
+        track: track_id
+            .and_then(|id| u8::try_from(id).ok())
+            .and_then(TrackId::try_from_id),
+        session_type: session_type.and_then(SessionType::try_from_id),
+        total_laps: row.get(6)?,
+    })
+}
+
+/// Expects `car_idx, lap_num, lap_time_in_ms, sector1..3, valid` starting at `offset`
+fn lap_from_row(row: &Row, offset: usize) -> rusqlite::Result<LapRecord> {
+    Ok(LapRecord {
+        car_idx: row.get(offset)?,
+        lap_num: row.get(offset + 1)?,
+        lap_time_in_ms: row.get(offset + 2)?,
+        sector_times_in_ms: [
+            row.get(offset + 3)?,
+            row.get(offset + 4)?,
+            row.get(offset + 5)?,
+        ],
+        valid: row.get(offset + 6)?,
+    })
+}
+
+impl SessionStore {
+    pub fn sessions(&self) -> Result<Vec<SessionRecord>, StoreError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {SESSION_COLUMNS} FROM sessions ORDER BY rowid"
+        ))?;
+        let rows = stmt.query_map([], session_from_row)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    pub fn session(&self, session_uid: u64) -> Result<Option<SessionRecord>, StoreError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {SESSION_COLUMNS} FROM sessions WHERE session_uid = ?1"
+        ))?;
+        let mut rows = stmt.query_map([session_uid as i64], session_from_row)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Sessions of one weekend, in the order they were run
+    pub fn sessions_in_weekend(
+        &self,
+        season_link_identifier: u32,
+        weekend_link_identifier: u32,
+    ) -> Result<Vec<SessionRecord>, StoreError> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {SESSION_COLUMNS} FROM sessions
+            WHERE season_link_identifier = ?1 AND weekend_link_identifier = ?2
+            ORDER BY session_link_identifier"
+        ))?;
+        let rows = stmt.query_map(
+            params![season_link_identifier, weekend_link_identifier],
+            session_from_row,
+        )?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Completed laps of one car, in lap order
+    pub fn laps(&self, session_uid: u64, car_idx: u8) -> Result<Vec<LapRecord>, StoreError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT car_idx, lap_num, lap_time_in_ms, sector1_in_ms, sector2_in_ms,
+                sector3_in_ms, valid
+            FROM laps WHERE session_uid = ?1 AND car_idx = ?2 ORDER BY lap_num",
+        )?;
+        let rows = stmt.query_map(params![session_uid as i64, car_idx], |row| {
+            lap_from_row(row, 0)
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Fastest valid lap of each car in each session at a track, fastest first.
+    ///
+    /// Entries are per session and car rather than per driver, as participant names
+    /// aren't unique, e.g. every local player is "Player".
+    pub fn best_laps_by_track(
+        &self,
+        track: TrackId,
+        limit: usize,
+    ) -> Result<Vec<BestLap>, StoreError> {
+        let mut stmt = self.conn.prepare_cached(
+            "WITH ranked AS (
+                SELECT l.session_uid, l.car_idx, l.lap_num, l.lap_time_in_ms, l.sector1_in_ms,
+                    l.sector2_in_ms, l.sector3_in_ms, l.valid,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY l.session_uid, l.car_idx
+                        ORDER BY l.lap_time_in_ms, l.lap_num
+                    ) AS rank
+                FROM laps l
+                JOIN sessions s ON s.session_uid = l.session_uid
+                WHERE s.track_id = ?1 AND l.valid = 1 AND l.lap_time_in_ms > 0
+            )
+            SELECT r.session_uid, COALESCE(p.name, ''), p.team_id,
+                r.car_idx, r.lap_num, r.lap_time_in_ms, r.sector1_in_ms, r.sector2_in_ms,
+                r.sector3_in_ms, r.valid
+            FROM ranked r
+            LEFT JOIN participants p ON p.session_uid = r.session_uid AND p.car_idx = r.car_idx
+            WHERE r.rank = 1
+            ORDER BY r.lap_time_in_ms, r.session_uid, r.car_idx
+            LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![track.id(), limit as i64], |row| {
+            let team: Option<u8> = row.get(2)?;
+            Ok(BestLap {
+                session_uid: row.get::<_, i64>(0)? as u64,
+                driver: row.get(1)?,
+                team: team.and_then(TeamId::try_from_id),
+                lap: lap_from_row(row, 3)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
@@ -0,0 +1,108 @@
+/// Current schema version, stored in `PRAGMA user_version`
+pub const SCHEMA_VERSION: i32 = 1;
+
+/// Every table is keyed by natural identifiers from the packets, so ingesting the same
+/// recording twice leaves the database unchanged. `session_uid` is a u64 in the packets
+/// and is stored bit-for-bit in SQLite's signed INTEGER.
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    session_uid             INTEGER PRIMARY KEY,
+    season_link_identifier  INTEGER,
+    weekend_link_identifier INTEGER,
+    session_link_identifier INTEGER,
+    game_year               INTEGER,
+    track_id                INTEGER,
+    track_length            INTEGER,
+    session_type            INTEGER,
+    formula                 INTEGER,
+    game_mode               INTEGER,
+    rule_set                INTEGER,
+    total_laps              INTEGER,
+    network_game            INTEGER,
+    player_car_idx          INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS sessions_link
+    ON sessions (season_link_identifier, weekend_link_identifier, session_link_identifier);
+
+CREATE TABLE IF NOT EXISTS participants (
+    session_uid   INTEGER NOT NULL REFERENCES sessions (session_uid),
+    car_idx       INTEGER NOT NULL,
+    ai_controlled INTEGER NOT NULL,
+    driver_id     INTEGER NOT NULL,
+    network_id    INTEGER NOT NULL,
+    team_id       INTEGER NOT NULL,
+    race_number   INTEGER NOT NULL,
+    nationality   INTEGER NOT NULL,
+    name          TEXT NOT NULL,
+    platform      INTEGER NOT NULL,
+    PRIMARY KEY (session_uid, car_idx)
+);
+
+CREATE TABLE IF NOT EXISTS laps (
+    session_uid     INTEGER NOT NULL REFERENCES sessions (session_uid),
+    car_idx         INTEGER NOT NULL,
+    lap_num         INTEGER NOT NULL,
+    lap_time_in_ms  INTEGER NOT NULL,
+    sector1_in_ms   INTEGER NOT NULL,
+    sector2_in_ms   INTEGER NOT NULL,
+    sector3_in_ms   INTEGER NOT NULL,
+    valid           INTEGER NOT NULL,
+    -- 'lap_data' when reconstructed from the lap packet, 'history' once confirmed
+    -- by a session history packet, which always takes precedence
+    source          TEXT NOT NULL,
+    PRIMARY KEY (session_uid, car_idx, lap_num)
+);
+
+CREATE TABLE IF NOT EXISTS stints (
+    session_uid     INTEGER NOT NULL REFERENCES sessions (session_uid),
+    car_idx         INTEGER NOT NULL,
+    stint_num       INTEGER NOT NULL,
+    -- NULL while the stint is still running
+    end_lap         INTEGER,
+    actual_compound INTEGER NOT NULL,
+    visual_compound INTEGER NOT NULL,
+    PRIMARY KEY (session_uid, car_idx, stint_num)
+);
+
+CREATE TABLE IF NOT EXISTS events (
+    session_uid       INTEGER NOT NULL REFERENCES sessions (session_uid),
+    frame             INTEGER NOT NULL,
+    session_time      REAL NOT NULL,
+    code              TEXT NOT NULL,
+    -- -1 for events that don't relate to a car
+    vehicle_idx       INTEGER NOT NULL,
+    other_vehicle_idx INTEGER,
+    PRIMARY KEY (session_uid, frame, code, vehicle_idx)
+);
+
+CREATE TABLE IF NOT EXISTS penalties (
+    session_uid       INTEGER NOT NULL REFERENCES sessions (session_uid),
+    frame             INTEGER NOT NULL,
+    vehicle_idx       INTEGER NOT NULL,
+    penalty_type      INTEGER NOT NULL,
+    infringement_type INTEGER NOT NULL,
+    other_vehicle_idx INTEGER NOT NULL,
+    time              INTEGER NOT NULL,
+    lap_num           INTEGER NOT NULL,
+    places_gained     INTEGER NOT NULL,
+    PRIMARY KEY (session_uid, frame, vehicle_idx, penalty_type, infringement_type)
+);
+
+CREATE TABLE IF NOT EXISTS final_classifications (
+    session_uid         INTEGER NOT NULL REFERENCES sessions (session_uid),
+    car_idx             INTEGER NOT NULL,
+    position            INTEGER NOT NULL,
+    num_laps            INTEGER NOT NULL,
+    grid_position       INTEGER NOT NULL,
+    points              INTEGER NOT NULL,
+    num_pit_stops       INTEGER NOT NULL,
+    result_status       INTEGER NOT NULL,
+    result_reason       INTEGER NOT NULL,
+    best_lap_time_in_ms INTEGER NOT NULL,
+    total_race_time     REAL NOT NULL,
+    penalties_time      INTEGER NOT NULL,
+    num_penalties       INTEGER NOT NULL,
+    PRIMARY KEY (session_uid, car_idx)
+);
+";
@@ -0,0 +1,610 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use rusqlite::{Connection, params};
+
+use crate::{
+    constants::{LapValidFlags, ResultStatus},
+    packet::{AnyRawPacket, PacketError, RecordingReader},
+    raw::{
+        constants::{MAX_NUM_CARS, MAX_TYRE_STINTS},
+        *,
+    },
+    store::sqlite::{SCHEMA, SCHEMA_VERSION, StoreError},
+    timing::LapTime,
+};
+
+/// Persists sessions into a normalised SQLite schema, see [`SCHEMA`].
+///
+/// Laps are taken from session history packets where available and otherwise
+/// reconstructed from lap packets as each car crosses the line. Button events are
+/// not stored.
+pub struct SessionStore {
+    pub(crate) conn: Connection,
+    /// Previous lap packet, to spot cars starting a new lap
+    last_lap_data: Option<(u64, [LapData; MAX_NUM_CARS])>,
+}
+
+impl SessionStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, StoreError> {
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(Self {
+            conn,
+            last_lap_data: None,
+        })
+    }
+
+    /// Underlying connection, for queries not covered by the query API
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Stores a single packet, as it arrives
+    pub fn push(&mut self, packet: &AnyRawPacket) -> Result<(), StoreError> {
+        write_packet(&self.conn, &mut self.last_lap_data, packet)
+    }
+
+    /// Stores a stream of packets in one transaction, returning the number of packets read.
+    ///
+    /// Re-ingesting the same packets is a no-op, so a recording can safely be imported
+    /// again. Nothing is committed if any packet fails to decode, and the store carries on
+    /// from where it was before the call.
+    pub fn ingest<I>(&mut self, packets: I) -> Result<usize, StoreError>
+    where
+        I: IntoIterator<Item = Result<AnyRawPacket, PacketError>>,
+    {
+        let last_lap_data = self.last_lap_data;
+        let result = Self::ingest_in(&mut self.conn, &mut self.last_lap_data, packets);
+        self.last_lap_data = match result {
+            Ok(_) => None,
+            Err(_) => last_lap_data,
+        };
+        result
+    }
+
+    fn ingest_in<I>(
+        conn: &mut Connection,
+        last_lap_data: &mut Option<(u64, [LapData; MAX_NUM_CARS])>,
+        packets: I,
+    ) -> Result<usize, StoreError>
+    where
+        I: IntoIterator<Item = Result<AnyRawPacket, PacketError>>,
+    {
+        let tx = conn.transaction()?;
+        let mut count = 0;
+        for packet in packets {
+            write_packet(&tx, last_lap_data, &packet?)?;
+            count += 1;
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// Ingests a recording file written by [`crate::packet::RecordingWriter`]
+    pub fn ingest_recording(&mut self, path: impl AsRef<Path>) -> Result<usize, StoreError> {
+        let file = File::open(path).map_err(PacketError::from)?;
+        self.ingest(RecordingReader::new(BufReader::new(file)))
+    }
+}
+
+fn write_packet(
+    conn: &Connection,
+    last_lap_data: &mut Option<(u64, [LapData; MAX_NUM_CARS])>,
+    packet: &AnyRawPacket,
+) -> Result<(), StoreError> {
+    let header = *packet.header();
+    let session_uid = header.session_uid as i64;
+
+    match packet {
+        AnyRawPacket::Session(p) => write_session(conn, p)?,
+        AnyRawPacket::Participants(p) => {
+            ensure_session(conn, session_uid)?;
+            write_participants(conn, session_uid, p)?;
+        }
+        AnyRawPacket::Lap(p) => {
+            ensure_session(conn, session_uid)?;
+            let lap_data = p.lap_data;
+            if let Some((uid, previous)) = last_lap_data
+                && *uid == header.session_uid
+            {
+                write_completed_laps(conn, session_uid, previous, &lap_data)?;
+            }
+            *last_lap_data = Some((header.session_uid, lap_data));
+        }
+        AnyRawPacket::SessionHistory(p) => {
+            ensure_session(conn, session_uid)?;
+            write_session_history(conn, session_uid, p)?;
+        }
+        AnyRawPacket::Event(p) => {
+            ensure_session(conn, session_uid)?;
+            write_event(conn, session_uid, p)?;
+        }
+        AnyRawPacket::FinalClassification(p) => {
+            ensure_session(conn, session_uid)?;
+            write_final_classification(conn, session_uid, p)?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn ensure_session(conn: &Connection, session_uid: i64) -> Result<(), StoreError> {
+    conn.prepare_cached("INSERT OR IGNORE INTO sessions (session_uid) VALUES (?1)")?
+        .execute([session_uid])?;
+    Ok(())
+}
+
+fn write_session(conn: &Connection, p: &PacketSessionData) -> Result<(), StoreError> {
+    let header = p.header;
+    conn.prepare_cached(
+        "INSERT INTO sessions (
+            session_uid, season_link_identifier, weekend_link_identifier, session_link_identifier,
+            game_year, track_id, track_length, session_type, formula, game_mode, rule_set,
+            total_laps, network_game, player_car_idx
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+        ON CONFLICT (session_uid) DO UPDATE SET
+            season_link_identifier = excluded.season_link_identifier,
+            weekend_link_identifier = excluded.weekend_link_identifier,
+            session_link_identifier = excluded.session_link_identifier,
+            game_year = excluded.game_year,
+            track_id = excluded.track_id,
+            track_length = excluded.track_length,
+            session_type = excluded.session_type,
+            formula = excluded.formula,
+            game_mode = excluded.game_mode,
+            rule_set = excluded.rule_set,
+            total_laps = excluded.total_laps,
+            network_game = excluded.network_game,
+            player_car_idx = excluded.player_car_idx",
+    )?
+    .execute(params![
+        header.session_uid as i64,
+        { p.season_link_identifier },
+        { p.weekend_link_identifier },
+        { p.session_link_identifier },
+        { header.game_year },
+        { p.track_id },
+        { p.track_length },
+        { p.session_type },
+        { p.formula },
+        { p.game_mode },
+        { p.rule_set },
+        { p.total_laps },
+        { p.network_game },
+        { header.player_car_index },
+    ])?;
+    Ok(())
+}
+
+fn write_participants(
+    conn: &Connection,
+    session_uid: i64,
+    p: &PacketParticipantsData,
+) -> Result<(), StoreError> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO participants (
+            session_uid, car_idx, ai_controlled, driver_id, network_id, team_id,
+            race_number, nationality, name, platform
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+    )?;
+
+    // Active cars need not be contiguous, empty slots have no name
+    let participants = p.participants;
+    for (car_idx, participant) in participants.iter().enumerate() {
+        if participant.name_str().is_empty() {
+            continue;
+        }
+        stmt.execute(params![
+            session_uid,
+            car_idx as u8,
+            { participant.ai_controlled },
+            { participant.driver_id },
+            { participant.network_id },
+            { participant.team_id },
+            { participant.race_number },
+            { participant.nationality },
+            participant.name_str(),
+            { participant.platform },
+        ])?;
+    }
+    Ok(())
+}
+
+fn write_completed_laps(
+    conn: &Connection,
+    session_uid: i64,
+    previous: &[LapData; MAX_NUM_CARS],
+    current: &[LapData; MAX_NUM_CARS],
+) -> Result<(), StoreError> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO laps (
+            session_uid, car_idx, lap_num, lap_time_in_ms,
+            sector1_in_ms, sector2_in_ms, sector3_in_ms, valid, source
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'lap_data')
+        ON CONFLICT (session_uid, car_idx, lap_num) DO UPDATE SET
+            lap_time_in_ms = excluded.lap_time_in_ms,
+            sector1_in_ms = excluded.sector1_in_ms,
+            sector2_in_ms = excluded.sector2_in_ms,
+            sector3_in_ms = excluded.sector3_in_ms,
+            valid = excluded.valid
+        WHERE laps.source = 'lap_data'",
+    )?;
+
+    for (car_idx, (prev, cur)) in previous.iter().zip(current.iter()).enumerate() {
+        let lap_time = cur.last_lap_time_in_ms;
+        if cur.current_lap_num <= prev.current_lap_num || prev.current_lap_num == 0 || lap_time == 0
+        {
+            continue;
+        }
+
+        let sector1 = LapTime::from(prev.sector1_time()).as_millis();
+        let sector2 = LapTime::from(prev.sector2_time()).as_millis();
+        let sector3 = lap_time.saturating_sub(sector1 + sector2);
+
+        stmt.execute(params![
+            session_uid,
+            car_idx as u8,
+            { prev.current_lap_num },
+            lap_time,
+            sector1,
+            sector2,
+            sector3,
+            prev.current_lap_invalid == 0,
+        ])?;
+    }
+    Ok(())
+}
+
+fn write_session_history(
+    conn: &Connection,
+    session_uid: i64,
+    p: &PacketSessionHistoryData,
+) -> Result<(), StoreError> {
+    let mut laps = conn.prepare_cached(
+        "INSERT INTO laps (
+            session_uid, car_idx, lap_num, lap_time_in_ms,
+            sector1_in_ms, sector2_in_ms, sector3_in_ms, valid, source
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'history')
+        ON CONFLICT DO UPDATE SET
+            lap_time_in_ms = excluded.lap_time_in_ms,
+            sector1_in_ms = excluded.sector1_in_ms,
+            sector2_in_ms = excluded.sector2_in_ms,
+            sector3_in_ms = excluded.sector3_in_ms,
+            valid = excluded.valid,
+            source = excluded.source",
+    )?;
+
+    let history = p.lap_history_data;
+    for (i, lap) in history.iter().enumerate().take(p.num_laps as usize) {
+        // The last entry is the lap in progress
+        if lap.lap_time_in_ms == 0 {
+            continue;
+        }
+
+        laps.execute(params![
+            session_uid,
+            { p.car_idx },
+            (i + 1) as u8,
+            { lap.lap_time_in_ms },
+            LapTime::from(lap.sector1_time()).as_millis(),
+            LapTime::from(lap.sector2_time()).as_millis(),
+            LapTime::from(lap.sector3_time()).as_millis(),
+            lap.validity().contains(LapValidFlags::LAP),
+        ])?;
+    }
+
+    let stints = p.tyre_stints_history_data;
+    write_stints(
+        conn,
+        session_uid,
+        p.car_idx,
+        stints
+            .iter()
+            .take(p.num_tyre_stints as usize)
+            .map(|s| (s.end_lap, s.tyre_actual_compound, s.tyre_visual_compound)),
+    )
+}
+
+/// Stints as `(end_lap, actual_compound, visual_compound)`, end lap 255 meaning still running
+fn write_stints(
+    conn: &Connection,
+    session_uid: i64,
+    car_idx: u8,
+    stints: impl Iterator<Item = (u8, u8, u8)>,
+) -> Result<(), StoreError> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO stints (
+            session_uid, car_idx, stint_num, end_lap, actual_compound, visual_compound
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+
+    for (i, (end_lap, actual, visual)) in stints.enumerate() {
+        let end_lap = (end_lap != 255).then_some(end_lap);
+        stmt.execute(params![
+            session_uid,
+            car_idx,
+            (i + 1) as u8,
+            end_lap,
+            actual,
+            visual
+        ])?;
+    }
+    Ok(())
+}
+
+fn write_event(conn: &Connection, session_uid: i64, p: &PacketEventData) -> Result<(), StoreError> {
+    let Some(event) = p.event() else {
+        return Ok(());
+    };
+
+    let (vehicle_idx, other_vehicle_idx): (i64, Option<u8>) = match event {
+        Event::Buttons(_) => return Ok(()),
+        Event::FastestLap(e) => (e.vehicle_idx.into(), None),
+        Event::Retirement(e) => (e.vehicle_idx.into(), None),
+        Event::TeamMateInPits(e) => (e.vehicle_idx.into(), None),
+        Event::RaceWinner(e) => (e.vehicle_idx.into(), None),
+        Event::Penalty(e) => (e.vehicle_idx.into(), Some(e.other_vehicle_idx)),
+        Event::SpeedTrap(e) => (e.vehicle_idx.into(), None),
+        Event::DriveThroughServed(e) => (e.vehicle_idx.into(), None),
+        Event::StopGoServed(e) => (e.vehicle_idx.into(), None),
+        Event::Overtake(e) => (
+            e.overtaking_vehicle_idx.into(),
+            Some(e.being_overtaken_vehicle_idx),
+        ),
+        Event::Collision(e) => (e.vehicle1_idx.into(), Some(e.vehicle2_idx)),
+        _ => (-1, None),
+    };
+
+    let header = p.header;
+    conn.prepare_cached(
+        "INSERT OR IGNORE INTO events (
+            session_uid, frame, session_time, code, vehicle_idx, other_vehicle_idx
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?
+    .execute(params![
+        session_uid,
+        { header.overall_frame_identifier },
+        { header.session_time },
+        p.code(),
+        vehicle_idx,
+        other_vehicle_idx,
+    ])?;
+
+    if let Event::Penalty(e) = event {
+        conn.prepare_cached(
+            "INSERT OR IGNORE INTO penalties (
+                session_uid, frame, vehicle_idx, penalty_type, infringement_type,
+                other_vehicle_idx, time, lap_num, places_gained
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?
+        .execute(params![
+            session_uid,
+            { header.overall_frame_identifier },
+            { e.vehicle_idx },
+            { e.penalty_type },
+            { e.infringement_type },
+            { e.other_vehicle_idx },
+            { e.time },
+            { e.lap_num },
+            { e.places_gained },
+        ])?;
+    }
+
+    Ok(())
+}
+
+fn write_final_classification(
+    conn: &Connection,
+    session_uid: i64,
+    p: &PacketFinalClassificationData,
+) -> Result<(), StoreError> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO final_classifications (
+            session_uid, car_idx, position, num_laps, grid_position, points, num_pit_stops,
+            result_status, result_reason, best_lap_time_in_ms, total_race_time,
+            penalties_time, num_penalties
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+    )?;
+
+    let classification = p.classification_data;
+    for (car_idx, result) in classification.iter().enumerate() {
+        if !ResultStatus::try_from_id(result.result_status).is_some_and(|s| s.is_participating()) {
+            continue;
+        }
+        stmt.execute(params![
+            session_uid,
+            car_idx as u8,
+            { result.position },
+            { result.num_laps },
+            { result.grid_position },
+            { result.points },
+            { result.num_pit_stops },
+            { result.result_status },
+            { result.result_reason },
+            { result.best_lap_time_in_ms },
+            { result.total_race_time },
+            { result.penalties_time },
+            { result.num_penalties },
+        ])?;
+
+        let (actual, visual, end_laps) = (
+            result.tyre_stints_actual,
+            result.tyre_stints_visual,
+            result.tyre_stints_end_laps,
+        );
+        write_stints(
+            conn,
+            session_uid,
+            car_idx as u8,
+            (0..(result.num_tyre_stints as usize).min(MAX_TYRE_STINTS))
+                .map(|i| (end_laps[i], actual[i], visual[i])),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::TrackId;
+
+    fn session(session_uid: u64, track: TrackId) -> AnyRawPacket {
+        let mut packet: PacketSessionData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        packet.track_id = track.id() as i8;
+        AnyRawPacket::Session(packet)
+    }
+
+    fn participants(session_uid: u64, names: &[&str]) -> AnyRawPacket {
+        let mut packet: PacketParticipantsData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        packet.num_active_cars = names.len() as u8;
+        for (participant, name) in packet.participants.iter_mut().zip(names) {
+            participant.name[..name.len()].copy_from_slice(name.as_bytes());
+        }
+        AnyRawPacket::Participants(packet)
+    }
+
+    /// Car 0 on `lap_num`, having done the previous lap in `last_lap_time_in_ms`
+    fn lap(session_uid: u64, lap_num: u8, last_lap_time_in_ms: u32) -> AnyRawPacket {
+        let mut packet: PacketLapData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = session_uid;
+        packet.lap_data[0].current_lap_num = lap_num;
+        packet.lap_data[0].last_lap_time_in_ms = last_lap_time_in_ms;
+        packet.lap_data[0].sector1_time_ms_part = 30_000;
+        AnyRawPacket::Lap(packet)
+    }
+
+    fn recording(session_uid: u64, lap_times: &[u32]) -> Vec<Result<AnyRawPacket, PacketError>> {
+        let mut packets = vec![
+            Ok(session(session_uid, TrackId::Monza)),
+            Ok(participants(session_uid, &["Player"])),
+            Ok(lap(session_uid, 1, 0)),
+        ];
+        for (i, &time) in lap_times.iter().enumerate() {
+            packets.push(Ok(lap(session_uid, i as u8 + 2, time)));
+        }
+        packets
+    }
+
+    #[test]
+    fn reconstructs_laps_from_lap_packets() {
+        let mut store = SessionStore::open_in_memory().unwrap();
+        store.ingest(recording(1, &[90_000, 89_000])).unwrap();
+
+        let laps = store.laps(1, 0).unwrap();
+        assert_eq!(laps.len(), 2);
+        assert_eq!(laps[1].lap_num, 2);
+        assert_eq!(laps[1].lap_time_in_ms, 89_000);
+        assert_eq!(laps[1].sector_times_in_ms, [30_000, 0, 59_000]);
+    }
+
+    #[test]
+    fn ingesting_a_recording_twice_is_a_no_op() {
+        let mut store = SessionStore::open_in_memory().unwrap();
+        store.ingest(recording(1, &[90_000, 89_000])).unwrap();
+        let laps = store.laps(1, 0).unwrap();
+
+        assert_eq!(store.ingest(recording(1, &[90_000, 89_000])).unwrap(), 5);
+        assert_eq!(store.laps(1, 0).unwrap(), laps);
+        assert_eq!(store.sessions().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn failed_ingest_leaves_the_lap_state_as_it_was() {
+        let mut store = SessionStore::open_in_memory().unwrap();
+        store.push(&lap(1, 1, 0)).unwrap();
+
+        let packets = vec![Ok(lap(1, 2, 90_000)), Err(PacketError::InvalidData)];
+        assert!(store.ingest(packets).is_err());
+        assert!(store.laps(1, 0).unwrap().is_empty());
+
+        // The rolled back packet arriving live still completes lap 1
+        store.push(&lap(1, 2, 90_000)).unwrap();
+        assert_eq!(store.laps(1, 0).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn a_lap_re_driven_after_a_flashback_replaces_the_first_attempt() {
+        let mut store = SessionStore::open_in_memory().unwrap();
+        store.ingest(recording(1, &[90_000])).unwrap();
+
+        // Flashback into lap 1, then complete it again
+        store.push(&lap(1, 1, 0)).unwrap();
+        store.push(&lap(1, 2, 88_000)).unwrap();
+
+        let laps = store.laps(1, 0).unwrap();
+        assert_eq!(laps.len(), 1);
+        assert_eq!(laps[0].lap_time_in_ms, 88_000);
+    }
+
+    #[test]
+    fn skips_empty_participant_and_classification_slots() {
+        let mut store = SessionStore::open_in_memory().unwrap();
+
+        let mut packet: PacketParticipantsData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.num_active_cars = 2;
+        packet.participants[0].name[..3].copy_from_slice(b"One");
+        packet.participants[2].name[..5].copy_from_slice(b"Three");
+        store.push(&AnyRawPacket::Participants(packet)).unwrap();
+
+        let mut packet: PacketFinalClassificationData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.num_cars = 2;
+        packet.classification_data[0].result_status = ResultStatus::Finished.id();
+        packet.classification_data[2].result_status = ResultStatus::Retired.id();
+        packet.classification_data[3].result_status = ResultStatus::Inactive.id();
+        store
+            .push(&AnyRawPacket::FinalClassification(packet))
+            .unwrap();
+
+        let car_indices = |table: &str| -> Vec<u8> {
+            let sql = format!("SELECT car_idx FROM {} ORDER BY car_idx", table);
+            let mut stmt = store.connection().prepare(&sql).unwrap();
+            stmt.query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+        assert_eq!(car_indices("participants"), [0, 2]);
+        assert_eq!(car_indices("final_classifications"), [0, 2]);
+    }
+
+    #[test]
+    fn best_laps_are_per_session_and_car() {
+        let mut store = SessionStore::open_in_memory().unwrap();
+        store.ingest(recording(1, &[90_000, 88_000])).unwrap();
+        store.ingest(recording(2, &[89_000, 91_000])).unwrap();
+
+        let best = store.best_laps_by_track(TrackId::Monza, 10).unwrap();
+        let laps: Vec<_> = best
+            .iter()
+            .map(|b| {
+                (
+                    b.session_uid,
+                    b.driver.as_str(),
+                    b.lap.lap_num,
+                    b.lap.lap_time_in_ms,
+                )
+            })
+            .collect();
+        assert_eq!(laps, [(1, "Player", 2, 88_000), (2, "Player", 1, 89_000)]);
+
+        assert!(
+            store
+                .best_laps_by_track(TrackId::Melbourne, 10)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
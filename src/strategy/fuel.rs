@@ -0,0 +1,444 @@
+use crate::{
+    constants::FuelMix,
+    packet::{AnyRawPacket, PacketConsumer},
+    raw::{PacketCarStatusData, PacketLapData, PacketSessionData, constants::MAX_NUM_CARS},
+};
+
+/// Number of `fuel_mix` modes, 0 = lean, 1 = standard, 2 = rich, 3 = max
+const NUM_FUEL_MIXES: usize = 4;
+
+/// Fuel burnt over a completed lap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FuelLap {
+    pub car_idx: u8,
+    pub lap_num: u8,
+    /// Fuel used over the lap (kg)
+    pub fuel_used: f32,
+    /// Fuel in tank when the lap was completed (kg)
+    pub fuel_in_tank: f32,
+    /// Mix the car covered most of the lap in, `None` if no distance was attributed
+    pub fuel_mix: Option<u8>,
+}
+
+/// Average consumption in one `fuel_mix` mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MixConsumption {
+    pub fuel_mix: u8,
+    /// Fuel per lap (kg) at the session's track length
+    pub fuel_per_lap: f32,
+    /// Distance covered in this mode, in laps
+    pub laps: f32,
+}
+
+/// Projection of the fuel left at the chequered flag
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FuelPrediction {
+    pub fuel_in_tank: f32,
+    pub fuel_per_lap: f32,
+    /// Laps left to race, including the remainder of the current one
+    pub laps_to_go: f32,
+    /// Fuel (kg) expected at the flag, negative when the car will run dry
+    pub margin: f32,
+    /// `margin` expressed in laps
+    pub margin_laps: f32,
+}
+
+/// (fuel, distance) burnt in one mode
+#[derive(Debug, Clone, Copy, Default)]
+struct MixSample {
+    fuel: f32,
+    distance: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LapRecord {
+    lap: FuelLap,
+    mixes: [MixSample; NUM_FUEL_MIXES],
+}
+
+#[derive(Debug, Clone, Copy)]
+struct StatusSample {
+    fuel_in_tank: f32,
+    fuel_mix: u8,
+    total_distance: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CarFuelState {
+    lap_num: u8,
+    lap_distance: f32,
+    total_distance: f32,
+    fuel_capacity: f32,
+    last_sample: Option<StatusSample>,
+    lap_start_fuel: Option<f32>,
+    /// Consumption per mode over the lap in progress
+    mixes: [MixSample; NUM_FUEL_MIXES],
+    laps: Vec<LapRecord>,
+}
+
+impl CarFuelState {
+    fn complete_lap(&mut self, car_idx: u8) -> Option<FuelLap> {
+        let mixes = std::mem::take(&mut self.mixes);
+        let fuel_in_tank = self.last_sample?.fuel_in_tank;
+        let start = self.lap_start_fuel.replace(fuel_in_tank)?;
+
+        let fuel_mix = mixes
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.distance > 0.0)
+            .max_by(|(_, a), (_, b)| a.distance.total_cmp(&b.distance))
+            .map(|(mix, _)| mix as u8);
+
+        let lap = FuelLap {
+            car_idx,
+            lap_num: self.lap_num,
+            fuel_used: (start - fuel_in_tank).max(0.0),
+            fuel_in_tank,
+            fuel_mix,
+        };
+        self.laps.push(LapRecord { lap, mixes });
+        Some(lap)
+    }
+
+    /// Forgets everything recorded from `lap_num` onwards
+    fn rewind_to(&mut self, lap_num: u8) {
+        self.laps.retain(|record| record.lap.lap_num < lap_num);
+        self.lap_start_fuel = None;
+    }
+}
+
+/// Measures fuel burn per lap and per `fuel_mix` mode.
+///
+/// Laps are delimited by the lap packets and the fuel comes from the car status packets.
+/// Fuel burnt between two status samples is attributed to the mix of the earlier sample,
+/// together with the distance covered meanwhile.
+///
+/// Flashbacks are detected from the session time going backwards. The lap in progress
+/// is discarded, and if the car went back to an earlier lap, so are the laps that will
+/// be re-driven. Measuring resumes from the next line crossing.
+#[derive(Debug, Clone, Default)]
+pub struct FuelTracker {
+    session_uid: Option<u64>,
+    session_time: f32,
+    total_laps: u8,
+    track_length: Option<f32>,
+    cars: [CarFuelState; MAX_NUM_CARS],
+}
+
+impl FuelTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::new();
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    fn rewind_on_flashback(&mut self, session_time: f32) {
+        if session_time < self.session_time {
+            for car in &mut self.cars {
+                car.last_sample = None;
+                car.lap_start_fuel = None;
+                car.mixes = Default::default();
+            }
+        }
+        self.session_time = session_time;
+    }
+
+    pub fn push_session(&mut self, packet: &PacketSessionData) {
+        self.reset_on_new_session(packet.header.session_uid);
+        self.total_laps = packet.total_laps;
+        self.track_length = Some(packet.track_length as f32).filter(|&l| l > 0.0);
+    }
+
+    pub fn push_car_status(&mut self, packet: &PacketCarStatusData) {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let statuses = packet.car_status_data;
+        for (car, status) in self.cars.iter_mut().zip(statuses.iter()) {
+            let sample = StatusSample {
+                fuel_in_tank: status.fuel_in_tank,
+                fuel_mix: status.fuel_mix,
+                total_distance: car.total_distance,
+            };
+            car.fuel_capacity = status.fuel_capacity;
+
+            if let Some(last) = car.last_sample.replace(sample) {
+                let fuel = last.fuel_in_tank - sample.fuel_in_tank;
+                let distance = sample.total_distance - last.total_distance;
+                if fuel >= 0.0
+                    && distance >= 0.0
+                    && let Some(mix) = car.mixes.get_mut(last.fuel_mix as usize)
+                {
+                    mix.fuel += fuel;
+                    mix.distance += distance;
+                }
+            }
+        }
+    }
+
+    /// Returns the laps completed by this packet
+    pub fn push_lap_data(&mut self, packet: &PacketLapData) -> Vec<FuelLap> {
+        let header = packet.header;
+        self.reset_on_new_session(header.session_uid);
+        self.rewind_on_flashback(header.session_time);
+
+        let mut laps = Vec::new();
+        let lap_data = packet.lap_data;
+        for (idx, (car, lap)) in self.cars.iter_mut().zip(lap_data.iter()).enumerate() {
+            car.lap_distance = lap.lap_distance;
+            car.total_distance = lap.total_distance;
+
+            let lap_num = lap.current_lap_num;
+            if lap_num < car.lap_num {
+                car.rewind_to(lap_num);
+            } else if lap_num > car.lap_num && car.lap_num > 0 {
+                // The first lap seen is partial, measuring starts at the next line crossing
+                laps.extend(car.complete_lap(idx as u8));
+            }
+            car.lap_num = lap_num;
+        }
+
+        laps
+    }
+
+    /// Completed laps of a car, in lap order
+    pub fn laps(&self, car_idx: u8) -> Vec<FuelLap> {
+        self.cars
+            .get(car_idx as usize)
+            .map(|car| car.laps.iter().map(|record| record.lap).collect())
+            .unwrap_or_default()
+    }
+
+    /// Average fuel used per completed lap, in any mix
+    pub fn fuel_per_lap(&self, car_idx: u8) -> Option<f32> {
+        let car = self.cars.get(car_idx as usize)?;
+        let laps = car.laps.len();
+        (laps > 0).then(|| car.laps.iter().map(|r| r.lap.fuel_used).sum::<f32>() / laps as f32)
+    }
+
+    /// Consumption in every mode the car has been driven in, by mode.
+    /// Requires the track length from the session packet.
+    pub fn consumption_by_mix(&self, car_idx: u8) -> Vec<MixConsumption> {
+        let (Some(car), Some(track_length)) = (self.cars.get(car_idx as usize), self.track_length)
+        else {
+            return Vec::new();
+        };
+
+        let mut totals = [MixSample::default(); NUM_FUEL_MIXES];
+        for mixes in car.laps.iter().map(|r| &r.mixes) {
+            for (total, mix) in totals.iter_mut().zip(mixes) {
+                total.fuel += mix.fuel;
+                total.distance += mix.distance;
+            }
+        }
+
+        totals
+            .iter()
+            .enumerate()
+            .filter(|(_, total)| total.distance > 0.0)
+            .map(|(mix, total)| MixConsumption {
+                fuel_mix: mix as u8,
+                fuel_per_lap: total.fuel / total.distance * track_length,
+                laps: total.distance / track_length,
+            })
+            .collect()
+    }
+
+    /// Fuel left at the flag at the average rate so far, for race sessions with a lap count
+    pub fn prediction(&self, car_idx: u8) -> Option<FuelPrediction> {
+        let car = self.cars.get(car_idx as usize)?;
+        let fuel_in_tank = car.last_sample?.fuel_in_tank;
+        let fuel_per_lap = self.fuel_per_lap(car_idx).filter(|&f| f > 0.0)?;
+        if self.total_laps == 0 || car.lap_num == 0 {
+            return None;
+        }
+
+        let lap_fraction = match self.track_length {
+            Some(length) => (car.lap_distance / length).clamp(0.0, 1.0),
+            None => 0.0,
+        };
+        let laps_to_go =
+            (self.total_laps as f32 - car.lap_num as f32 + 1.0 - lap_fraction).max(0.0);
+        let margin = fuel_in_tank - laps_to_go * fuel_per_lap;
+
+        Some(FuelPrediction {
+            fuel_in_tank,
+            fuel_per_lap,
+            laps_to_go,
+            margin,
+            margin_laps: margin / fuel_per_lap,
+        })
+    }
+
+    /// Starting fuel (kg, as in `CarSetupData::fuel_load`) for a `race_laps` race on this
+    /// track, with `margin_laps` of extra fuel.
+    ///
+    /// Uses the standard mix rate when it has been measured and the overall average
+    /// otherwise, and is capped to the car's fuel capacity.
+    pub fn recommended_fuel_load(
+        &self,
+        car_idx: u8,
+        race_laps: u8,
+        margin_laps: f32,
+    ) -> Option<f32> {
+        let car = self.cars.get(car_idx as usize)?;
+        let fuel_per_lap = self
+            .consumption_by_mix(car_idx)
+            .iter()
+            .find(|mix| mix.fuel_mix == FuelMix::Standard.id() && mix.laps >= 1.0)
+            .map(|mix| mix.fuel_per_lap)
+            .or_else(|| self.fuel_per_lap(car_idx))?;
+
+        let load = fuel_per_lap * (race_laps as f32 + margin_laps.max(0.0));
+        Some(match car.fuel_capacity > 0.0 {
+            true => load.min(car.fuel_capacity),
+            false => load,
+        })
+    }
+}
+
+impl PacketConsumer for FuelTracker {
+    type Event = FuelLap;
+
+    fn consume(&mut self, packet: &AnyRawPacket) -> Vec<FuelLap> {
+        match packet {
+            AnyRawPacket::Session(p) => {
+                self.push_session(p);
+                Vec::new()
+            }
+            AnyRawPacket::CarStatus(p) => {
+                self.push_car_status(p);
+                Vec::new()
+            }
+            AnyRawPacket::Lap(p) => self.push_lap_data(p),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Feed {
+        tracker: FuelTracker,
+        session_time: f32,
+    }
+
+    impl Feed {
+        fn new(total_laps: u8) -> Self {
+            let mut session: PacketSessionData = bytemuck::Zeroable::zeroed();
+            session.header.session_uid = 1;
+            session.total_laps = total_laps;
+            session.track_length = 5000;
+
+            let mut tracker = FuelTracker::new();
+            tracker.push_session(&session);
+            Self {
+                tracker,
+                session_time: 0.0,
+            }
+        }
+
+        /// Car 0 at `distance` into the race with `fuel` left, `dt` seconds later
+        fn sample(&mut self, dt: f32, distance: f32, fuel: f32, fuel_mix: u8) -> Vec<FuelLap> {
+            self.session_time += dt;
+
+            let mut lap: PacketLapData = bytemuck::Zeroable::zeroed();
+            lap.header.session_uid = 1;
+            lap.header.session_time = self.session_time;
+            lap.lap_data[0].current_lap_num = (distance / 5000.0) as u8 + 1;
+            lap.lap_data[0].lap_distance = distance % 5000.0;
+            lap.lap_data[0].total_distance = distance;
+            let laps = self.tracker.push_lap_data(&lap);
+
+            let mut status: PacketCarStatusData = bytemuck::Zeroable::zeroed();
+            status.header.session_uid = 1;
+            status.header.session_time = self.session_time;
+            status.car_status_data[0].fuel_in_tank = fuel;
+            status.car_status_data[0].fuel_mix = fuel_mix;
+            status.car_status_data[0].fuel_capacity = 110.0;
+            self.tracker.push_car_status(&status);
+            laps
+        }
+    }
+
+    #[test]
+    fn measures_fuel_per_lap_and_mix_from_the_first_line_crossing() {
+        let mut feed = Feed::new(10);
+        feed.sample(1.0, 2500.0, 50.0, 1);
+        assert!(feed.sample(1.0, 5000.0, 49.0, 1).is_empty());
+        feed.sample(1.0, 7500.0, 48.0, 1);
+        feed.sample(1.0, 9000.0, 47.0, 3);
+
+        let laps = feed.sample(1.0, 10000.0, 46.0, 3);
+        assert_eq!(laps.len(), 1);
+        assert_eq!(laps[0].lap_num, 2);
+        assert_eq!(laps[0].fuel_used, 3.0);
+        assert_eq!(laps[0].fuel_mix, Some(1));
+        assert_eq!(feed.tracker.fuel_per_lap(0), Some(3.0));
+
+        // Fuel is read at the sample before the line, the lap covered 6500 m in standard mix
+        let mixes = feed.tracker.consumption_by_mix(0);
+        assert_eq!(mixes.len(), 1);
+        assert_eq!(mixes[0].fuel_mix, 1);
+        assert_eq!(mixes[0].laps, 1.3);
+        assert!((mixes[0].fuel_per_lap - 3.0 / 1.3).abs() < 1e-4);
+    }
+
+    #[test]
+    fn discards_the_lap_in_progress_on_a_flashback() {
+        let mut feed = Feed::new(10);
+        feed.sample(1.0, 4000.0, 50.0, 1);
+        feed.sample(1.0, 5000.0, 49.0, 1);
+        feed.sample(1.0, 7500.0, 48.0, 1);
+
+        // Flashback within lap 2, the lap's fuel at its start is no longer known
+        feed.sample(-1.5, 6000.0, 48.5, 1);
+        assert!(feed.sample(1.0, 10000.0, 47.0, 1).is_empty());
+        assert!(feed.tracker.laps(0).is_empty());
+
+        let laps = feed.sample(1.0, 15000.0, 45.0, 1);
+        assert_eq!(laps[0].lap_num, 3);
+        assert_eq!(laps[0].fuel_used, 1.5);
+    }
+
+    #[test]
+    fn drops_re_driven_laps_when_flashing_back_a_lap() {
+        let mut feed = Feed::new(10);
+        feed.sample(1.0, 2500.0, 52.0, 1);
+        feed.sample(1.0, 5000.0, 50.0, 1);
+        feed.sample(1.0, 10000.0, 48.0, 1);
+        feed.sample(1.0, 15000.0, 46.0, 1);
+        assert_eq!(feed.tracker.laps(0).len(), 2);
+
+        // Back into lap 3, which will be driven again
+        feed.sample(-1.5, 12000.0, 47.2, 1);
+        let laps: Vec<_> = feed.tracker.laps(0).iter().map(|l| l.lap_num).collect();
+        assert_eq!(laps, [2]);
+    }
+
+    #[test]
+    fn predicts_the_fuel_margin_and_recommends_a_load() {
+        let mut feed = Feed::new(5);
+        feed.sample(1.0, 2500.0, 12.0, 1);
+        feed.sample(1.0, 5000.0, 10.0, 1);
+        feed.sample(1.0, 10000.0, 8.0, 1);
+        feed.sample(1.0, 12500.0, 7.0, 1);
+
+        // Half of lap 3 and laps 4 and 5 to go at 2 kg a lap
+        let prediction = feed.tracker.prediction(0).unwrap();
+        assert_eq!(prediction.laps_to_go, 2.5);
+        assert_eq!(prediction.margin, 2.0);
+        assert_eq!(prediction.margin_laps, 1.0);
+
+        assert_eq!(feed.tracker.recommended_fuel_load(0, 50, 1.0), Some(102.0));
+        assert_eq!(feed.tracker.recommended_fuel_load(0, 60, 1.0), Some(110.0));
+    }
+}
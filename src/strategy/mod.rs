@@ -0,0 +1,5 @@
+pub mod fuel;
+pub mod tyres;
+
+pub use fuel::*;
+pub use tyres::*;
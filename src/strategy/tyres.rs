@@ -0,0 +1,586 @@
+use std::collections::HashMap;
+
+use crate::{
+    constants::{ActualTyreCompound, Formula, VisualTyreCompound},
+    packet::AnyRawPacket,
+    raw::{
+        PacketCarDamageData, PacketLapData, PacketSessionData, PacketSessionHistoryData,
+        PacketTyreSetsData, TyreSetData,
+        constants::{MAX_NUM_CARS, MAX_TYRE_SETS},
+    },
+};
+
+/// Measured wear rate of an actual compound
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Degradation {
+    pub actual_tyre_compound: ActualTyreCompound,
+    /// Wear of the most worn tyre, in percent per lap
+    pub wear_per_lap: f32,
+    /// Number of laps the rate was measured over
+    pub laps: u32,
+}
+
+/// Projected life of the set a car has fitted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TyreLife {
+    pub actual_tyre_compound: ActualTyreCompound,
+    pub visual_tyre_compound: VisualTyreCompound,
+    /// Wear of the most worn tyre (percentage)
+    pub wear: f32,
+    /// `None` until the compound has been measured over a full lap
+    pub wear_per_lap: Option<f32>,
+    /// Wear (percentage) at which the set is worn out, derived from its usable life
+    pub wear_limit: f32,
+    /// Laps until the wear limit is reached at the measured rate
+    pub projected_laps: Option<f32>,
+    /// Laps left according to the game, from the tyre sets packet
+    pub life_span: Option<u8>,
+}
+
+/// A candidate pit stop, see [`TyreStrategy::pit_options`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PitOption {
+    /// Lap on which the car pits
+    pub pit_lap: u8,
+    /// Index into the car's tyre sets
+    pub tyre_set_idx: u8,
+    pub actual_tyre_compound: ActualTyreCompound,
+    pub visual_tyre_compound: VisualTyreCompound,
+    pub laps_on_new_set: u8,
+    /// Projected wear of the new set at the finish, `None` when the compound hasn't been measured
+    pub projected_wear: Option<f32>,
+    /// Both sets are projected to stay within the wear limit
+    pub feasible: bool,
+    /// Estimated race time relative to continuing at the fitted set's current pace,
+    /// excluding the pit stop itself
+    pub time_delta_ms: i32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct StintSample {
+    actual_tyre_compound: ActualTyreCompound,
+    start_lap: u8,
+    start_wear: f32,
+    lap: u8,
+    wear: f32,
+}
+
+impl StintSample {
+    fn laps(&self) -> u32 {
+        self.lap.saturating_sub(self.start_lap) as u32
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct CarTyreState {
+    lap_num: u8,
+    /// Wear of the most worn tyre
+    wear: Option<f32>,
+    /// (actual, visual) compound ids from the stint history
+    stint_compound: Option<(u8, u8)>,
+    tyre_sets: Option<([TyreSetData; MAX_TYRE_SETS], u8)>,
+    stint: Option<StintSample>,
+}
+
+impl CarTyreState {
+    /// (actual, visual) compound fitted, preferring the tyre sets packet
+    fn fitted_compound(
+        &self,
+        formula: Formula,
+    ) -> Option<(ActualTyreCompound, VisualTyreCompound)> {
+        let (actual, visual) = self
+            .fitted_set()
+            .map(|set| (set.actual_tyre_compound, set.visual_tyre_compound))
+            .or(self.stint_compound)?;
+        Some((
+            ActualTyreCompound::try_from_id(formula, actual)?,
+            VisualTyreCompound::try_from_id(formula, visual)?,
+        ))
+    }
+
+    fn fitted_set(&self) -> Option<&TyreSetData> {
+        let (sets, fitted_idx) = self.tyre_sets.as_ref()?;
+        sets.get(*fitted_idx as usize)
+    }
+}
+
+/// Tyre degradation and pit stop planning.
+///
+/// Wear comes from the car damage packet and is sampled every time a car starts a new
+/// lap, attributed to the compound fitted according to the tyre sets packet (or the
+/// stint history when no tyre sets have been received for that car). A stint ends when
+/// the compound changes or the wear drops, i.e. new tyres were fitted. Compounds are
+/// decoded with the formula of the session packet, so nothing is measured before it.
+///
+/// Wear is always that of the most worn tyre, since it is the one that limits the stint.
+/// A set is worn out at the wear it is projected to have at the end of its
+/// `usable_life`, going by the wear it took over the laps already used. Sets not used yet
+/// take the limit of a used set of the same compound, or the default limit.
+#[derive(Debug, Clone)]
+pub struct TyreStrategy {
+    session_uid: Option<u64>,
+    formula: Option<Formula>,
+    wear_limit: f32,
+    pit_window: Option<(u8, u8)>,
+    cars: [CarTyreState; MAX_NUM_CARS],
+    /// Total (wear, laps) of the finished stints per actual compound
+    finished: HashMap<ActualTyreCompound, (f32, u32)>,
+}
+
+impl Default for TyreStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TyreStrategy {
+    /// Wear limit of sets whose usable life hasn't been measured
+    pub const DEFAULT_WEAR_LIMIT: f32 = 70.0;
+
+    pub fn new() -> Self {
+        Self::with_wear_limit(Self::DEFAULT_WEAR_LIMIT)
+    }
+
+    /// Wear (percentage) at which a set is considered worn out, when it can't be derived
+    /// from the tyre sets packet
+    pub fn with_wear_limit(percent: f32) -> Self {
+        Self {
+            session_uid: None,
+            formula: None,
+            wear_limit: percent.clamp(1.0, 100.0),
+            pit_window: None,
+            cars: [CarTyreState::default(); MAX_NUM_CARS],
+            finished: HashMap::new(),
+        }
+    }
+
+    fn reset_on_new_session(&mut self, session_uid: u64) {
+        if self.session_uid != Some(session_uid) {
+            *self = Self::with_wear_limit(self.wear_limit);
+            self.session_uid = Some(session_uid);
+        }
+    }
+
+    pub fn push(&mut self, packet: &AnyRawPacket) {
+        match packet {
+            AnyRawPacket::Session(p) => self.push_session(p),
+            AnyRawPacket::Lap(p) => self.push_lap_data(p),
+            AnyRawPacket::CarDamage(p) => self.push_car_damage(p),
+            AnyRawPacket::TyreSets(p) => self.push_tyre_sets(p),
+            AnyRawPacket::SessionHistory(p) => self.push_session_history(p),
+            _ => {}
+        }
+    }
+
+    pub fn push_session(&mut self, packet: &PacketSessionData) {
+        self.reset_on_new_session(packet.header.session_uid);
+        self.formula = packet.formula();
+        let (ideal, latest) = (
+            packet.pit_stop_window_ideal_lap,
+            packet.pit_stop_window_latest_lap,
+        );
+        self.pit_window = (ideal > 0 && latest >= ideal).then_some((ideal, latest));
+    }
+
+    pub fn push_car_damage(&mut self, packet: &PacketCarDamageData) {
+        self.reset_on_new_session(packet.header.session_uid);
+
+        let damage = packet.car_damage_data;
+        for (car, damage) in self.cars.iter_mut().zip(damage.iter()) {
+            let tyres_wear = damage.tyres_wear;
+            car.wear = Some(tyres_wear.into_iter().fold(0.0, f32::max));
+        }
+    }
+
+    pub fn push_tyre_sets(&mut self, packet: &PacketTyreSetsData) {
+        self.reset_on_new_session(packet.header.session_uid);
+
+        if let Some(car) = self.cars.get_mut(packet.car_idx as usize) {
+            car.tyre_sets = Some((packet.tyre_set_data, packet.fitted_idx));
+        }
+    }
+
+    pub fn push_session_history(&mut self, packet: &PacketSessionHistoryData) {
+        self.reset_on_new_session(packet.header.session_uid);
+
+        let stints = packet.tyre_stints_history_data;
+        let current = stints[..(packet.num_tyre_stints as usize).min(stints.len())].last();
+        if let (Some(car), Some(stint)) = (self.cars.get_mut(packet.car_idx as usize), current) {
+            car.stint_compound = Some((stint.tyre_actual_compound, stint.tyre_visual_compound));
+        }
+    }
+
+    pub fn push_lap_data(&mut self, packet: &PacketLapData) {
+        self.reset_on_new_session(packet.header.session_uid);
+
+        let formula = self.formula;
+        let lap_data = packet.lap_data;
+        for (car, lap) in self.cars.iter_mut().zip(lap_data.iter()) {
+            let lap_num = std::mem::replace(&mut car.lap_num, lap.current_lap_num);
+            if lap.current_lap_num < lap_num {
+                // Flashback, the open stint may contain laps that are no longer driven
+                car.stint = None;
+                continue;
+            }
+            if lap.current_lap_num == lap_num {
+                continue;
+            }
+
+            let fitted = formula.and_then(|formula| car.fitted_compound(formula));
+            let (Some(wear), Some((actual, _))) = (car.wear, fitted) else {
+                continue;
+            };
+
+            let new_stint = match &mut car.stint {
+                Some(stint) if stint.actual_tyre_compound == actual && wear + 0.5 >= stint.wear => {
+                    stint.lap = lap.current_lap_num;
+                    stint.wear = wear;
+                    None
+                }
+                _ => Some(StintSample {
+                    actual_tyre_compound: actual,
+                    start_lap: lap.current_lap_num,
+                    start_wear: wear,
+                    lap: lap.current_lap_num,
+                    wear,
+                }),
+            };
+
+            if let Some(stint) = new_stint
+                && let Some(finished) = car.stint.replace(stint).filter(|s| s.laps() > 0)
+            {
+                let total = self
+                    .finished
+                    .entry(finished.actual_tyre_compound)
+                    .or_default();
+                total.0 += finished.wear - finished.start_wear;
+                total.1 += finished.laps();
+            }
+        }
+    }
+
+    /// Wear rate of a compound over all cars' finished and ongoing stints
+    pub fn degradation(&self, actual_tyre_compound: ActualTyreCompound) -> Option<Degradation> {
+        let (mut wear, mut laps) = self
+            .finished
+            .get(&actual_tyre_compound)
+            .copied()
+            .unwrap_or_default();
+
+        for stint in self.cars.iter().filter_map(|car| car.stint) {
+            if stint.actual_tyre_compound == actual_tyre_compound {
+                wear += stint.wear - stint.start_wear;
+                laps += stint.laps();
+            }
+        }
+
+        (laps > 0).then(|| Degradation {
+            actual_tyre_compound,
+            wear_per_lap: (wear / laps as f32).max(0.0),
+            laps,
+        })
+    }
+
+    /// Wear rates of every compound measured so far, by compound
+    pub fn degradations(&self) -> Vec<Degradation> {
+        let mut compounds: Vec<ActualTyreCompound> = self.finished.keys().copied().collect();
+        compounds.extend(
+            self.cars
+                .iter()
+                .filter_map(|car| car.stint.map(|s| s.actual_tyre_compound)),
+        );
+        compounds.sort_unstable_by_key(|compound| compound.id());
+        compounds.dedup();
+
+        compounds
+            .into_iter()
+            .filter_map(|compound| self.degradation(compound))
+            .collect()
+    }
+
+    pub fn tyre_life(&self, car_idx: u8) -> Option<TyreLife> {
+        let car = self.cars.get(car_idx as usize)?;
+        let (actual, visual) = car.fitted_compound(self.formula?)?;
+        let wear = car.wear?;
+        let wear_per_lap = self.degradation(actual).map(|d| d.wear_per_lap);
+        let wear_limit = match &car.tyre_sets {
+            Some((sets, fitted_idx)) => self.set_wear_limit(sets, *fitted_idx as usize),
+            None => self.wear_limit,
+        };
+
+        Some(TyreLife {
+            actual_tyre_compound: actual,
+            visual_tyre_compound: visual,
+            wear,
+            wear_per_lap,
+            wear_limit,
+            projected_laps: wear_per_lap
+                .filter(|&rate| rate > 0.0)
+                .map(|rate| ((wear_limit - wear) / rate).max(0.0)),
+            life_span: car.fitted_set().map(|set| set.life_span),
+        })
+    }
+
+    /// Ranks every available tyre set and pit lap for a one stop finish, best first.
+    ///
+    /// `remaining_laps` includes the lap the car is on. Pit laps are limited to the
+    /// session's pit window when it has one. Options projected to exceed the wear limit
+    /// on either set are kept but ranked after the feasible ones. The time estimate is
+    /// linear in wear, scaled by comparing the `lap_delta_time` of unused sets of the
+    /// fitted compound with the fitted set; the pit stop itself is the same for every
+    /// option and not included.
+    pub fn pit_options(&self, car_idx: u8, remaining_laps: u8) -> Vec<PitOption> {
+        let Some(car) = self.cars.get(car_idx as usize) else {
+            return Vec::new();
+        };
+        let (Some(formula), Some((sets, fitted_idx)), Some(life)) =
+            (self.formula, car.tyre_sets, self.tyre_life(car_idx))
+        else {
+            return Vec::new();
+        };
+
+        let current_lap = car.lap_num.max(1);
+        let finish_lap = current_lap.saturating_add(remaining_laps.saturating_sub(1));
+        let (first, last) = match self.pit_window {
+            Some((ideal, latest)) if latest >= current_lap => (ideal.max(current_lap), latest),
+            _ => (current_lap, u8::MAX),
+        };
+        let last = last.min(finish_lap.saturating_sub(1));
+
+        let ms_per_wear = self.time_loss_per_wear(&sets, fitted_idx, life.wear);
+        let rate_of =
+            |compound: ActualTyreCompound| self.degradation(compound).map(|d| d.wear_per_lap);
+
+        let mut options = Vec::new();
+        for pit_lap in first..=last {
+            let laps_on_fitted = (pit_lap - current_lap + 1) as f32;
+            let fitted_ok = match life.projected_laps {
+                Some(laps) => laps >= laps_on_fitted,
+                None => life
+                    .life_span
+                    .is_none_or(|span| span as f32 >= laps_on_fitted),
+            };
+
+            for (idx, set) in sets.iter().enumerate() {
+                if set.available == 0 || set.fitted != 0 || idx == fitted_idx as usize {
+                    continue;
+                }
+                let (Some(actual), Some(visual)) = (
+                    set.actual_tyre_compound(formula),
+                    set.visual_tyre_compound(formula),
+                ) else {
+                    continue;
+                };
+
+                let laps_on_new_set = finish_lap - pit_lap;
+                let rate = rate_of(actual);
+                let projected_wear =
+                    rate.map(|rate| set.wear as f32 + rate * laps_on_new_set as f32);
+                let new_ok = match projected_wear {
+                    Some(wear) => wear <= self.set_wear_limit(&sets, idx),
+                    None => set.life_span >= laps_on_new_set,
+                };
+
+                let mut time_delta_ms = set.lap_delta_time as f32 * laps_on_new_set as f32;
+                if let Some(ms_per_wear) = ms_per_wear {
+                    let n = laps_on_fitted;
+                    time_delta_ms +=
+                        ms_per_wear * life.wear_per_lap.unwrap_or(0.0) * n * (n + 1.0) / 2.0;
+                    let m = laps_on_new_set as f32;
+                    time_delta_ms +=
+                        ms_per_wear * rate.unwrap_or(0.0) * m * (m - 1.0).max(0.0) / 2.0;
+                }
+
+                options.push(PitOption {
+                    pit_lap,
+                    tyre_set_idx: idx as u8,
+                    actual_tyre_compound: actual,
+                    visual_tyre_compound: visual,
+                    laps_on_new_set,
+                    projected_wear,
+                    feasible: fitted_ok && new_ok,
+                    time_delta_ms: time_delta_ms.round() as i32,
+                });
+            }
+        }
+
+        let ideal_lap = self.pit_window.map_or(first, |(ideal, _)| ideal);
+        options.sort_by_key(|o| (!o.feasible, o.time_delta_ms, o.pit_lap.abs_diff(ideal_lap)));
+        options
+    }
+
+    /// Wear (percentage) at which a set is worn out: the set's wear scaled from the laps
+    /// used to its `usable_life`, or that of a used set of the same compound, or the default
+    fn set_wear_limit(&self, sets: &[TyreSetData; MAX_TYRE_SETS], set_idx: usize) -> f32 {
+        let end_of_life_wear = |set: &TyreSetData| {
+            let used = set.usable_life.checked_sub(set.life_span)?;
+            (used > 0 && set.wear > 0)
+                .then(|| set.wear as f32 * set.usable_life as f32 / used as f32)
+        };
+
+        let Some(set) = sets.get(set_idx) else {
+            return self.wear_limit;
+        };
+        end_of_life_wear(set)
+            .or_else(|| {
+                sets.iter()
+                    .filter(|other| other.actual_tyre_compound == set.actual_tyre_compound)
+                    .find_map(end_of_life_wear)
+            })
+            .map_or(self.wear_limit, |limit| limit.clamp(1.0, 100.0))
+    }
+
+    /// Lap time lost per percent of wear, from unused sets of the fitted compound
+    fn time_loss_per_wear(
+        &self,
+        sets: &[TyreSetData; MAX_TYRE_SETS],
+        fitted_idx: u8,
+        fitted_wear: f32,
+    ) -> Option<f32> {
+        let fitted = sets.get(fitted_idx as usize)?;
+        let samples: Vec<f32> = sets
+            .iter()
+            .filter(|set| {
+                set.actual_tyre_compound == fitted.actual_tyre_compound
+                    && set.fitted == 0
+                    && (set.wear as f32) < fitted_wear
+            })
+            .map(|set| -(set.lap_delta_time as f32) / (fitted_wear - set.wear as f32))
+            .filter(|ms| *ms > 0.0)
+            .collect();
+
+        (!samples.is_empty()).then(|| samples.iter().sum::<f32>() / samples.len() as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOFT: u8 = 16;
+    const MEDIUM: u8 = 17;
+
+    fn tyre_sets(sets: &[(u8, u8, u8, u8)], fitted_idx: u8) -> PacketTyreSetsData {
+        let mut packet: PacketTyreSetsData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.fitted_idx = fitted_idx;
+        for (i, (data, &(compound, wear, life_span, usable_life))) in
+            packet.tyre_set_data.iter_mut().zip(sets).enumerate()
+        {
+            data.actual_tyre_compound = compound;
+            data.visual_tyre_compound = compound;
+            data.wear = wear;
+            data.life_span = life_span;
+            data.usable_life = usable_life;
+            data.available = 1;
+            data.fitted = (i == fitted_idx as usize) as u8;
+        }
+        packet
+    }
+
+    fn session(formula: Formula) -> PacketSessionData {
+        let mut packet: PacketSessionData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.formula = formula.id();
+        packet
+    }
+
+    fn damage(wear: f32) -> PacketCarDamageData {
+        let mut packet: PacketCarDamageData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.car_damage_data[0].tyres_wear = [wear, wear - 1.0, wear - 2.0, wear - 3.0];
+        packet
+    }
+
+    fn lap(lap_num: u8) -> PacketLapData {
+        let mut packet: PacketLapData = bytemuck::Zeroable::zeroed();
+        packet.header.session_uid = 1;
+        packet.lap_data[0].current_lap_num = lap_num;
+        packet
+    }
+
+    /// Car 0 wearing its fitted set by 3% a lap from 10% on lap 1
+    fn driven(sets: PacketTyreSetsData, laps: u8) -> TyreStrategy {
+        driven_in(Some(Formula::F1Modern), sets, laps)
+    }
+
+    fn driven_in(formula: Option<Formula>, sets: PacketTyreSetsData, laps: u8) -> TyreStrategy {
+        let mut strategy = TyreStrategy::new();
+        if let Some(formula) = formula {
+            strategy.push_session(&session(formula));
+        }
+        strategy.push_tyre_sets(&sets);
+        for lap_num in 1..=laps {
+            strategy.push_car_damage(&damage(10.0 + 3.0 * (lap_num - 1) as f32));
+            strategy.push_lap_data(&lap(lap_num));
+        }
+        strategy
+    }
+
+    #[test]
+    fn measures_wear_per_lap_of_the_most_worn_tyre() {
+        let strategy = driven(tyre_sets(&[(SOFT, 10, 15, 20)], 0), 4);
+
+        let degradation = strategy.degradation(ActualTyreCompound::C5).unwrap();
+        assert_eq!(degradation.wear_per_lap, 3.0);
+        assert_eq!(degradation.laps, 3);
+        assert!(strategy.degradation(ActualTyreCompound::C4).is_none());
+    }
+
+    #[test]
+    fn decodes_compounds_with_the_session_formula() {
+        let life = driven(tyre_sets(&[(SOFT, 10, 15, 20)], 0), 2)
+            .tyre_life(0)
+            .unwrap();
+        assert_eq!(life.actual_tyre_compound, ActualTyreCompound::C5);
+        assert_eq!(life.visual_tyre_compound, VisualTyreCompound::Soft);
+
+        // 16 is not an F2 compound, and nothing can be decoded before the session packet
+        for formula in [Some(Formula::F2), None] {
+            let strategy = driven_in(formula, tyre_sets(&[(SOFT, 10, 15, 20)], 0), 4);
+            assert!(strategy.degradations().is_empty());
+            assert!(strategy.tyre_life(0).is_none());
+        }
+    }
+
+    #[test]
+    fn derives_the_wear_limit_from_the_usable_life() {
+        // 30% wear over the 10 of 20 laps used, so worn out at 60%
+        let strategy = driven(tyre_sets(&[(SOFT, 30, 10, 20)], 0), 4);
+
+        let life = strategy.tyre_life(0).unwrap();
+        assert_eq!(life.wear, 19.0);
+        assert_eq!(life.wear_limit, 60.0);
+        assert_eq!(life.projected_laps, Some((60.0 - 19.0) / 3.0));
+        assert_eq!(life.life_span, Some(10));
+    }
+
+    #[test]
+    fn falls_back_to_the_default_limit_for_unused_compounds() {
+        let sets = tyre_sets(&[(SOFT, 0, 20, 20), (SOFT, 0, 20, 20)], 0);
+        let strategy = driven(sets, 2);
+        assert_eq!(strategy.tyre_life(0).unwrap().wear_limit, 70.0);
+
+        // An unused set takes the limit of a used one of the same compound
+        let sets = tyre_sets(&[(SOFT, 0, 20, 20), (SOFT, 40, 10, 20)], 0);
+        let strategy = driven(sets, 2);
+        assert_eq!(strategy.tyre_life(0).unwrap().wear_limit, 80.0);
+    }
+
+    #[test]
+    fn ranks_feasible_pit_options_first() {
+        let sets = tyre_sets(
+            &[(SOFT, 30, 10, 20), (SOFT, 0, 20, 20), (MEDIUM, 0, 30, 30)],
+            0,
+        );
+        let strategy = driven(sets, 4);
+
+        let options = strategy.pit_options(0, 20);
+        assert!(!options.is_empty());
+        assert!(options[0].feasible);
+        assert!(options.iter().all(|o| o.tyre_set_idx != 0));
+
+        let infeasible = options.iter().position(|o| !o.feasible).unwrap();
+        assert!(options[infeasible..].iter().all(|o| !o.feasible));
+    }
+}